@@ -0,0 +1,10 @@
+use std::io;
+use std::process;
+
+fn main() {
+    let stdin = io::stdin();
+    if let Err(err) = chess::uci::run(stdin.lock(), io::stdout()) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
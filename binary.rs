@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use super::board::Board;
+use super::clock::ClockState;
+use super::geometry::Geometry;
+use super::piece::{Castle, Color, MoveMeta, Piece, PieceEntity, PieceEntityData, Position};
+use super::template::{BoardTemplate, TEMPLATE_VERSION};
+use super::utils::ChessError;
+
+pub const BINARY_MAGIC: [u8; 4] = *b"CHSB";
+pub const BINARY_VERSION: u16 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BinaryEntity {
+    piece: Piece,
+    pos: Position,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BinaryBoard {
+    variant: String,
+    geometry: Geometry,
+    turn: Color,
+    castling: Castle,
+    passant_target: Option<Position>,
+    halfmove: u8,
+    fullmove: u8,
+    history: Vec<MoveMeta>,
+    entities: Vec<BinaryEntity>,
+}
+
+fn encode_error(err: impl std::fmt::Display) -> ChessError {
+    ChessError::InternalError(format!("fail encoding board: {}", err))
+}
+
+fn decode_error(err: impl std::fmt::Display) -> ChessError {
+    ChessError::ParseError(format!("fail decoding board: {}", err))
+}
+
+impl BinaryBoard {
+    fn from_template(template: BoardTemplate) -> Result<Self, ChessError> {
+        let entities = template
+            .entities
+            .into_iter()
+            .map(|entity| {
+                Ok(BinaryEntity {
+                    piece: entity.piece,
+                    pos: entity.pos,
+                    data: serde_json::to_string(&entity.data).map_err(encode_error)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ChessError>>()?;
+
+        Ok(Self {
+            variant: template.variant,
+            geometry: template.geometry,
+            turn: template.turn,
+            castling: template.castling,
+            passant_target: template.passant_target,
+            halfmove: template.halfmove,
+            fullmove: template.fullmove,
+            history: template.history,
+            entities,
+        })
+    }
+
+    fn into_template(self, clock: Option<ClockState>) -> Result<BoardTemplate, ChessError> {
+        let entities = self
+            .entities
+            .into_iter()
+            .map(|entity| {
+                let data: PieceEntityData =
+                    serde_json::from_str(&entity.data).map_err(decode_error)?;
+                Ok(PieceEntity {
+                    piece: entity.piece,
+                    pos: entity.pos,
+                    data,
+                })
+            })
+            .collect::<Result<Vec<_>, ChessError>>()?;
+
+        Ok(BoardTemplate {
+            version: TEMPLATE_VERSION,
+            variant: self.variant,
+            geometry: self.geometry,
+            turn: self.turn,
+            castling: self.castling,
+            passant_target: self.passant_target,
+            halfmove: self.halfmove,
+            fullmove: self.fullmove,
+            history: self.history,
+            entities,
+            clock,
+        })
+    }
+}
+
+impl Board {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ChessError> {
+        let template = self.template();
+        let clock = template.clock.clone();
+        let payload = BinaryBoard::from_template(template)?;
+
+        let mut bytes = Vec::from(BINARY_MAGIC);
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &payload).map_err(encode_error)?;
+        bincode::serialize_into(&mut bytes, &clock).map_err(encode_error)?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChessError> {
+        if bytes.len() < 6 || bytes[..4] != BINARY_MAGIC {
+            return Err(ChessError::ParseError("not a binary board encoding".into()));
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        let mut reader = &bytes[6..];
+        let template = match version {
+            1 | 2 => {
+                let payload: BinaryBoard =
+                    bincode::deserialize_from(&mut reader).map_err(decode_error)?;
+                let clock = match version {
+                    1 => None,
+                    _ => bincode::deserialize_from(&mut reader).map_err(decode_error)?,
+                };
+                payload.into_template(clock)?
+            }
+            _ => {
+                return Err(ChessError::ParseError(format!(
+                    "unsupported binary board version {}, expected {}",
+                    version, BINARY_VERSION
+                )))
+            }
+        };
+
+        let mut board = Board::new();
+        board.load_template(template)?;
+        Ok(board)
+    }
+}
@@ -1,51 +1,170 @@
+use super::clock::{Clock, SystemTimeSource};
+use super::geometry::{Bitboard, Geometry};
+use super::hooks::{dispatch, HookEvent, HookFlow, HookRegistry};
+use super::piece::meta::{MetaSchema, MOVED_META_KEY};
+use super::piece::pawn::Promotion;
 use super::piece::{Castle, Color, MoveMeta, Piece, PieceEntity, Position};
-use super::utils::{all_positions, mask, ChessError, FILES};
-use rayon::prelude::*;
+use super::rules::filter_moves;
+#[cfg(feature = "scripting")]
+use super::scripting::ScriptRegistry;
+use super::template::{BoardTemplate, VersionedTemplate, DEFAULT_VARIANT, TEMPLATE_VERSION};
+use super::utils::ChessError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[derive(Default, Debug, Clone)]
 struct Pieces {
-    white_pawns: u64,
-    white_rooks: u64,
-    white_knights: u64,
-    white_bishops: u64,
-    white_king: u64,
-    white_queen: u64,
-
-    black_pawns: u64,
-    black_rooks: u64,
-    black_knights: u64,
-    black_bishops: u64,
-    black_king: u64,
-    black_queen: u64,
+    white_pawns: Bitboard,
+    white_rooks: Bitboard,
+    white_knights: Bitboard,
+    white_bishops: Bitboard,
+    white_king: Bitboard,
+    white_queen: Bitboard,
+
+    black_pawns: Bitboard,
+    black_rooks: Bitboard,
+    black_knights: Bitboard,
+    black_bishops: Bitboard,
+    black_king: Bitboard,
+    black_queen: Bitboard,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "BoardTemplate", try_from = "VersionedTemplate")]
 pub struct Board {
+    variant: String,
+    geometry: Geometry,
     positions: Pieces,
     turn: Color,
     castling: Castle,
     passant_target: Option<Position>,
     fullmove: u8,
     halfmove: u8,
-    entities: [Option<PieceEntity>; 64],
+    entities: Vec<Option<PieceEntity>>,
     history: Vec<MoveMeta>,
-    // clock:
+    hooks: Arc<HookRegistry>,
+    meta_schema: Arc<MetaSchema>,
+    #[cfg(feature = "scripting")]
+    scripts: Arc<ScriptRegistry>,
+    clock: Option<Clock>,
 }
 
 impl Board {
     pub fn new() -> Self {
+        Self::with_geometry(Geometry::default())
+    }
+
+    pub fn with_geometry(geometry: Geometry) -> Self {
         Self {
+            variant: DEFAULT_VARIANT.into(),
+            geometry,
             positions: Pieces::default(),
             turn: Color::default(),
             castling: Castle::default(),
             passant_target: None,
             fullmove: 1,
             halfmove: 0,
-            entities: std::array::from_fn(|_| None),
+            entities: vec![None; geometry.squares()],
             history: Vec::new(),
+            hooks: Arc::new(HookRegistry::new()),
+            meta_schema: Arc::new(MetaSchema::standard()),
+            #[cfg(feature = "scripting")]
+            scripts: Arc::new(ScriptRegistry::new()),
+            clock: None,
         }
     }
 
+    pub fn get_variant(&self) -> &str {
+        &self.variant
+    }
+
+    pub fn set_variant(&mut self, variant: impl Into<String>) -> () {
+        self.variant = variant.into();
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub fn clear(&mut self) -> () {
+        self.positions = Pieces::default();
+        self.entities = vec![None; self.geometry.squares()];
+    }
+
+    pub fn hooks(&self) -> &HookRegistry {
+        &self.hooks
+    }
+
+    pub fn set_hooks(&mut self, hooks: HookRegistry) -> () {
+        self.hooks = Arc::new(hooks);
+    }
+
+    pub fn meta_schema(&self) -> &MetaSchema {
+        &self.meta_schema
+    }
+
+    pub fn set_meta_schema(&mut self, schema: MetaSchema) -> () {
+        self.meta_schema = Arc::new(schema);
+    }
+
+    #[cfg(feature = "scripting")]
+    pub fn scripts(&self) -> &ScriptRegistry {
+        &self.scripts
+    }
+
+    #[cfg(feature = "scripting")]
+    pub fn set_scripts(&mut self, scripts: ScriptRegistry) -> () {
+        self.scripts = Arc::new(scripts);
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    pub fn clock_mut(&mut self) -> Option<&mut Clock> {
+        self.clock.as_mut()
+    }
+
+    pub fn set_clock(&mut self, clock: Option<Clock>) -> () {
+        self.clock = clock;
+    }
+
+    pub fn get_turn(&self) -> Color {
+        self.turn
+    }
+
+    pub fn set_turn(&mut self, turn: Color) -> () {
+        self.turn = turn;
+    }
+
+    pub fn get_castling(&self) -> &Castle {
+        &self.castling
+    }
+
+    pub fn set_castling(&mut self, castling: Castle) -> () {
+        self.castling = castling;
+    }
+
+    pub fn get_halfmove(&self) -> u8 {
+        self.halfmove
+    }
+
+    pub fn set_halfmove(&mut self, halfmove: u8) -> () {
+        self.halfmove = halfmove;
+    }
+
+    pub fn get_fullmove(&self) -> u8 {
+        self.fullmove
+    }
+
+    pub fn set_fullmove(&mut self, fullmove: u8) -> () {
+        self.fullmove = fullmove;
+    }
+
+    pub fn get_history(&self) -> &[MoveMeta] {
+        &self.history
+    }
+
     pub fn get_passant_target(&self) -> Option<Position> {
         self.passant_target
     }
@@ -55,7 +174,7 @@ impl Board {
     }
 
     pub fn get(&self, rank: usize, file: usize) -> Result<Option<Piece>, ChessError> {
-        let mask = mask(rank, file)?;
+        let mask = self.geometry.mask(rank, file)?;
         let boards = [
             (self.positions.white_pawns, Piece::Pawn(Color::White)),
             (self.positions.white_rooks, Piece::Rook(Color::White)),
@@ -92,8 +211,26 @@ impl Board {
         Ok(found.1)
     }
 
+    pub fn bitboard(&self, piece: Piece) -> Bitboard {
+        match piece {
+            Piece::Pawn(Color::White) => self.positions.white_pawns,
+            Piece::Knight(Color::White) => self.positions.white_knights,
+            Piece::Bishop(Color::White) => self.positions.white_bishops,
+            Piece::Rook(Color::White) => self.positions.white_rooks,
+            Piece::Queen(Color::White) => self.positions.white_queen,
+            Piece::King(Color::White) => self.positions.white_king,
+
+            Piece::Pawn(Color::Black) => self.positions.black_pawns,
+            Piece::Knight(Color::Black) => self.positions.black_knights,
+            Piece::Bishop(Color::Black) => self.positions.black_bishops,
+            Piece::Rook(Color::Black) => self.positions.black_rooks,
+            Piece::Queen(Color::Black) => self.positions.black_queen,
+            Piece::King(Color::Black) => self.positions.black_king,
+        }
+    }
+
     pub fn set(&mut self, rank: usize, file: usize, p: Option<Piece>) -> Result<(), ChessError> {
-        let mask = mask(rank, file)?;
+        let mask = self.geometry.mask(rank, file)?;
 
         self.positions.white_pawns &= !mask;
         self.positions.white_rooks &= !mask;
@@ -131,24 +268,35 @@ impl Board {
     }
 
     pub fn get_king_pos(&self, color: Color) -> Result<Option<Position>, ChessError> {
-        Ok(all_positions().find(|&pos| {
+        Ok(self.geometry.positions().find(|&pos| {
             self.get(pos.rank, pos.file)
                 .map_or(false, |p| p == Some(Piece::King(color)))
         }))
     }
 
     pub fn get_entity(&self, rank: usize, file: usize) -> Option<&PieceEntity> {
-        let idx = Position { rank, file }.to_index();
-        self.entities[idx].as_ref()
+        let pos = Position { rank, file };
+        if !self.geometry.contains_pos(pos) {
+            return None;
+        }
+        self.entities[self.geometry.index(pos)].as_ref()
     }
 
     pub fn get_entity_mut(&mut self, rank: usize, file: usize) -> Option<&mut PieceEntity> {
-        let idx = Position { rank, file }.to_index();
+        let pos = Position { rank, file };
+        if !self.geometry.contains_pos(pos) {
+            return None;
+        }
+        let idx = self.geometry.index(pos);
         self.entities[idx].as_mut()
     }
 
     pub fn set_entity(&mut self, rank: usize, file: usize, entity: Option<PieceEntity>) -> () {
-        let idx = Position { rank, file }.to_index();
+        let pos = Position { rank, file };
+        if !self.geometry.contains_pos(pos) {
+            return;
+        }
+        let idx = self.geometry.index(pos);
         self.entities[idx] = entity;
     }
 
@@ -179,32 +327,25 @@ impl Board {
         from: Position,
         attacker_color: Color,
     ) -> Result<bool, ChessError> {
-        let attacked = all_positions()
-            .par_bridge()
-            .filter_map(|pos| self.get_entity(pos.rank, pos.file))
-            .filter(|e| e.piece.color() == attacker_color)
-            .try_fold(
-                || false,
-                |acc, attacker| {
-                    if acc {
-                        Ok(acc)
-                    } else {
-                        let moves = attacker.legal_moves(self)?;
-                        Ok(moves.contains(&from))
-                    }
-                },
-            )
-            .try_reduce(|| false, |a, b| Ok(a || b))?;
+        for pos in self.geometry.positions() {
+            if let Some(attacker) = self.get_entity(pos.rank, pos.file) {
+                if attacker.piece.color() == attacker_color
+                    && attacker.attacks(self)?.contains(&from)
+                {
+                    return Ok(true);
+                }
+            }
+        }
 
-        Ok(attacked)
+        Ok(false)
     }
 
     pub fn generate_fen(&self) -> Result<String, ChessError> {
         let mut board_chars: Vec<char> = Vec::new();
 
-        for rank in (0..8).rev() {
+        for rank in (0..self.geometry.height).rev() {
             let mut empty_count = 0;
-            for file in 0..8 {
+            for file in 0..self.geometry.width {
                 if let Some(piece) = self.get(rank, file)? {
                     if empty_count > 0 {
                         for digit in empty_count.to_string().chars() {
@@ -233,7 +374,7 @@ impl Board {
             self.turn.fen_char(),
             self.castling.as_fen(),
             if let Some(p_target) = self.passant_target.clone() {
-                format!("{}{}", p_target.rank, FILES[p_target.file].1)
+                self.geometry.square_name(p_target)
             } else {
                 '-'.to_string()
             },
@@ -253,30 +394,70 @@ impl Board {
         let (placement, turn, castling, passant, halfmove, fullmove) =
             (parts[0], parts[1], parts[2], parts[3], parts[4], parts[5]);
 
-        for (rank_idx, row) in placement.split('/').enumerate() {
+        let rows: Vec<&str> = placement.split('/').collect();
+        if rows.len() != self.geometry.height {
+            return Err(ChessError::ParseError(format!(
+                "invalid FEN, expected {} ranks, got {}",
+                self.geometry.height,
+                rows.len()
+            )));
+        }
+
+        self.clear();
+
+        for (rank_idx, row) in rows.iter().enumerate() {
+            let rank = self.geometry.height - 1 - rank_idx;
             let mut file_idx = 0;
-            for ch in row.chars() {
+            let mut chars = row.chars().peekable();
+
+            while let Some(ch) = chars.next() {
                 if ch.is_digit(10) {
-                    file_idx += ch.to_digit(10).unwrap() as usize;
+                    let mut empty = ch.to_digit(10).unwrap() as usize;
+                    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        empty = empty * 10 + digit as usize;
+                        chars.next();
+                    }
+                    file_idx += empty;
                 } else {
-                    let rank = 7 - rank_idx;
-                    let file = file_idx;
+                    if file_idx >= self.geometry.width {
+                        return Err(ChessError::ParseError(format!(
+                            "invalid FEN, rank {} is wider than {} files",
+                            rank + 1,
+                            self.geometry.width
+                        )));
+                    }
 
                     let piece = Piece::from_fen(ch);
-                    self.set(rank, file, Some(piece));
+                    let pos = Position {
+                        rank,
+                        file: file_idx,
+                    };
+                    self.set(rank, file_idx, Some(piece))?;
+                    self.set_entity(rank, file_idx, Some(PieceEntity::new(piece, pos)));
 
                     file_idx += 1;
                 }
             }
+
+            if file_idx != self.geometry.width {
+                return Err(ChessError::ParseError(format!(
+                    "invalid FEN, rank {} has {} files instead of {}",
+                    rank + 1,
+                    file_idx,
+                    self.geometry.width
+                )));
+            }
         }
 
         self.turn = Color::from_fen(turn);
 
-        self.castling.from_fen(castling.to_string());
+        self.castling = Castle::default();
+        self.castling.from_fen(castling.to_string())?;
         self.passant_target = match passant {
             "-" => None,
-            _ => Some(Position::from_fen(passant)),
+            _ => Some(self.geometry.parse_square(passant)?),
         };
+        self.infer_moved_meta();
 
         self.halfmove = halfmove
             .parse::<u8>()
@@ -290,16 +471,336 @@ impl Board {
     }
 
     pub fn from_template(&mut self, template: String) -> Result<(), ChessError> {
-        let vec: Vec<Option<PieceEntity>> = serde_json::from_str(&template)
-            .map_err(|err| ChessError::ParseError(format!("fail parsing template: {}", err)))?;
+        self.load_template(BoardTemplate::parse(&template)?)
+    }
+
+    pub fn to_template(&self) -> Result<String, ChessError> {
+        self.template().to_json()
+    }
 
-        for entity in vec.iter() {
-            if let Some(ent) = entity {
-                self.set(ent.pos.rank, ent.pos.file, Some(ent.piece));
-                self.set_entity(ent.pos.rank, ent.pos.file, Some(ent.clone()));
+    pub fn template(&self) -> BoardTemplate {
+        BoardTemplate {
+            version: TEMPLATE_VERSION,
+            variant: self.variant.clone(),
+            geometry: self.geometry,
+            turn: self.turn,
+            castling: self.castling.clone(),
+            passant_target: self.passant_target,
+            halfmove: self.halfmove,
+            fullmove: self.fullmove,
+            history: self.history.clone(),
+            entities: self.entities.iter().flatten().cloned().collect(),
+            clock: self.clock.as_ref().map(Clock::state),
+        }
+    }
+
+    pub fn load_template(&mut self, template: BoardTemplate) -> Result<(), ChessError> {
+        let geometry = Geometry::new(template.geometry.width, template.geometry.height)?;
+
+        for entity in template.entities.iter() {
+            if !geometry.contains_pos(entity.pos) {
+                return Err(ChessError::ParseError(format!(
+                    "entity {} at {}:{} is outside of a {}x{} board",
+                    entity.piece.fen_char(),
+                    entity.pos.rank,
+                    entity.pos.file,
+                    geometry.width,
+                    geometry.height
+                )));
+            }
+            self.meta_schema.validate(entity)?;
+        }
+        if let Some(target) = template.passant_target {
+            if !geometry.contains_pos(target) {
+                return Err(ChessError::ParseError(
+                    "passant target is outside of the board".into(),
+                ));
             }
         }
 
+        let clock = template
+            .clock
+            .map(|state| Clock::from_state(state, Arc::new(SystemTimeSource::new())))
+            .transpose()?;
+
+        self.geometry = geometry;
+        self.clear();
+
+        for ent in template.entities.into_iter() {
+            self.set(ent.pos.rank, ent.pos.file, Some(ent.piece))?;
+            self.set_entity(ent.pos.rank, ent.pos.file, Some(ent));
+        }
+
+        self.variant = template.variant;
+        self.turn = template.turn;
+        self.castling = template.castling;
+        self.passant_target = template.passant_target;
+        self.halfmove = template.halfmove;
+        self.fullmove = template.fullmove;
+        self.history = template.history;
+        self.clock = clock;
+
         Ok(())
     }
+
+    pub fn make_move(
+        &mut self,
+        from: Position,
+        to: Position,
+        promotion: Option<Piece>,
+    ) -> Result<MoveMeta, ChessError> {
+        let geometry = self.geometry;
+        let square = |pos: Position| geometry.square_name(pos);
+        let entity = self
+            .get_entity(from.rank, from.file)
+            .cloned()
+            .ok_or_else(|| ChessError::IllegalMove(format!("no piece on {}", square(from))))?;
+
+        let color = entity.piece.color();
+        if color != self.turn {
+            return Err(ChessError::IllegalMove(format!(
+                "{:?} to move, {} is {:?}",
+                self.turn,
+                square(from),
+                color
+            )));
+        }
+        if let Some(flagged) = self.clock.as_mut().and_then(Clock::check_flag) {
+            return Err(ChessError::IllegalMove(format!(
+                "{:?} flag has fallen",
+                flagged
+            )));
+        }
+
+        let mut moves = entity.legal_moves(self)?;
+        filter_moves(self, &mut moves, from, color)?;
+        if !moves.contains(&to) {
+            return Err(ChessError::IllegalMove(format!(
+                "{} cannot move from {} to {}",
+                entity.piece.fen_char(),
+                square(from),
+                square(to)
+            )));
+        }
+
+        let is_pawn = matches!(entity.piece, Piece::Pawn(_));
+        let last_rank = match color {
+            Color::White => self.geometry.height - 1,
+            Color::Black => 0,
+        };
+        match (is_pawn && to.rank == last_rank, promotion) {
+            (true, None) => {
+                return Err(ChessError::IllegalMove(format!(
+                    "pawn reaching {} must promote",
+                    square(to)
+                )))
+            }
+            (false, Some(_)) => {
+                return Err(ChessError::IllegalMove(format!(
+                    "{} to {} is not a promotion",
+                    square(from),
+                    square(to)
+                )))
+            }
+            (true, Some(Piece::Pawn(_) | Piece::King(_))) => {
+                return Err(ChessError::IllegalMove(
+                    "pawns promote to a knight, bishop, rook or queen".into(),
+                ))
+            }
+            (true, Some(p)) if p.color() != color => {
+                return Err(ChessError::IllegalMove(
+                    "promotion piece must keep the pawn's color".into(),
+                ))
+            }
+            _ => {}
+        }
+
+        let victim = if self.is_enemy(to.rank, to.file, color)? {
+            Some(to)
+        } else if is_pawn && from.file != to.file {
+            Some(Position {
+                rank: from.rank,
+                file: to.file,
+            })
+        } else {
+            None
+        };
+
+        let mut flow = dispatch(self, HookEvent::Move, from, Some(from), Some(to))?;
+        if let Some(victim_pos) = victim {
+            if flow == HookFlow::Continue {
+                flow = dispatch(self, HookEvent::Capture, from, Some(from), Some(to))?;
+            }
+            if flow == HookFlow::Continue {
+                flow = dispatch(self, HookEvent::Captured, victim_pos, Some(from), Some(to))?;
+            }
+        }
+        if flow == HookFlow::Cancel {
+            return Err(ChessError::IllegalMove(format!(
+                "{} to {} was cancelled by a hook",
+                square(from),
+                square(to)
+            )));
+        }
+
+        let mut mover = self
+            .get_entity(from.rank, from.file)
+            .cloned()
+            .ok_or_else(|| {
+                ChessError::InternalError(format!("hook removed the piece on {}", square(from)))
+            })?;
+
+        self.passant_target = None;
+        let mut meta = mover.on_move(
+            to,
+            self,
+            promotion.map(|new_piece| Promotion {
+                pawn_pos: from,
+                new_piece,
+            }),
+        )?;
+        meta.capture = victim;
+
+        if let Some(victim_pos) = victim {
+            self.set(victim_pos.rank, victim_pos.file, None)?;
+            self.set_entity(victim_pos.rank, victim_pos.file, None);
+        }
+
+        self.set(from.rank, from.file, None)?;
+        self.set_entity(from.rank, from.file, None);
+        mover.pos = to;
+        self.set(to.rank, to.file, Some(mover.piece))?;
+        self.set_entity(to.rank, to.file, Some(mover));
+
+        if let Some(castle) = &meta.castle {
+            if castle.white_king || castle.white_queen || castle.black_king || castle.black_queen {
+                self.move_castling_rook(from, to, color)?;
+            }
+        }
+        self.update_castling_rights(&meta);
+
+        self.halfmove = match is_pawn || victim.is_some() {
+            true => 0,
+            false => self.halfmove.saturating_add(1),
+        };
+        if color == Color::Black {
+            self.fullmove = self.fullmove.saturating_add(1);
+        }
+        self.turn = color.opposite();
+        self.history.push(meta.clone());
+
+        if let Some(clock) = self.clock.as_mut() {
+            if clock.running() == Some(color) {
+                clock.press()?;
+            }
+        }
+
+        if meta.promotion.is_some() {
+            dispatch(self, HookEvent::Promote, to, Some(from), Some(to))?;
+        }
+
+        let to_move: Vec<Position> = self
+            .geometry
+            .positions()
+            .filter(|pos| {
+                self.get_entity(pos.rank, pos.file)
+                    .map_or(false, |e| e.piece.color() == self.turn)
+            })
+            .collect();
+        for pos in to_move {
+            dispatch(self, HookEvent::TurnStart, pos, None, None)?;
+        }
+
+        Ok(meta)
+    }
+
+    fn move_castling_rook(
+        &mut self,
+        king_from: Position,
+        king_to: Position,
+        color: Color,
+    ) -> Result<(), ChessError> {
+        let d_file = (king_to.file as i8 - king_from.file as i8).signum();
+
+        let mut current = king_to;
+        while let Some(next) = self.geometry.shift(current, d_file, 0) {
+            current = next;
+            if self.get(next.rank, next.file)? != Some(Piece::Rook(color)) {
+                continue;
+            }
+
+            let mut rook = self
+                .get_entity(next.rank, next.file)
+                .cloned()
+                .ok_or_else(|| ChessError::InternalError("castling rook has no entity".into()))?;
+            let rook_to = self.geometry.shift(king_to, -d_file, 0).unwrap();
+
+            rook.set_meta(MOVED_META_KEY, true);
+            rook.pos = rook_to;
+            self.set(next.rank, next.file, None)?;
+            self.set_entity(next.rank, next.file, None);
+            self.set(rook_to.rank, rook_to.file, Some(rook.piece))?;
+            self.set_entity(rook_to.rank, rook_to.file, Some(rook));
+            return Ok(());
+        }
+
+        Err(ChessError::InternalError(
+            "castling without a rook to move".into(),
+        ))
+    }
+
+    fn update_castling_rights(&mut self, meta: &MoveMeta) -> () {
+        let last_rank = self.geometry.height - 1;
+        let last_file = self.geometry.width - 1;
+
+        match meta.piece {
+            Piece::King(Color::White) => {
+                self.castling.white_king = false;
+                self.castling.white_queen = false;
+            }
+            Piece::King(Color::Black) => {
+                self.castling.black_king = false;
+                self.castling.black_queen = false;
+            }
+            _ => {}
+        }
+
+        for pos in [Some(meta.from), meta.capture].into_iter().flatten() {
+            match (pos.rank, pos.file) {
+                (0, 0) => self.castling.white_queen = false,
+                (0, f) if f == last_file => self.castling.white_king = false,
+                (r, 0) if r == last_rank => self.castling.black_queen = false,
+                (r, f) if r == last_rank && f == last_file => self.castling.black_king = false,
+                _ => {}
+            }
+        }
+    }
+
+    pub fn infer_moved_meta(&mut self) -> () {
+        let last_rank = self.geometry.height - 1;
+        let last_file = self.geometry.width - 1;
+        let castling = self.castling.clone();
+
+        for entity in self.entities.iter_mut().flatten() {
+            let (pos, color) = (entity.pos, entity.piece.color());
+            let home_rank = match color {
+                Color::White => 0,
+                Color::Black => last_rank,
+            };
+            let (king_side, queen_side) = match color {
+                Color::White => (castling.white_king, castling.white_queen),
+                Color::Black => (castling.black_king, castling.black_queen),
+            };
+
+            let moved = match entity.piece {
+                Piece::Pawn(_) => pos.rank != self.geometry.pawn_rank(color),
+                Piece::King(_) => pos.rank != home_rank || !(king_side || queen_side),
+                Piece::Rook(_) if pos.rank == home_rank && pos.file == 0 => !queen_side,
+                Piece::Rook(_) if pos.rank == home_rank && pos.file == last_file => !king_side,
+                Piece::Rook(_) => true,
+                _ => continue,
+            };
+            entity.set_meta(MOVED_META_KEY, moved);
+        }
+    }
 }
@@ -0,0 +1,401 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::piece::Color;
+use super::utils::ChessError;
+
+pub trait TimeSource: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SystemTimeSource {
+    start: Instant,
+}
+
+impl SystemTimeSource {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ManualTimeSource {
+    now: Mutex<Duration>,
+}
+
+impl ManualTimeSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) -> () {
+        *self.now.lock().unwrap() += by;
+    }
+
+    pub fn set(&self, now: Duration) -> () {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum ClockMode {
+    Fischer,
+    Bronstein,
+    SimpleDelay,
+    Hourglass,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct TimeStage {
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub bonus: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct TimeControl {
+    pub mode: ClockMode,
+    pub stages: Vec<TimeStage>,
+}
+
+impl TimeControl {
+    pub fn new(mode: ClockMode, stages: Vec<TimeStage>) -> Result<Self, ChessError> {
+        if stages.is_empty() {
+            return Err(ChessError::ParseError(
+                "time control needs at least one stage".into(),
+            ));
+        }
+        if stages.iter().any(|s| s.moves == Some(0)) {
+            return Err(ChessError::ParseError(
+                "time control stage cannot last zero moves".into(),
+            ));
+        }
+        Ok(Self { mode, stages })
+    }
+
+    pub fn sudden_death(mode: ClockMode, time: Duration, bonus: Duration) -> Self {
+        Self {
+            mode,
+            stages: vec![TimeStage {
+                moves: None,
+                time,
+                bonus,
+            }],
+        }
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        Self::sudden_death(ClockMode::Fischer, time, increment)
+    }
+
+    pub fn hourglass(time: Duration) -> Self {
+        Self::sudden_death(ClockMode::Hourglass, time, Duration::ZERO)
+    }
+
+    pub fn from_pgn(tag: &str) -> Result<Self, ChessError> {
+        let err = || ChessError::ParseError(format!("invalid TimeControl: {}", tag));
+        let secs = |s: &str| s.parse::<u64>().map(Duration::from_secs).map_err(|_| err());
+
+        if let Some(time) = tag.strip_prefix('*') {
+            return Ok(Self::hourglass(secs(time)?));
+        }
+
+        let mut mode = ClockMode::Fischer;
+        let mut stages = Vec::new();
+        for field in tag.split(':') {
+            let (moves, rest) = match field.split_once('/') {
+                Some((moves, rest)) => (Some(moves.parse::<u32>().map_err(|_| err())?), rest),
+                None => (None, field),
+            };
+            let (time, bonus) = match rest.split_once('+') {
+                Some((time, bonus)) => (secs(time)?, secs(bonus)?),
+                None => match rest.split_once('d') {
+                    Some((time, delay)) => {
+                        mode = ClockMode::SimpleDelay;
+                        (secs(time)?, secs(delay)?)
+                    }
+                    None => match rest.split_once('b') {
+                        Some((time, delay)) => {
+                            mode = ClockMode::Bronstein;
+                            (secs(time)?, secs(delay)?)
+                        }
+                        None => (secs(rest)?, Duration::ZERO),
+                    },
+                },
+            };
+            stages.push(TimeStage { moves, time, bonus });
+        }
+
+        Self::new(mode, stages)
+    }
+
+    pub fn to_pgn(&self) -> String {
+        if self.mode == ClockMode::Hourglass {
+            return format!("*{}", self.stages[0].time.as_secs());
+        }
+
+        let separator = match self.mode {
+            ClockMode::Fischer => '+',
+            ClockMode::Bronstein => 'b',
+            _ => 'd',
+        };
+        self.stages
+            .iter()
+            .map(|stage| {
+                let mut field = String::new();
+                if let Some(moves) = stage.moves {
+                    field.push_str(&format!("{}/", moves));
+                }
+                field.push_str(&stage.time.as_secs().to_string());
+                if !stage.bonus.is_zero() {
+                    field.push(separator);
+                    field.push_str(&stage.bonus.as_secs().to_string());
+                }
+                field
+            })
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct ClockState {
+    pub control: TimeControl,
+    pub remaining: [Duration; 2],
+    pub stage_moves: [u32; 2],
+    pub stage: [usize; 2],
+    pub flagged: Option<Color>,
+}
+
+fn side(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+#[derive(Clone)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    stage_moves: [u32; 2],
+    stage: [usize; 2],
+    running: Option<Color>,
+    turn_start: Duration,
+    flagged: Option<Color>,
+    source: Arc<dyn TimeSource>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Result<Self, ChessError> {
+        Self::with_source(control, Arc::new(SystemTimeSource::new()))
+    }
+
+    pub fn with_source(
+        control: TimeControl,
+        source: Arc<dyn TimeSource>,
+    ) -> Result<Self, ChessError> {
+        let control = TimeControl::new(control.mode, control.stages)?;
+        let initial = control.stages[0].time;
+        Ok(Self {
+            control,
+            remaining: [initial, initial],
+            stage_moves: [0, 0],
+            stage: [0, 0],
+            running: None,
+            turn_start: Duration::ZERO,
+            flagged: None,
+            source,
+        })
+    }
+
+    pub fn set_source(&mut self, source: Arc<dyn TimeSource>) -> () {
+        let elapsed = self.elapsed();
+        self.source = source;
+        self.turn_start = self.source.now().saturating_sub(elapsed);
+    }
+
+    pub fn control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    pub fn running(&self) -> Option<Color> {
+        self.running
+    }
+
+    pub fn flagged(&self) -> Option<Color> {
+        self.flagged
+    }
+
+    pub fn start(&mut self, color: Color) -> () {
+        self.running = Some(color);
+        self.turn_start = self.source.now();
+    }
+
+    pub fn stop(&mut self) -> () {
+        if let Some(color) = self.running {
+            let elapsed = self.elapsed();
+            self.remaining[side(color)] = self.remaining(color);
+            if self.control.mode == ClockMode::Hourglass {
+                self.remaining[side(color.opposite())] += elapsed;
+            }
+            self.running = None;
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.running {
+            Some(_) => self.source.now().saturating_sub(self.turn_start),
+            None => Duration::ZERO,
+        }
+    }
+
+    pub fn remaining(&self, color: Color) -> Duration {
+        let stored = self.remaining[side(color)];
+        let elapsed = self.elapsed();
+
+        match self.running {
+            Some(running) if running == color => {
+                stored.saturating_sub(self.charged(color, elapsed))
+            }
+            Some(_) if self.control.mode == ClockMode::Hourglass => stored + elapsed,
+            _ => stored,
+        }
+    }
+
+    pub fn check_flag(&mut self) -> Option<Color> {
+        if self.flagged.is_none() {
+            if let Some(color) = self.running {
+                if self.remaining(color).is_zero() {
+                    self.flagged = Some(color);
+                    self.remaining[side(color)] = Duration::ZERO;
+                    self.running = None;
+                }
+            }
+        }
+        self.flagged
+    }
+
+    pub fn press(&mut self) -> Result<Duration, ChessError> {
+        let color = self
+            .running
+            .ok_or_else(|| ChessError::IllegalMove("clock is not running".into()))?;
+        if let Some(flagged) = self.check_flag() {
+            return Err(ChessError::IllegalMove(format!(
+                "{:?} flag has fallen",
+                flagged
+            )));
+        }
+
+        let elapsed = self.elapsed();
+        let bonus = self.control.stages[self.stage[side(color)]].bonus;
+        let charged = self.charged_with(elapsed, bonus);
+        let remaining = &mut self.remaining[side(color)];
+
+        *remaining = remaining.saturating_sub(charged);
+        match self.control.mode {
+            ClockMode::Fischer => *remaining += bonus,
+            ClockMode::Bronstein => *remaining += elapsed.min(bonus),
+            ClockMode::SimpleDelay => {}
+            ClockMode::Hourglass => self.remaining[side(color.opposite())] += elapsed,
+        }
+
+        self.advance_stage(color);
+        self.start(color.opposite());
+        Ok(elapsed)
+    }
+
+    pub fn state(&self) -> ClockState {
+        let mut clock = self.clone();
+        clock.stop();
+        ClockState {
+            control: clock.control,
+            remaining: clock.remaining,
+            stage_moves: clock.stage_moves,
+            stage: clock.stage,
+            flagged: clock.flagged,
+        }
+    }
+
+    pub fn from_state(state: ClockState, source: Arc<dyn TimeSource>) -> Result<Self, ChessError> {
+        let control = TimeControl::new(state.control.mode, state.control.stages)?;
+        if state.stage.iter().any(|&s| s >= control.stages.len()) {
+            return Err(ChessError::ParseError("clock stage is out of range".into()));
+        }
+
+        Ok(Self {
+            control,
+            remaining: state.remaining,
+            stage_moves: state.stage_moves,
+            stage: state.stage,
+            running: None,
+            turn_start: Duration::ZERO,
+            flagged: state.flagged,
+            source,
+        })
+    }
+
+    fn charged(&self, color: Color, elapsed: Duration) -> Duration {
+        let bonus = self.control.stages[self.stage[side(color)]].bonus;
+        self.charged_with(elapsed, bonus)
+    }
+
+    fn charged_with(&self, elapsed: Duration, bonus: Duration) -> Duration {
+        match self.control.mode {
+            ClockMode::SimpleDelay => elapsed.saturating_sub(bonus),
+            _ => elapsed,
+        }
+    }
+
+    fn advance_stage(&mut self, color: Color) -> () {
+        let s = side(color);
+        self.stage_moves[s] += 1;
+
+        if let Some(moves) = self.control.stages[self.stage[s]].moves {
+            if self.stage_moves[s] >= moves {
+                self.stage_moves[s] = 0;
+                self.stage[s] = (self.stage[s] + 1).min(self.control.stages.len() - 1);
+                self.remaining[s] += self.control.stages[self.stage[s]].time;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clock")
+            .field("control", &self.control)
+            .field("white", &self.remaining(Color::White))
+            .field("black", &self.remaining(Color::Black))
+            .field("running", &self.running)
+            .field("flagged", &self.flagged)
+            .finish()
+    }
+}
@@ -0,0 +1,289 @@
+use super::board::Board;
+use super::geometry::Geometry;
+use super::piece::{Castle, Color, Move, Piece, PieceEntity, Position};
+use super::utils::ChessError;
+
+const SIDE_BIT: u8 = 1 << 0;
+const WHITE_KING_BIT: u8 = 1 << 1;
+const WHITE_QUEEN_BIT: u8 = 1 << 2;
+const BLACK_KING_BIT: u8 = 1 << 3;
+const BLACK_QUEEN_BIT: u8 = 1 << 4;
+const PASSANT_BIT: u8 = 1 << 5;
+
+const PROMOTION_KNIGHT: u16 = 1;
+const PROMOTION_BISHOP: u16 = 2;
+const PROMOTION_ROOK: u16 = 3;
+const PROMOTION_QUEEN: u16 = 4;
+
+pub fn piece_code(piece: Piece) -> u8 {
+    let kind = match piece {
+        Piece::Pawn(_) => 0,
+        Piece::Knight(_) => 1,
+        Piece::Bishop(_) => 2,
+        Piece::Rook(_) => 3,
+        Piece::Queen(_) => 4,
+        Piece::King(_) => 5,
+    };
+    match piece.color() {
+        Color::White => kind,
+        Color::Black => kind | 0x8,
+    }
+}
+
+pub fn piece_from_code(code: u8) -> Result<Piece, ChessError> {
+    let color = match code & 0x8 {
+        0 => Color::White,
+        _ => Color::Black,
+    };
+    Ok(match code & 0x7 {
+        0 => Piece::Pawn(color),
+        1 => Piece::Knight(color),
+        2 => Piece::Bishop(color),
+        3 => Piece::Rook(color),
+        4 => Piece::Queen(color),
+        5 => Piece::King(color),
+        _ => {
+            return Err(ChessError::ParseError(format!(
+                "invalid piece code {:#x}",
+                code
+            )))
+        }
+    })
+}
+
+fn truncated() -> ChessError {
+    ChessError::ParseError("encoded position is truncated".into())
+}
+
+impl Board {
+    pub fn encode_position(&self) -> Result<Vec<u8>, ChessError> {
+        let geometry = self.geometry();
+        let mut occupancy = vec![0u8; geometry.squares().div_ceil(8)];
+        let mut codes: Vec<u8> = Vec::new();
+
+        for pos in geometry.positions() {
+            if let Some(piece) = self.get(pos.rank, pos.file)? {
+                let idx = geometry.index(pos);
+                occupancy[idx / 8] |= 1 << (idx % 8);
+                codes.push(piece_code(piece));
+            }
+        }
+
+        let mut bytes = occupancy;
+        for pair in codes.chunks(2) {
+            bytes.push(pair[0] | pair.get(1).map_or(0, |code| code << 4));
+        }
+
+        let castling = self.get_castling();
+        let mut state = 0u8;
+        for (flag, bit) in [
+            (self.get_turn() == Color::Black, SIDE_BIT),
+            (castling.white_king, WHITE_KING_BIT),
+            (castling.white_queen, WHITE_QUEEN_BIT),
+            (castling.black_king, BLACK_KING_BIT),
+            (castling.black_queen, BLACK_QUEEN_BIT),
+            (self.get_passant_target().is_some(), PASSANT_BIT),
+        ] {
+            if flag {
+                state |= bit;
+            }
+        }
+        bytes.push(state);
+
+        if let Some(target) = self.get_passant_target() {
+            bytes.push(target.file as u8);
+        }
+        bytes.push(self.get_halfmove());
+        bytes.push(self.get_fullmove());
+
+        Ok(bytes)
+    }
+
+    pub fn decode_position(bytes: &[u8], geometry: Geometry) -> Result<Board, ChessError> {
+        let occupancy_len = geometry.squares().div_ceil(8);
+        let occupancy = bytes.get(..occupancy_len).ok_or_else(truncated)?;
+
+        let occupied: Vec<Position> = geometry
+            .positions()
+            .filter(|&pos| {
+                let idx = geometry.index(pos);
+                occupancy[idx / 8] & (1 << (idx % 8)) != 0
+            })
+            .collect();
+        if occupancy_len * 8 > geometry.squares() {
+            let last = occupancy[occupancy_len - 1] as u16;
+            if last >> (geometry.squares() - (occupancy_len - 1) * 8) != 0 {
+                return Err(ChessError::ParseError(
+                    "occupancy marks squares outside of the board".into(),
+                ));
+            }
+        }
+
+        let codes_len = occupied.len().div_ceil(2);
+        let codes = bytes
+            .get(occupancy_len..occupancy_len + codes_len)
+            .ok_or_else(truncated)?;
+        if occupied.len() % 2 == 1 && codes[codes_len - 1] >> 4 != 0 {
+            return Err(ChessError::ParseError(
+                "unused piece code bits are set".into(),
+            ));
+        }
+        let mut rest = &bytes[occupancy_len + codes_len..];
+
+        let mut board = Board::with_geometry(geometry);
+        for (i, &pos) in occupied.iter().enumerate() {
+            let code = (codes[i / 2] >> (4 * (i % 2))) & 0xf;
+            let piece = piece_from_code(code)?;
+            board.set(pos.rank, pos.file, Some(piece))?;
+            board.set_entity(pos.rank, pos.file, Some(PieceEntity::new(piece, pos)));
+        }
+
+        let mut next = || -> Result<u8, ChessError> {
+            let (&byte, tail) = rest.split_first().ok_or_else(truncated)?;
+            rest = tail;
+            Ok(byte)
+        };
+
+        let state = next()?;
+        let turn = match state & SIDE_BIT {
+            0 => Color::White,
+            _ => Color::Black,
+        };
+        board.set_turn(turn);
+        board.set_castling(Castle {
+            white_king: state & WHITE_KING_BIT != 0,
+            white_queen: state & WHITE_QUEEN_BIT != 0,
+            black_king: state & BLACK_KING_BIT != 0,
+            black_queen: state & BLACK_QUEEN_BIT != 0,
+        });
+
+        if state & PASSANT_BIT != 0 {
+            let file = next()? as usize;
+            // the target sits one step behind the pawn that just moved
+            let rank = match turn {
+                Color::White => geometry.pawn_rank(Color::Black).checked_sub(1),
+                Color::Black => Some(geometry.pawn_rank(Color::White) + 1),
+            };
+            let target = rank
+                .map(|rank| Position { rank, file })
+                .filter(|&pos| geometry.contains_pos(pos))
+                .ok_or_else(|| ChessError::ParseError("invalid en passant file".into()))?;
+            board.set_passant_target(Some(target));
+        }
+
+        board.set_halfmove(next()?);
+        board.set_fullmove(next()?);
+        if !rest.is_empty() {
+            return Err(ChessError::ParseError(
+                "trailing bytes after encoded position".into(),
+            ));
+        }
+
+        board.infer_moved_meta();
+        Ok(board)
+    }
+}
+
+impl Move {
+    pub fn pack(&self, geometry: Geometry) -> Result<u16, ChessError> {
+        if geometry.squares() > 64 {
+            return Err(ChessError::InvalidPosition(
+                "packed moves only fit boards of up to 64 squares".into(),
+            ));
+        }
+        if !geometry.contains_pos(self.from) || !geometry.contains_pos(self.to) {
+            return Err(ChessError::InvalidPosition(
+                "move is outside of the board".into(),
+            ));
+        }
+
+        let promotion = match self.promotion {
+            None => 0,
+            Some(Piece::Knight(_)) => PROMOTION_KNIGHT,
+            Some(Piece::Bishop(_)) => PROMOTION_BISHOP,
+            Some(Piece::Rook(_)) => PROMOTION_ROOK,
+            Some(Piece::Queen(_)) => PROMOTION_QUEEN,
+            Some(piece) => {
+                return Err(ChessError::IllegalMove(format!(
+                    "cannot promote to {}",
+                    piece.fen_char()
+                )))
+            }
+        };
+
+        Ok(geometry.index(self.from) as u16
+            | (geometry.index(self.to) as u16) << 6
+            | promotion << 12)
+    }
+
+    pub fn unpack(packed: u16, geometry: Geometry, color: Color) -> Result<Self, ChessError> {
+        if geometry.squares() > 64 {
+            return Err(ChessError::InvalidPosition(
+                "packed moves only fit boards of up to 64 squares".into(),
+            ));
+        }
+
+        let square = |idx: u16| {
+            let idx = idx as usize;
+            match idx < geometry.squares() {
+                true => Ok(geometry.position(idx)),
+                false => Err(ChessError::ParseError(format!(
+                    "square {} is outside of the board",
+                    idx
+                ))),
+            }
+        };
+
+        let promotion = match packed >> 12 {
+            0 => None,
+            PROMOTION_KNIGHT => Some(Piece::Knight(color)),
+            PROMOTION_BISHOP => Some(Piece::Bishop(color)),
+            PROMOTION_ROOK => Some(Piece::Rook(color)),
+            PROMOTION_QUEEN => Some(Piece::Queen(color)),
+            flag => {
+                return Err(ChessError::ParseError(format!(
+                    "invalid promotion flag {}",
+                    flag
+                )))
+            }
+        };
+
+        Ok(Self {
+            from: square(packed & 0x3f)?,
+            to: square((packed >> 6) & 0x3f)?,
+            promotion,
+        })
+    }
+}
+
+pub fn encode_moves(moves: &[Move], geometry: Geometry) -> Result<Vec<u8>, ChessError> {
+    let mut bytes = Vec::with_capacity(moves.len() * 2);
+    for mv in moves {
+        bytes.extend_from_slice(&mv.pack(geometry)?.to_le_bytes());
+    }
+    Ok(bytes)
+}
+
+pub fn decode_moves(
+    bytes: &[u8],
+    geometry: Geometry,
+    first_to_move: Color,
+) -> Result<Vec<Move>, ChessError> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(ChessError::ParseError(
+            "packed move list has an odd length".into(),
+        ));
+    }
+
+    let mut color = first_to_move;
+    let mut moves = Vec::with_capacity(bytes.len() / 2);
+    for pair in bytes.chunks(2) {
+        moves.push(Move::unpack(
+            u16::from_le_bytes([pair[0], pair[1]]),
+            geometry,
+            color,
+        )?);
+        color = color.opposite();
+    }
+    Ok(moves)
+}
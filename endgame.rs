@@ -0,0 +1,804 @@
+use std::collections::HashMap;
+
+use super::board::Board;
+use super::geometry::Geometry;
+use super::piece::{Color, Move, Piece};
+use super::rules::legal_moves;
+use super::utils::ChessError;
+
+/// Most pieces, kings included, a solved table may hold.
+pub const MAX_PIECES: usize = 4;
+
+const SQUARES: usize = 64;
+
+// 0 is a draw, otherwise plies to mate plus one
+const DRAW: u16 = 0;
+const INVALID: u16 = u16::MAX;
+
+const CANNOT_LOSE: u8 = u8::MAX;
+
+const KING_STEPS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const ROOK_STEPS: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const BISHOP_STEPS: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+/// Distance to mate, in plies, for the side to move. `Loss(0)` is checkmate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dtm {
+    Win(u32),
+    Draw,
+    Loss(u32),
+}
+
+impl Dtm {
+    fn encode(self) -> u16 {
+        match self {
+            Dtm::Win(plies) | Dtm::Loss(plies) => plies as u16 + 1,
+            Dtm::Draw => DRAW,
+        }
+    }
+
+    fn decode(value: u16) -> Self {
+        match value {
+            DRAW => Dtm::Draw,
+            value if value % 2 == 0 => Dtm::Win(value as u32 - 1),
+            value => Dtm::Loss(value as u32 - 1),
+        }
+    }
+
+    /// The result for the side whose move reached a position worth `self`.
+    pub fn parent(self) -> Self {
+        match self {
+            Dtm::Win(plies) => Dtm::Loss(plies + 1),
+            Dtm::Draw => Dtm::Draw,
+            Dtm::Loss(plies) => Dtm::Win(plies + 1),
+        }
+    }
+
+    fn preference(self) -> i64 {
+        match self {
+            Dtm::Win(plies) => i64::MAX - plies as i64,
+            Dtm::Draw => 0,
+            Dtm::Loss(plies) => i64::MIN + plies as i64,
+        }
+    }
+}
+
+fn piece_order(piece: Piece) -> usize {
+    match piece {
+        Piece::King(_) => 0,
+        Piece::Queen(_) => 1,
+        Piece::Rook(_) => 2,
+        Piece::Bishop(_) => 3,
+        Piece::Knight(_) => 4,
+        Piece::Pawn(_) => 5,
+    }
+}
+
+fn recolor(piece: Piece) -> Piece {
+    match piece {
+        Piece::King(color) => Piece::King(color.opposite()),
+        Piece::Queen(color) => Piece::Queen(color.opposite()),
+        Piece::Rook(color) => Piece::Rook(color.opposite()),
+        Piece::Bishop(color) => Piece::Bishop(color.opposite()),
+        Piece::Knight(color) => Piece::Knight(color.opposite()),
+        Piece::Pawn(color) => Piece::Pawn(color.opposite()),
+    }
+}
+
+fn material_name(pieces: impl Iterator<Item = Piece> + Clone) -> String {
+    let side = |color: Color| {
+        let mut side: Vec<Piece> = pieces.clone().filter(|p| p.color() == color).collect();
+        side.sort_by_key(|&p| piece_order(p));
+        side.into_iter()
+            .map(|p| p.fen_char().to_ascii_uppercase())
+            .collect::<String>()
+    };
+    format!("{}v{}", side(Color::White), side(Color::Black))
+}
+
+fn flipped_name(name: &str) -> String {
+    let (white, black) = name.split_once('v').unwrap_or((name, ""));
+    format!("{}v{}", black, white)
+}
+
+fn parse_material(material: &str) -> Result<Vec<Piece>, ChessError> {
+    let invalid = || ChessError::ParseError(format!("invalid endgame material: {}", material));
+    let (white, black) = material.split_once('v').ok_or_else(invalid)?;
+
+    let mut pieces = vec![Piece::King(Color::White), Piece::King(Color::Black)];
+    for (side, color) in [(white, Color::White), (black, Color::Black)] {
+        let rest = side.strip_prefix('K').ok_or_else(invalid)?;
+        for letter in rest.chars() {
+            pieces.push(match letter {
+                'Q' => Piece::Queen(color),
+                'R' => Piece::Rook(color),
+                'B' => Piece::Bishop(color),
+                'N' => Piece::Knight(color),
+                'P' => Piece::Pawn(color),
+                _ => return Err(invalid()),
+            });
+        }
+    }
+    if pieces.len() > MAX_PIECES {
+        return Err(ChessError::ParseError(format!(
+            "{} has more than {} pieces",
+            material, MAX_PIECES
+        )));
+    }
+    Ok(pieces)
+}
+
+const fn step(square: usize, (df, dr): (i8, i8)) -> Option<usize> {
+    let file = (square % 8) as i8 + df;
+    let rank = (square / 8) as i8 + dr;
+    if file < 0 || file >= 8 || rank < 0 || rank >= 8 {
+        return None;
+    }
+    Some((rank * 8 + file) as usize)
+}
+
+const fn leap_table(steps: &[(i8, i8)]) -> [u64; SQUARES] {
+    let mut table = [0; SQUARES];
+    let mut square = 0;
+    while square < SQUARES {
+        let mut i = 0;
+        while i < steps.len() {
+            if let Some(target) = step(square, steps[i]) {
+                table[square] |= 1 << target;
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+const fn ray_table() -> [[u64; SQUARES]; 8] {
+    let mut table = [[0; SQUARES]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let towards = match direction < 4 {
+            true => ROOK_STEPS[direction],
+            false => BISHOP_STEPS[direction - 4],
+        };
+        let mut square = 0;
+        while square < SQUARES {
+            let mut current = square;
+            while let Some(target) = step(current, towards) {
+                table[direction][square] |= 1 << target;
+                current = target;
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+    table
+}
+
+const KING_ATTACKS: [u64; SQUARES] = leap_table(&KING_STEPS);
+const KNIGHT_ATTACKS: [u64; SQUARES] = leap_table(&KNIGHT_STEPS);
+const WHITE_PAWN_ATTACKS: [u64; SQUARES] = leap_table(&[(-1, 1), (1, 1)]);
+const BLACK_PAWN_ATTACKS: [u64; SQUARES] = leap_table(&[(-1, -1), (1, -1)]);
+const RAYS: [[u64; SQUARES]; 8] = ray_table();
+
+fn slides(square: usize, directions: std::ops::Range<usize>, occupied: u64) -> u64 {
+    let mut bits = 0;
+    for direction in directions {
+        let ray = RAYS[direction][square];
+        let blockers = ray & occupied;
+        if blockers == 0 {
+            bits |= ray;
+            continue;
+        }
+        let (df, dr) = match direction < 4 {
+            true => ROOK_STEPS[direction],
+            false => BISHOP_STEPS[direction - 4],
+        };
+        let nearest = match dr > 0 || (dr == 0 && df > 0) {
+            true => blockers.trailing_zeros(),
+            false => 63 - blockers.leading_zeros(),
+        };
+        bits |= ray ^ RAYS[direction][nearest as usize];
+    }
+    bits
+}
+
+fn forward(color: Color) -> i8 {
+    match color {
+        Color::White => 1,
+        Color::Black => -1,
+    }
+}
+
+fn attacks(piece: Piece, square: usize, occupied: u64) -> u64 {
+    match piece {
+        Piece::King(_) => KING_ATTACKS[square],
+        Piece::Knight(_) => KNIGHT_ATTACKS[square],
+        Piece::Bishop(_) => slides(square, 4..8, occupied),
+        Piece::Rook(_) => slides(square, 0..4, occupied),
+        Piece::Queen(_) => slides(square, 0..8, occupied),
+        Piece::Pawn(Color::White) => WHITE_PAWN_ATTACKS[square],
+        Piece::Pawn(Color::Black) => BLACK_PAWN_ATTACKS[square],
+    }
+}
+
+fn last_rank(color: Color) -> usize {
+    match color {
+        Color::White => 7,
+        Color::Black => 0,
+    }
+}
+
+fn transform(mut square: usize, symmetry: usize) -> usize {
+    if symmetry & 4 != 0 {
+        square = (square % 8) * 8 + square / 8;
+    }
+    if symmetry & 1 != 0 {
+        square ^= 7;
+    }
+    if symmetry & 2 != 0 {
+        square ^= 56;
+    }
+    square
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Setup {
+    pieces: [(Piece, usize); MAX_PIECES],
+    len: usize,
+    turn: Color,
+}
+
+impl Setup {
+    fn new(placed: &[(Piece, usize)], turn: Color) -> Self {
+        let mut pieces = [(Piece::King(Color::White), 0); MAX_PIECES];
+        pieces[..placed.len()].copy_from_slice(placed);
+        Self {
+            pieces,
+            len: placed.len(),
+            turn,
+        }
+    }
+
+    fn placed(&self) -> &[(Piece, usize)] {
+        &self.pieces[..self.len]
+    }
+
+    fn occupied(&self) -> u64 {
+        self.placed()
+            .iter()
+            .fold(0, |bits, &(_, sq)| bits | 1 << sq)
+    }
+
+    fn colored(&self, color: Color) -> u64 {
+        self.placed()
+            .iter()
+            .filter(|(piece, _)| piece.color() == color)
+            .fold(0, |bits, &(_, sq)| bits | 1 << sq)
+    }
+
+    fn in_check(&self, color: Color) -> bool {
+        let occupied = self.occupied();
+        let Some(&(_, king)) = self
+            .placed()
+            .iter()
+            .find(|&&(piece, _)| piece == Piece::King(color))
+        else {
+            return false;
+        };
+        self.placed().iter().any(|&(piece, sq)| {
+            piece.color() != color && attacks(piece, sq, occupied) & (1 << king) != 0
+        })
+    }
+
+    fn is_valid(&self) -> bool {
+        let distinct = self.occupied().count_ones() as usize == self.len;
+        let pawns_ok = self
+            .placed()
+            .iter()
+            .all(|&(piece, sq)| !matches!(piece, Piece::Pawn(_)) || (1..7).contains(&(sq / 8)));
+        distinct && pawns_ok && !self.in_check(self.turn.opposite())
+    }
+
+    fn flipped(&self) -> Self {
+        let mut flipped = *self;
+        for (piece, sq) in flipped.pieces[..self.len].iter_mut() {
+            *piece = recolor(*piece);
+            *sq ^= 56;
+        }
+        flipped.turn = self.turn.opposite();
+        flipped
+    }
+
+    fn transformed(&self, symmetry: usize) -> Self {
+        let mut transformed = *self;
+        for (_, sq) in transformed.pieces[..self.len].iter_mut() {
+            *sq = transform(*sq, symmetry);
+        }
+        transformed
+    }
+
+    fn children(&self) -> Vec<(Setup, bool)> {
+        let occupied = self.occupied();
+        let own = self.colored(self.turn);
+        let mut children = Vec::new();
+
+        for slot in 0..self.len {
+            let (piece, from) = self.pieces[slot];
+            if piece.color() != self.turn {
+                continue;
+            }
+            let mut targets = match piece {
+                Piece::Pawn(color) => {
+                    let mut targets = attacks(piece, from, occupied) & occupied & !own;
+                    if let Some(single) = step(from, (0, forward(color))) {
+                        if occupied & (1 << single) == 0 {
+                            targets |= 1 << single;
+                            let start = (from / 8) as i8
+                                == last_rank(color.opposite()) as i8 + forward(color);
+                            match step(single, (0, forward(color))) {
+                                Some(double) if start && occupied & (1 << double) == 0 => {
+                                    targets |= 1 << double;
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    targets
+                }
+                piece => attacks(piece, from, occupied) & !own,
+            };
+
+            while targets != 0 {
+                let to = targets.trailing_zeros() as usize;
+                targets &= targets - 1;
+                let promotions: &[Option<Piece>] = match piece {
+                    Piece::Pawn(color) if to / 8 == last_rank(color) => &[
+                        Some(Piece::Queen(color)),
+                        Some(Piece::Rook(color)),
+                        Some(Piece::Bishop(color)),
+                        Some(Piece::Knight(color)),
+                    ],
+                    _ => &[None],
+                };
+                for &promotion in promotions {
+                    let mut child = *self;
+                    child.pieces[slot] = (promotion.unwrap_or(piece), to);
+                    let captured = (0..self.len).find(|&i| i != slot && self.pieces[i].1 == to);
+                    if let Some(captured) = captured {
+                        if matches!(self.pieces[captured].0, Piece::King(_)) {
+                            continue;
+                        }
+                        child.pieces.copy_within(captured + 1..self.len, captured);
+                        child.len -= 1;
+                    }
+                    if child.in_check(self.turn) {
+                        continue;
+                    }
+                    child.turn = self.turn.opposite();
+                    children.push((child, captured.is_some() || promotion.is_some()));
+                }
+            }
+        }
+        children
+    }
+
+    fn predecessors(&self) -> Vec<Setup> {
+        let occupied = self.occupied();
+        let mover = self.turn.opposite();
+        let mut predecessors = Vec::new();
+
+        for slot in 0..self.len {
+            let (piece, to) = self.pieces[slot];
+            if piece.color() != mover {
+                continue;
+            }
+            let mut sources = match piece {
+                Piece::Pawn(color) => {
+                    let back = -forward(color);
+                    let mut sources = 0;
+                    if let Some(single) =
+                        step(to, (0, back)).filter(|&sq| occupied & (1 << sq) == 0)
+                    {
+                        if single / 8 != last_rank(color.opposite()) {
+                            sources |= 1 << single;
+                        }
+                        let double_rank = last_rank(color.opposite()) as i8 + 3 * forward(color);
+                        match step(single, (0, back)) {
+                            Some(start)
+                                if to / 8 == double_rank as usize
+                                    && occupied & (1 << start) == 0 =>
+                            {
+                                sources |= 1 << start;
+                            }
+                            _ => {}
+                        }
+                    }
+                    sources
+                }
+                piece => attacks(piece, to, occupied) & !occupied,
+            };
+
+            while sources != 0 {
+                let from = sources.trailing_zeros() as usize;
+                sources &= sources - 1;
+                let mut predecessor = *self;
+                predecessor.pieces[slot].1 = from;
+                predecessor.turn = mover;
+                predecessors.push(predecessor);
+            }
+        }
+        predecessors
+    }
+}
+
+/// Distance to mate of every position of one material combination.
+#[derive(Debug, Clone)]
+pub struct EndgameTable {
+    material: String,
+    pieces: Vec<Piece>,
+    symmetries: usize,
+    values: Vec<u16>,
+}
+
+impl EndgameTable {
+    pub fn material(&self) -> &str {
+        &self.material
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn longest_mate(&self) -> Option<u32> {
+        self.values
+            .iter()
+            .filter(|&&value| value != INVALID)
+            .filter_map(|&value| match Dtm::decode(value) {
+                Dtm::Win(plies) => Some(plies),
+                _ => None,
+            })
+            .max()
+    }
+
+    fn setup(&self, mut index: usize) -> Setup {
+        let mut placed = [(Piece::King(Color::White), 0); MAX_PIECES];
+        for slot in (0..self.pieces.len()).rev() {
+            placed[slot] = (self.pieces[slot], index % SQUARES);
+            index /= SQUARES;
+        }
+        let turn = match index {
+            0 => Color::White,
+            _ => Color::Black,
+        };
+        Setup::new(&placed[..self.pieces.len()], turn)
+    }
+
+    fn slot_index(setup: &Setup) -> usize {
+        let side = (setup.turn == Color::Black) as usize;
+        setup
+            .placed()
+            .iter()
+            .fold(side, |index, &(_, sq)| index * SQUARES + sq)
+    }
+
+    fn canonical(&self, ordered: &Setup) -> usize {
+        let king = ordered.pieces[0].1;
+        let lowest = (0..self.symmetries)
+            .map(|symmetry| transform(king, symmetry))
+            .min()
+            .unwrap_or(king);
+        (0..self.symmetries)
+            .filter(|&symmetry| transform(king, symmetry) == lowest)
+            .map(|symmetry| Self::slot_index(&ordered.transformed(symmetry)))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn index(&self, setup: &Setup) -> Option<usize> {
+        if setup.len != self.pieces.len() {
+            return None;
+        }
+        let mut used = [false; MAX_PIECES];
+        let mut ordered = *setup;
+        for (slot, &piece) in self.pieces.iter().enumerate() {
+            let found = (0..setup.len).find(|&i| !used[i] && setup.pieces[i].0 == piece)?;
+            used[found] = true;
+            ordered.pieces[slot] = setup.pieces[found];
+        }
+        Some(self.canonical(&ordered))
+    }
+
+    fn probe(&self, setup: &Setup) -> Option<Dtm> {
+        match self.values[self.index(setup)?] {
+            INVALID => None,
+            value => Some(Dtm::decode(value)),
+        }
+    }
+}
+
+/// Endgame tables of up to `MAX_PIECES` pieces, solved on demand.
+#[derive(Debug, Clone, Default)]
+pub struct Endgames {
+    tables: HashMap<String, EndgameTable>,
+}
+
+impl Endgames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn materials(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.tables.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    pub fn table(&self, material: &str) -> Option<&EndgameTable> {
+        self.tables.get(material)
+    }
+
+    /// Solves `material`, e.g. `KRvK`, and every table it converts into.
+    pub fn solve(&mut self, material: &str) -> Result<(), ChessError> {
+        let pieces = parse_material(material)?;
+        let name = material_name(pieces.iter().copied());
+        if self.tables.contains_key(&name) || self.tables.contains_key(&flipped_name(&name)) {
+            return Ok(());
+        }
+
+        for slot in 2..pieces.len() {
+            let mut captured = pieces.clone();
+            captured.remove(slot);
+            self.solve(&material_name(captured.into_iter()))?;
+
+            if let Piece::Pawn(color) = pieces[slot] {
+                for promotion in [
+                    Piece::Queen(color),
+                    Piece::Rook(color),
+                    Piece::Bishop(color),
+                    Piece::Knight(color),
+                ] {
+                    let mut promoted = pieces.clone();
+                    promoted[slot] = promotion;
+                    self.solve(&material_name(promoted.into_iter()))?;
+                }
+            }
+        }
+
+        let table = self.retrograde(name.clone(), pieces)?;
+        self.tables.insert(name, table);
+        Ok(())
+    }
+
+    fn probe_setup(&self, setup: &Setup) -> Option<Dtm> {
+        let name = material_name(setup.placed().iter().map(|&(piece, _)| piece));
+        match self.tables.get(&name) {
+            Some(table) => table.probe(setup),
+            None => self
+                .tables
+                .get(&flipped_name(&name))
+                .and_then(|table| table.probe(&setup.flipped())),
+        }
+    }
+
+    fn retrograde(&self, material: String, pieces: Vec<Piece>) -> Result<EndgameTable, ChessError> {
+        let size = 2 * SQUARES.pow(pieces.len() as u32);
+        let pawns = pieces.iter().any(|piece| matches!(piece, Piece::Pawn(_)));
+        // a lone minor piece never mates, so every position is drawn
+        let drawn = pieces.len() <= 3
+            && pieces
+                .iter()
+                .all(|piece| matches!(piece, Piece::King(_) | Piece::Bishop(_) | Piece::Knight(_)));
+        let mut table = EndgameTable {
+            material,
+            pieces,
+            symmetries: if pawns { 2 } else { 8 },
+            values: vec![DRAW; size],
+        };
+        let mut remaining = vec![0u8; size];
+        let mut floor = vec![0u32; size];
+        let mut queue: Vec<Vec<u32>> = Vec::new();
+        fn push(queue: &mut Vec<Vec<u32>>, plies: u32, index: usize) -> () {
+            if queue.len() <= plies as usize {
+                queue.resize(plies as usize + 1, Vec::new());
+            }
+            queue[plies as usize].push(index as u32);
+        }
+
+        let kings = (0..SQUARES)
+            .filter(|&sq| (0..table.symmetries).all(|symmetry| transform(sq, symmetry) >= sq))
+            .fold(0u64, |bits, sq| bits | 1 << sq);
+        let king_stride = SQUARES.pow(table.pieces.len() as u32 - 1);
+
+        for index in 0..size {
+            if kings & (1 << (index / king_stride % SQUARES)) == 0 {
+                table.values[index] = INVALID;
+                continue;
+            }
+            let setup = table.setup(index);
+            if table.canonical(&setup) != index || !setup.is_valid() {
+                table.values[index] = INVALID;
+                continue;
+            }
+            if drawn {
+                continue;
+            }
+            let children = setup.children();
+            if children.is_empty() {
+                match setup.in_check(setup.turn) {
+                    true => {
+                        table.values[index] = Dtm::Loss(0).encode();
+                        push(&mut queue, 0, index);
+                    }
+                    false => remaining[index] = CANNOT_LOSE,
+                }
+                continue;
+            }
+
+            let mut best_exit: Option<Dtm> = None;
+            let mut replies = Vec::new();
+            for (child, converted) in children {
+                if !converted {
+                    replies.push(table.canonical(&child));
+                    continue;
+                }
+                let result = self
+                    .probe_setup(&child)
+                    .ok_or_else(|| {
+                        ChessError::InternalError(format!(
+                            "no endgame table for a conversion of {}",
+                            table.material
+                        ))
+                    })?
+                    .parent();
+                if best_exit.is_none_or(|best| result.preference() > best.preference()) {
+                    best_exit = Some(result);
+                }
+            }
+
+            replies.sort_unstable();
+            replies.dedup();
+            let moves = replies.len() as u8;
+            remaining[index] = moves;
+            match best_exit {
+                Some(Dtm::Win(plies)) => {
+                    table.values[index] = Dtm::Win(plies).encode();
+                    push(&mut queue, plies, index);
+                }
+                Some(Dtm::Draw) => remaining[index] = CANNOT_LOSE,
+                Some(Dtm::Loss(plies)) if moves == 0 => {
+                    table.values[index] = Dtm::Loss(plies).encode();
+                    push(&mut queue, plies, index);
+                }
+                Some(Dtm::Loss(plies)) => floor[index] = plies,
+                None => {}
+            }
+        }
+
+        let mut plies = 0;
+        while plies < queue.len() {
+            for index in std::mem::take(&mut queue[plies]) {
+                let index = index as usize;
+                let value = Dtm::decode(table.values[index]);
+                if !matches!(value, Dtm::Win(p) | Dtm::Loss(p) if p as usize == plies) {
+                    continue;
+                }
+
+                let mut predecessors: Vec<usize> = table
+                    .setup(index)
+                    .predecessors()
+                    .iter()
+                    .map(|predecessor| table.canonical(predecessor))
+                    .collect();
+                predecessors.sort_unstable();
+                predecessors.dedup();
+
+                for previous in predecessors {
+                    let current = table.values[previous];
+                    if current == INVALID {
+                        continue;
+                    }
+                    match value.parent() {
+                        Dtm::Win(won) => {
+                            let quicker = match Dtm::decode(current) {
+                                Dtm::Win(known) => won < known,
+                                _ => true,
+                            };
+                            if quicker {
+                                table.values[previous] = Dtm::Win(won).encode();
+                                push(&mut queue, won, previous);
+                            }
+                        }
+                        Dtm::Loss(lost) => {
+                            let winning = matches!(Dtm::decode(current), Dtm::Win(_));
+                            if winning || remaining[previous] == CANNOT_LOSE {
+                                continue;
+                            }
+                            remaining[previous] -= 1;
+                            if remaining[previous] == 0 {
+                                let lost = lost.max(floor[previous]);
+                                table.values[previous] = Dtm::Loss(lost).encode();
+                                push(&mut queue, lost, previous);
+                            }
+                        }
+                        Dtm::Draw => {}
+                    }
+                }
+            }
+            plies += 1;
+        }
+        Ok(table)
+    }
+
+    fn board_setup(board: &Board) -> Result<Option<Setup>, ChessError> {
+        if board.geometry() != Geometry::STANDARD {
+            return Err(ChessError::InvalidPosition(
+                "endgame tables need a standard 8x8 board".into(),
+            ));
+        }
+        let mut placed = Vec::new();
+        for color in [Color::White, Color::Black] {
+            for piece in [
+                Piece::King(color),
+                Piece::Queen(color),
+                Piece::Rook(color),
+                Piece::Bishop(color),
+                Piece::Knight(color),
+                Piece::Pawn(color),
+            ] {
+                let mut bits = board.bitboard(piece);
+                while bits != 0 {
+                    placed.push((piece, bits.trailing_zeros() as usize));
+                    bits &= bits - 1;
+                }
+            }
+        }
+        Ok((placed.len() <= MAX_PIECES).then(|| Setup::new(&placed, board.get_turn())))
+    }
+
+    pub fn dtm(&self, board: &Board) -> Result<Option<Dtm>, ChessError> {
+        Ok(Self::board_setup(board)?.and_then(|setup| self.probe_setup(&setup)))
+    }
+
+    /// The quickest mate, a draw, or the longest resistance.
+    pub fn best_move(&self, board: &Board) -> Result<Option<Move>, ChessError> {
+        if self.dtm(board)?.is_none() {
+            return Ok(None);
+        }
+        let mut best: Option<(Dtm, Move)> = None;
+        for mv in legal_moves(&mut board.clone(), board.get_turn())? {
+            let mut child = board.clone();
+            child.make_move(mv.from, mv.to, mv.promotion)?;
+            let Some(result) = self.dtm(&child)?.map(Dtm::parent) else {
+                continue;
+            };
+            if best.is_none_or(|(known, _)| result.preference() > known.preference()) {
+                best = Some((result, mv));
+            }
+        }
+        Ok(best.map(|(_, mv)| mv))
+    }
+}
@@ -0,0 +1,446 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use super::board::Board;
+use super::geometry::{Bitboard, Geometry};
+use super::piece::{Color, Piece, Position};
+use super::utils::ChessError;
+
+/// Game phase of the starting material: a knight or bishop counts 1, a rook 2
+/// and a queen 4.
+pub const PHASE_TOTAL: i32 = 24;
+
+/// Entries of a piece-square table. Tables describe a standard board and are
+/// stretched over other geometries.
+pub const TABLE_SQUARES: usize = 64;
+
+/// Middlegame and endgame halves of a weight, serialized as `[mg, eg]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tapered(pub i32, pub i32);
+
+impl Tapered {
+    fn add(&mut self, other: Tapered, times: i32) -> () {
+        self.0 += other.0 * times;
+        self.1 += other.1 * times;
+    }
+
+    /// Blends both halves, `phase` running from 0 (bare kings) to PHASE_TOTAL.
+    pub fn taper(&self, phase: i32) -> i32 {
+        (self.0 * phase + self.1 * (PHASE_TOTAL - phase)) / PHASE_TOTAL
+    }
+}
+
+/// Piece-square table from white's point of view, listed rank 8 first like a
+/// diagram (a8, b8, ..., h1).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct PieceSquareTable {
+    pub mg: Vec<i32>,
+    pub eg: Vec<i32>,
+}
+
+/// Every weight of the evaluation. Per-piece arrays are ordered pawn, knight,
+/// bishop, rook, queen, king; passed pawn bonuses are by relative rank 1-8.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct EvalParams {
+    pub material: [Tapered; 6],
+    pub psqt: [PieceSquareTable; 6],
+    pub mobility: [Tapered; 6],
+    pub doubled_pawn: Tapered,
+    pub isolated_pawn: Tapered,
+    pub backward_pawn: Tapered,
+    pub passed_pawn: [Tapered; 8],
+    pub king_shield: Tapered,
+    pub king_open_file: Tapered,
+    pub king_attack: Tapered,
+    pub bishop_pair: Tapered,
+}
+
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,   0,   0,   0,   0,  0,  0,
+    50, 50,  50,  50,  50,  50, 50, 50,
+    10, 10,  20,  30,  30,  20, 10, 10,
+     5,  5,  10,  25,  25,  10,  5,  5,
+     0,  0,   0,  20,  20,   0,  0,  0,
+     5, -5, -10,   0,   0, -10, -5,  5,
+     5, 10,  10, -20, -20,  10, 10,  5,
+     0,  0,   0,   0,   0,   0,  0,  0,
+];
+
+#[rustfmt::skip]
+const PAWN_TABLE_EG: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    80, 80, 80, 80, 80, 80, 80, 80,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    30, 30, 30, 30, 30, 30, 30, 30,
+    15, 15, 15, 15, 15, 15, 15, 15,
+     5,  5,  5,  5,  5,  5,  5,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20, -10, -10, -5, -5, -10, -10, -20,
+    -10,   0,   0,  0,  0,   0,   0, -10,
+    -10,   0,   5,  5,  5,   5,   0, -10,
+     -5,   0,   5,  5,  5,   5,   0,  -5,
+      0,   0,   5,  5,  5,   5,   0,  -5,
+    -10,   5,   5,  5,  5,   5,   0, -10,
+    -10,   0,   5,  0,  0,   0,   0, -10,
+    -20, -10, -10, -5, -5, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+#[rustfmt::skip]
+const KING_TABLE_EG: [i32; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        let table = |mg: &[i32; 64], eg: &[i32; 64]| PieceSquareTable {
+            mg: mg.to_vec(),
+            eg: eg.to_vec(),
+        };
+
+        Self {
+            material: [
+                Tapered(82, 94),
+                Tapered(337, 281),
+                Tapered(365, 297),
+                Tapered(477, 512),
+                Tapered(1025, 936),
+                Tapered(0, 0),
+            ],
+            psqt: [
+                table(&PAWN_TABLE, &PAWN_TABLE_EG),
+                table(&KNIGHT_TABLE, &KNIGHT_TABLE),
+                table(&BISHOP_TABLE, &BISHOP_TABLE),
+                table(&ROOK_TABLE, &ROOK_TABLE),
+                table(&QUEEN_TABLE, &QUEEN_TABLE),
+                table(&KING_TABLE, &KING_TABLE_EG),
+            ],
+            mobility: [
+                Tapered(0, 0),
+                Tapered(4, 4),
+                Tapered(5, 5),
+                Tapered(2, 4),
+                Tapered(1, 2),
+                Tapered(0, 0),
+            ],
+            doubled_pawn: Tapered(-10, -20),
+            isolated_pawn: Tapered(-10, -15),
+            backward_pawn: Tapered(-8, -10),
+            passed_pawn: [
+                Tapered(0, 0),
+                Tapered(5, 10),
+                Tapered(10, 20),
+                Tapered(15, 35),
+                Tapered(25, 60),
+                Tapered(40, 100),
+                Tapered(60, 150),
+                Tapered(0, 0),
+            ],
+            king_shield: Tapered(10, 0),
+            king_open_file: Tapered(-20, 0),
+            king_attack: Tapered(8, 2),
+            bishop_pair: Tapered(30, 50),
+        }
+    }
+}
+
+impl EvalParams {
+    pub fn parse(params: &str) -> Result<Self, ChessError> {
+        let params: Self = serde_json::from_str(params)
+            .map_err(|err| ChessError::ParseError(format!("fail parsing eval params: {}", err)))?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    pub fn to_json(&self) -> Result<String, ChessError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| ChessError::InternalError(format!("fail writing eval params: {}", err)))
+    }
+
+    pub fn validate(&self) -> Result<(), ChessError> {
+        for (idx, table) in self.psqt.iter().enumerate() {
+            if table.mg.len() != TABLE_SQUARES || table.eg.len() != TABLE_SQUARES {
+                return Err(ChessError::ParseError(format!(
+                    "piece-square table {} needs {} entries",
+                    idx, TABLE_SQUARES
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn piece_index(piece: Piece) -> usize {
+    match piece {
+        Piece::Pawn(_) => 0,
+        Piece::Knight(_) => 1,
+        Piece::Bishop(_) => 2,
+        Piece::Rook(_) => 3,
+        Piece::Queen(_) => 4,
+        Piece::King(_) => 5,
+    }
+}
+
+/// Rank counted from `color`'s own side of the board.
+fn relative_rank(geometry: Geometry, pos: Position, color: Color) -> usize {
+    match color {
+        Color::White => pos.rank,
+        Color::Black => geometry.height - 1 - pos.rank,
+    }
+}
+
+/// Maps a square onto the 8x8 tables, scaling odd geometries.
+fn table_index(geometry: Geometry, pos: Position, color: Color) -> usize {
+    let rank = relative_rank(geometry, pos, color) * 8 / geometry.height;
+    let file = pos.file * 8 / geometry.width;
+    (7 - rank) * 8 + file
+}
+
+fn squares(geometry: Geometry, mut bitboard: Bitboard) -> impl Iterator<Item = Position> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        let idx = bitboard.trailing_zeros() as usize;
+        bitboard &= bitboard - 1;
+        Some(geometry.position(idx))
+    })
+}
+
+fn sq_mask(geometry: Geometry, pos: Position) -> Bitboard {
+    1 << geometry.index(pos)
+}
+
+fn adjacent_files(geometry: Geometry, file: usize) -> Bitboard {
+    let mut mask = 0;
+    if file > 0 {
+        mask |= geometry.file_mask(file - 1);
+    }
+    if file + 1 < geometry.width {
+        mask |= geometry.file_mask(file + 1);
+    }
+    mask
+}
+
+/// Squares on the ranks in front of `rank` as seen by `color`.
+fn forward_mask(geometry: Geometry, rank: usize, color: Color) -> Bitboard {
+    geometry.ranks_mask(rank, color == Color::White)
+}
+
+pub fn phase(board: &Board) -> i32 {
+    let mut phase = 0;
+    for color in [Color::White, Color::Black] {
+        phase += board.bitboard(Piece::Knight(color)).count_ones() as i32;
+        phase += board.bitboard(Piece::Bishop(color)).count_ones() as i32;
+        phase += board.bitboard(Piece::Rook(color)).count_ones() as i32 * 2;
+        phase += board.bitboard(Piece::Queen(color)).count_ones() as i32 * 4;
+    }
+    phase.min(PHASE_TOTAL)
+}
+
+fn pawn_structure(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let geometry = board.geometry();
+    let own = board.bitboard(Piece::Pawn(color));
+    let enemy = board.bitboard(Piece::Pawn(color.opposite()));
+    let mut score = Tapered::default();
+
+    for file in 0..geometry.width {
+        let count = (own & geometry.file_mask(file)).count_ones() as i32;
+        if count > 1 {
+            score.add(params.doubled_pawn, count - 1);
+        }
+    }
+
+    let dir: i8 = match color {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+    for pos in squares(geometry, own) {
+        let file = geometry.file_mask(pos.file);
+        let neighbours = adjacent_files(geometry, pos.file);
+        let ahead = forward_mask(geometry, pos.rank, color);
+
+        if own & neighbours == 0 {
+            score.add(params.isolated_pawn, 1);
+        } else if own & neighbours & !ahead == 0 {
+            // no friendly pawn can come level, so a guarded stop square pins it back
+            let guarded = geometry
+                .shift(pos, 0, 2 * dir)
+                .map(|two_ahead| {
+                    [-1, 1].iter().any(|&dx| {
+                        geometry
+                            .shift(two_ahead, dx, 0)
+                            .is_some_and(|sq| enemy & sq_mask(geometry, sq) != 0)
+                    })
+                })
+                .unwrap_or(false);
+            if guarded {
+                score.add(params.backward_pawn, 1);
+            }
+        }
+
+        if enemy & (file | neighbours) & ahead == 0 && own & file & ahead == 0 {
+            let rank = relative_rank(geometry, pos, color) * 8 / geometry.height;
+            score.add(params.passed_pawn[rank.min(7)], 1);
+        }
+    }
+
+    score
+}
+
+fn king_shelter(board: &Board, params: &EvalParams, color: Color, king: Position) -> Tapered {
+    let geometry = board.geometry();
+    let own = board.bitboard(Piece::Pawn(color));
+    let dir: i8 = match color {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+    let mut score = Tapered::default();
+
+    for dx in -1..=1 {
+        let Some(file) = geometry.shift(king, dx, 0) else {
+            continue;
+        };
+        if own & geometry.file_mask(file.file) == 0 {
+            score.add(params.king_open_file, 1);
+        }
+        for dy in 1..=2 {
+            if let Some(sq) = geometry.shift(file, 0, dy * dir) {
+                if own & sq_mask(geometry, sq) != 0 {
+                    score.add(params.king_shield, 1);
+                }
+            }
+        }
+    }
+    score
+}
+
+fn evaluate_color(board: &Board, params: &EvalParams, color: Color) -> Result<Tapered, ChessError> {
+    let geometry = board.geometry();
+    let mut score = Tapered::default();
+
+    let enemy_king = squares(geometry, board.bitboard(Piece::King(color.opposite()))).next();
+    let king_zone: Bitboard = enemy_king.map_or(0, |king| {
+        (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)))
+            .filter_map(|(dx, dy)| geometry.shift(king, dx, dy))
+            .fold(0, |zone, sq| zone | sq_mask(geometry, sq))
+    });
+
+    for pos in geometry.positions() {
+        let Some(entity) = board.get_entity(pos.rank, pos.file) else {
+            continue;
+        };
+        let piece = entity.piece;
+        if piece.color() != color {
+            continue;
+        }
+        let idx = piece_index(piece);
+        let table = &params.psqt[idx];
+        let square = table_index(geometry, pos, color);
+
+        score.add(params.material[idx], 1);
+        score.add(Tapered(table.mg[square], table.eg[square]), 1);
+
+        if matches!(piece, Piece::King(_)) {
+            score.add(king_shelter(board, params, color, pos), 1);
+            continue;
+        }
+
+        // pawns count the squares they attack, not the ones they can advance to
+        let targets = entity.attacks(board)?;
+        if !matches!(piece, Piece::Pawn(_)) {
+            score.add(params.mobility[idx], targets.len() as i32);
+        }
+        let pressure = targets
+            .iter()
+            .filter(|&&sq| king_zone & sq_mask(geometry, sq) != 0)
+            .count() as i32;
+        score.add(params.king_attack, pressure);
+    }
+
+    if board.bitboard(Piece::Bishop(color)).count_ones() >= 2 {
+        score.add(params.bishop_pair, 1);
+    }
+    score.add(pawn_structure(board, params, color), 1);
+    Ok(score)
+}
+
+/// Scores the board in centipawns from the side to move.
+pub fn evaluate_with(board: &Board, params: &EvalParams) -> Result<i32, ChessError> {
+    let color = board.get_turn();
+    let mut score = evaluate_color(board, params, color)?;
+    score.add(evaluate_color(board, params, color.opposite())?, -1);
+    Ok(score.taper(phase(board)))
+}
+
+pub fn default_params() -> &'static EvalParams {
+    static PARAMS: OnceLock<EvalParams> = OnceLock::new();
+    PARAMS.get_or_init(EvalParams::default)
+}
+
+pub fn evaluate(board: &Board) -> Result<i32, ChessError> {
+    evaluate_with(board, default_params())
+}
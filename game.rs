@@ -0,0 +1,479 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::board::Board;
+use super::clock::Clock;
+use super::notation::{parse_san, pgn_tag, to_san, wrap_movetext};
+use super::piece::{Color, Move, MoveMeta};
+use super::rules::{outcome, Outcome};
+use super::utils::ChessError;
+
+pub static START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Ongoing,
+}
+
+impl GameResult {
+    pub fn win_for(color: Color) -> Self {
+        match color {
+            Color::White => GameResult::WhiteWins,
+            Color::Black => GameResult::BlackWins,
+        }
+    }
+
+    pub fn from_pgn(result: &str) -> Self {
+        match result {
+            "1-0" => GameResult::WhiteWins,
+            "0-1" => GameResult::BlackWins,
+            "1/2-1/2" => GameResult::Draw,
+            _ => GameResult::Ongoing,
+        }
+    }
+
+    pub fn as_pgn(&self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_pgn())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    FiftyMoves,
+    Timeout,
+    TimeoutVsInsufficientMaterial,
+    Resignation,
+    DrawAgreement,
+    Aborted,
+}
+
+impl Termination {
+    pub fn as_pgn(&self) -> &'static str {
+        match self {
+            Termination::Timeout | Termination::TimeoutVsInsufficientMaterial => "time forfeit",
+            Termination::Aborted => "abandoned",
+            _ => "normal",
+        }
+    }
+}
+
+impl From<Outcome> for Termination {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::Checkmate { .. } => Termination::Checkmate,
+            Outcome::Timeout { .. } => Termination::Timeout,
+            Outcome::Stalemate => Termination::Stalemate,
+            Outcome::TimeoutVsInsufficientMaterial => Termination::TimeoutVsInsufficientMaterial,
+            Outcome::InsufficientMaterial => Termination::InsufficientMaterial,
+            Outcome::FiftyMoves => Termination::FiftyMoves,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Player {
+    pub name: String,
+    pub rating: Option<u32>,
+}
+
+impl Player {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            rating: None,
+        }
+    }
+
+    pub fn rated(name: impl Into<String>, rating: u32) -> Self {
+        Self {
+            name: name.into(),
+            rating: Some(rating),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameMove {
+    pub mv: Move,
+    pub san: String,
+    pub meta: MoveMeta,
+    pub played_at: SystemTime,
+    pub clock: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    board: Board,
+    start_fen: String,
+    white: Player,
+    black: Player,
+    event: Option<String>,
+    site: Option<String>,
+    round: Option<String>,
+    tags: BTreeMap<String, String>,
+    started_at: SystemTime,
+    moves: Vec<GameMove>,
+    result: GameResult,
+    termination: Option<Termination>,
+    draw_offer: Option<Color>,
+}
+
+impl Game {
+    pub fn new() -> Result<Self, ChessError> {
+        let mut board = Board::new();
+        board.from_fen(START_FEN.to_string())?;
+        Self::from_board(board)
+    }
+
+    pub fn from_board(board: Board) -> Result<Self, ChessError> {
+        Ok(Self {
+            start_fen: board.generate_fen()?,
+            board,
+            white: Player::default(),
+            black: Player::default(),
+            event: None,
+            site: None,
+            round: None,
+            tags: BTreeMap::new(),
+            started_at: SystemTime::now(),
+            moves: Vec::new(),
+            result: GameResult::Ongoing,
+            termination: None,
+            draw_offer: None,
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn start_fen(&self) -> &str {
+        &self.start_fen
+    }
+
+    pub fn moves(&self) -> &[GameMove] {
+        &self.moves
+    }
+
+    pub fn result(&self) -> GameResult {
+        self.result
+    }
+
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result != GameResult::Ongoing || self.termination == Some(Termination::Aborted)
+    }
+
+    pub fn draw_offer(&self) -> Option<Color> {
+        self.draw_offer
+    }
+
+    pub fn player(&self, color: Color) -> &Player {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    pub fn set_player(&mut self, color: Color, player: Player) -> () {
+        match color {
+            Color::White => self.white = player,
+            Color::Black => self.black = player,
+        }
+    }
+
+    pub fn set_event(&mut self, event: impl Into<String>) -> () {
+        self.event = Some(event.into());
+    }
+
+    pub fn set_site(&mut self, site: impl Into<String>) -> () {
+        self.site = Some(site.into());
+    }
+
+    pub fn set_round(&mut self, round: impl Into<String>) -> () {
+        self.round = Some(round.into());
+    }
+
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    pub fn set_started_at(&mut self, started_at: SystemTime) -> () {
+        self.started_at = started_at;
+    }
+
+    pub fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
+    pub fn set_tag(&mut self, name: impl Into<String>, value: impl Into<String>) -> () {
+        self.tags.insert(name.into(), value.into());
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.board.clock()
+    }
+
+    pub fn set_clock(&mut self, clock: Option<Clock>) -> () {
+        self.board.set_clock(clock);
+    }
+
+    pub fn play(&mut self, mv: Move) -> Result<&GameMove, ChessError> {
+        self.ensure_ongoing()?;
+        self.check_outcome()?;
+        self.ensure_ongoing()?;
+
+        let color = self.board.get_turn();
+        let san = to_san(&self.board, mv)?;
+
+        if let Some(clock) = self.board.clock_mut() {
+            if clock.running().is_none() {
+                clock.start(color);
+            }
+        }
+        let meta = self.board.make_move(mv.from, mv.to, mv.promotion)?;
+
+        if self.draw_offer == Some(color.opposite()) {
+            self.draw_offer = None;
+        }
+        self.moves.push(GameMove {
+            mv,
+            san,
+            meta,
+            played_at: SystemTime::now(),
+            clock: self.board.clock().map(|clock| clock.remaining(color)),
+        });
+        self.check_outcome()?;
+
+        Ok(self.moves.last().unwrap())
+    }
+
+    pub fn play_san(&mut self, san: &str) -> Result<&GameMove, ChessError> {
+        let mv = parse_san(&self.board, san)?;
+        self.play(mv)
+    }
+
+    pub fn check_outcome(&mut self) -> Result<GameResult, ChessError> {
+        if self.is_over() {
+            return Ok(self.result);
+        }
+
+        if let Some(result) = outcome(&mut self.board)? {
+            self.finish(
+                match result.winner() {
+                    Some(winner) => GameResult::win_for(winner),
+                    None => GameResult::Draw,
+                },
+                result.into(),
+            );
+        }
+        Ok(self.result)
+    }
+
+    pub fn resign(&mut self, color: Color) -> Result<(), ChessError> {
+        self.ensure_ongoing()?;
+        self.finish(
+            GameResult::win_for(color.opposite()),
+            Termination::Resignation,
+        );
+        Ok(())
+    }
+
+    pub fn offer_draw(&mut self, color: Color) -> Result<(), ChessError> {
+        self.ensure_ongoing()?;
+        if self.draw_offer == Some(color.opposite()) {
+            return self.accept_draw(color);
+        }
+        self.draw_offer = Some(color);
+        Ok(())
+    }
+
+    pub fn accept_draw(&mut self, color: Color) -> Result<(), ChessError> {
+        self.ensure_ongoing()?;
+        if self.draw_offer != Some(color.opposite()) {
+            return Err(ChessError::IllegalMove(format!(
+                "{:?} has no draw offer to accept",
+                color
+            )));
+        }
+        self.finish(GameResult::Draw, Termination::DrawAgreement);
+        Ok(())
+    }
+
+    pub fn decline_draw(&mut self, color: Color) -> Result<(), ChessError> {
+        if self.draw_offer != Some(color.opposite()) {
+            return Err(ChessError::IllegalMove(format!(
+                "{:?} has no draw offer to decline",
+                color
+            )));
+        }
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    pub fn abort(&mut self) -> Result<(), ChessError> {
+        self.ensure_ongoing()?;
+        if self.moves.len() >= 2 {
+            return Err(ChessError::IllegalMove(
+                "a game can only be aborted before both sides have moved".into(),
+            ));
+        }
+        self.finish(GameResult::Ongoing, Termination::Aborted);
+        Ok(())
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let (date, time) = utc_date_time(self.started_at);
+        let unknown = |value: &Option<String>| value.clone().unwrap_or_else(|| "?".into());
+        let name = |player: &Player| match player.name.is_empty() {
+            true => "?".to_string(),
+            false => player.name.clone(),
+        };
+
+        let mut headers: Vec<(String, String)> = vec![
+            ("Event".into(), unknown(&self.event)),
+            ("Site".into(), unknown(&self.site)),
+            ("Date".into(), date.clone()),
+            ("Round".into(), unknown(&self.round)),
+            ("White".into(), name(&self.white)),
+            ("Black".into(), name(&self.black)),
+            ("Result".into(), self.result.as_pgn().into()),
+            ("UTCDate".into(), date),
+            ("UTCTime".into(), time),
+        ];
+        for (tag, player) in [("WhiteElo", &self.white), ("BlackElo", &self.black)] {
+            if let Some(rating) = player.rating {
+                headers.push((tag.into(), rating.to_string()));
+            }
+        }
+        headers.push((
+            "TimeControl".into(),
+            self.clock()
+                .map_or_else(|| "-".into(), |clock| clock.control().to_pgn()),
+        ));
+        if self.start_fen != START_FEN {
+            headers.push(("SetUp".into(), "1".into()));
+            headers.push(("FEN".into(), self.start_fen.clone()));
+        }
+        headers.push((
+            "Termination".into(),
+            self.termination
+                .map_or("unterminated", |termination| termination.as_pgn())
+                .into(),
+        ));
+        for (tag, value) in self.tags.iter() {
+            if !headers.iter().any(|(name, _)| name == tag) {
+                headers.push((tag.clone(), value.clone()));
+            }
+        }
+
+        let mut pgn = String::new();
+        for (tag, value) in headers {
+            pgn.push_str(&pgn_tag(&tag, &value));
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        let mut fields = self.start_fen.split_whitespace().skip(1);
+        let mut color = Color::from_fen(fields.next().unwrap_or("w"));
+        let mut number: u32 = fields.nth(3).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+        for (i, played) in self.moves.iter().enumerate() {
+            match color {
+                Color::White => tokens.push(format!("{}.", number)),
+                Color::Black if i == 0 => tokens.push(format!("{}...", number)),
+                Color::Black => {}
+            }
+            tokens.push(played.san.clone());
+            if let Some(remaining) = played.clock {
+                tokens.push(format!("{{[%clk {}]}}", format_clock(remaining)));
+            }
+
+            if color == Color::Black {
+                number += 1;
+            }
+            color = color.opposite();
+        }
+        tokens.push(self.result.as_pgn().into());
+
+        pgn.push_str(&wrap_movetext(tokens));
+        pgn
+    }
+
+    fn ensure_ongoing(&self) -> Result<(), ChessError> {
+        match self.is_over() {
+            true => Err(ChessError::IllegalMove(format!(
+                "the game is over ({})",
+                self.result
+            ))),
+            false => Ok(()),
+        }
+    }
+
+    fn finish(&mut self, result: GameResult, termination: Termination) -> () {
+        self.result = result;
+        self.termination = Some(termination);
+        self.draw_offer = None;
+        if let Some(clock) = self.board.clock_mut() {
+            clock.stop();
+        }
+    }
+}
+
+pub fn format_clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// days since the unix epoch to a proleptic gregorian date, after Howard Hinnant's civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn utc_date_time(time: SystemTime) -> (String, String) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let of_day = secs.rem_euclid(86_400);
+
+    (
+        format!("{:04}.{:02}.{:02}", year, month, day),
+        format!(
+            "{:02}:{:02}:{:02}",
+            of_day / 3600,
+            of_day / 60 % 60,
+            of_day % 60
+        ),
+    )
+}
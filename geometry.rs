@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+
+use super::piece::{Color, Position};
+use super::utils::ChessError;
+
+/// Bitboard wide enough for every supported geometry (up to 128 squares).
+pub type Bitboard = u128;
+
+pub const MAX_SQUARES: usize = Bitboard::BITS as usize;
+pub const MAX_FILES: usize = 26;
+
+/// Width and height of a board. Squares are indexed rank-major, so
+/// `index = rank * width + file`, with rank 0 being white's home rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct Geometry {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+impl Geometry {
+    pub const STANDARD: Geometry = Geometry {
+        width: 8,
+        height: 8,
+    };
+    pub const CAPABLANCA: Geometry = Geometry {
+        width: 10,
+        height: 8,
+    };
+    pub const GRAND: Geometry = Geometry {
+        width: 10,
+        height: 10,
+    };
+    pub const GARDNER: Geometry = Geometry {
+        width: 5,
+        height: 5,
+    };
+    pub const LOS_ALAMOS: Geometry = Geometry {
+        width: 6,
+        height: 6,
+    };
+
+    pub fn new(width: usize, height: usize) -> Result<Self, ChessError> {
+        if width == 0 || height == 0 {
+            return Err(ChessError::InvalidPosition(
+                "board width and height must be non-zero".into(),
+            ));
+        }
+        if width > MAX_FILES {
+            return Err(ChessError::InvalidPosition(format!(
+                "board width {} exceeds the {} file letters",
+                width, MAX_FILES
+            )));
+        }
+        if width * height > MAX_SQUARES {
+            return Err(ChessError::InvalidPosition(format!(
+                "{}x{} board does not fit into a {}-bit bitboard",
+                width, height, MAX_SQUARES
+            )));
+        }
+        Ok(Self { width, height })
+    }
+
+    pub fn squares(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn contains(&self, rank: i8, file: i8) -> bool {
+        (0..self.height as i8).contains(&rank) && (0..self.width as i8).contains(&file)
+    }
+
+    pub fn contains_pos(&self, pos: Position) -> bool {
+        pos.rank < self.height && pos.file < self.width
+    }
+
+    /// Rank the pawns of `color` start on: the third on boards of ten ranks
+    /// or more, as in Grand Chess, the second otherwise.
+    pub fn pawn_rank(&self, color: Color) -> usize {
+        let rank = match self.height {
+            h if h >= 10 => 2,
+            _ => 1,
+        };
+        match color {
+            Color::White => rank,
+            Color::Black => self.height - 1 - rank,
+        }
+    }
+
+    pub fn index(&self, pos: Position) -> usize {
+        pos.rank * self.width + pos.file
+    }
+
+    pub fn position(&self, index: usize) -> Position {
+        Position {
+            rank: index / self.width,
+            file: index % self.width,
+        }
+    }
+
+    pub fn mask(&self, rank: usize, file: usize) -> Result<Bitboard, ChessError> {
+        if rank >= self.height || file >= self.width {
+            return Err(ChessError::ParseError(format!(
+                "rank and file expected to be in range 0-{}x0-{}",
+                self.height, self.width
+            )));
+        }
+        Ok(1 << self.index(Position { rank, file }))
+    }
+
+    pub fn unmask(&self, masked: Bitboard) -> Result<(usize, usize), ChessError> {
+        if masked == 0 || !masked.is_power_of_two() {
+            return Err(ChessError::ParseError(format!(
+                "unmask expects a single‐bit mask, got {:#x}",
+                masked
+            )));
+        }
+
+        let idx = masked.trailing_zeros() as usize;
+        if idx >= self.squares() {
+            return Err(ChessError::ParseError(format!(
+                "bit {} is outside of a {}x{} board",
+                idx, self.width, self.height
+            )));
+        }
+        let pos = self.position(idx);
+        Ok((pos.rank, pos.file))
+    }
+
+    pub fn full_mask(&self) -> Bitboard {
+        match self.squares() {
+            MAX_SQUARES => Bitboard::MAX,
+            n => (1 << n) - 1,
+        }
+    }
+
+    pub fn file_mask(&self, file: usize) -> Bitboard {
+        (0..self.height).fold(0, |mask, rank| mask | 1 << (rank * self.width + file))
+    }
+
+    /// Squares on ranks strictly above `rank` (`ahead` from white's view) or below it.
+    pub fn ranks_mask(&self, rank: usize, ahead: bool) -> Bitboard {
+        let split = |rank: usize| match rank * self.width {
+            MAX_SQUARES => Bitboard::MAX,
+            n => (1 << n) - 1,
+        };
+        match ahead {
+            true => self.full_mask() & !split(rank + 1),
+            false => split(rank),
+        }
+    }
+
+    pub fn shift(&self, pos: Position, dx: i8, dy: i8) -> Option<Position> {
+        let new_file = pos.file as i8 + dx;
+        let new_rank = pos.rank as i8 + dy;
+
+        if self.contains(new_rank, new_file) {
+            Some(Position {
+                file: new_file as usize,
+                rank: new_rank as usize,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |rank| (0..width).map(move |file| Position { rank, file }))
+    }
+
+    pub fn file_char(file: usize) -> char {
+        (b'a' + file as u8) as char
+    }
+
+    pub fn square_name(&self, pos: Position) -> String {
+        format!("{}{}", Self::file_char(pos.file), pos.rank + 1)
+    }
+
+    pub fn parse_square(&self, name: &str) -> Result<Position, ChessError> {
+        let pos = Position::from_fen(name)?;
+        if !self.contains_pos(pos) {
+            return Err(ChessError::InvalidPosition(format!(
+                "{} is outside of a {}x{} board",
+                name, self.width, self.height
+            )));
+        }
+        Ok(pos)
+    }
+}
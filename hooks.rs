@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::board::Board;
+use super::piece::meta::{MetaSchema, HOOKS_META_KEY};
+use super::piece::{Piece, PieceEntity, Position};
+use super::utils::ChessError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum HookEvent {
+    Move,
+    Capture,
+    Captured,
+    TurnStart,
+    Promote,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookFlow {
+    Continue,
+    Cancel,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookContext {
+    pub event: HookEvent,
+    pub pos: Position,
+    pub from: Option<Position>,
+    pub to: Option<Position>,
+}
+
+pub type HookFn =
+    Arc<dyn Fn(&mut Board, &HookContext) -> Result<HookFlow, ChessError> + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct HookRegistry {
+    by_piece: HashMap<(Piece, HookEvent), Vec<HookFn>>,
+    named: HashMap<(String, HookEvent), Vec<HookFn>>,
+}
+
+impl HookRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_piece.is_empty() && self.named.is_empty()
+    }
+
+    pub fn on_piece<F>(&mut self, piece: Piece, event: HookEvent, handler: F) -> ()
+    where
+        F: Fn(&mut Board, &HookContext) -> Result<HookFlow, ChessError> + Send + Sync + 'static,
+    {
+        self.by_piece
+            .entry((piece, event))
+            .or_default()
+            .push(Arc::new(handler));
+    }
+
+    pub fn register<F>(&mut self, name: impl Into<String>, event: HookEvent, handler: F) -> ()
+    where
+        F: Fn(&mut Board, &HookContext) -> Result<HookFlow, ChessError> + Send + Sync + 'static,
+    {
+        self.named
+            .entry((name.into(), event))
+            .or_default()
+            .push(Arc::new(handler));
+    }
+
+    pub fn handlers(
+        &self,
+        schema: &MetaSchema,
+        entity: &PieceEntity,
+        event: HookEvent,
+    ) -> Result<Vec<HookFn>, ChessError> {
+        let mut handlers: Vec<HookFn> = self
+            .by_piece
+            .get(&(entity.piece, event))
+            .cloned()
+            .unwrap_or_default();
+
+        for name in entity_hooks(schema, entity)? {
+            if let Some(named) = self.named.get(&(name, event)) {
+                handlers.extend(named.iter().cloned());
+            }
+        }
+
+        Ok(handlers)
+    }
+}
+
+impl fmt::Debug for HookRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HookRegistry")
+            .field("by_piece", &self.by_piece.keys().collect::<Vec<_>>())
+            .field("named", &self.named.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+pub fn entity_hooks(schema: &MetaSchema, entity: &PieceEntity) -> Result<Vec<String>, ChessError> {
+    let names = schema
+        .get::<Vec<serde_json::Value>>(entity, HOOKS_META_KEY)?
+        .unwrap_or_default();
+    Ok(names
+        .iter()
+        .filter_map(|name| name.as_str().map(String::from))
+        .collect())
+}
+
+pub fn dispatch(
+    board: &mut Board,
+    event: HookEvent,
+    pos: Position,
+    from: Option<Position>,
+    to: Option<Position>,
+) -> Result<HookFlow, ChessError> {
+    let handlers = match board.get_entity(pos.rank, pos.file) {
+        Some(entity) => board.hooks().handlers(board.meta_schema(), entity, event)?,
+        None => return Ok(HookFlow::Continue),
+    };
+
+    let ctx = HookContext {
+        event,
+        pos,
+        from,
+        to,
+    };
+    for handler in handlers {
+        if handler(board, &ctx)? == HookFlow::Cancel {
+            return Ok(HookFlow::Cancel);
+        }
+    }
+
+    Ok(HookFlow::Continue)
+}
@@ -1,7 +1,34 @@
+#[cfg(feature = "binary")]
+pub mod binary;
 pub mod board;
+pub mod clock;
+pub mod encoding;
+pub mod endgame;
+pub mod eval;
+pub mod game;
+pub mod geometry;
+pub mod hooks;
+pub mod mate;
+pub mod notation;
+pub mod ordering;
 pub mod piece;
+pub mod polyglot;
+pub mod puzzle;
 pub mod rules;
+pub mod search;
+pub mod syzygy;
+pub mod tactics;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod template;
+pub mod timeman;
+pub mod tree;
+pub mod tt;
+pub mod uci;
+pub mod uci_client;
 pub mod utils;
+pub mod xboard;
+pub mod zobrist;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,163 @@
+use super::board::Board;
+use super::piece::Move;
+use super::rules::{is_checkmate, is_in_check, legal_moves};
+use super::utils::ChessError;
+
+/// A move that forces mate, with every defence against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MateTree {
+    pub mv: Move,
+    /// Moves of the attacker, this one included, until mate against the
+    /// most stubborn defence.
+    pub mate_in: u32,
+    /// Empty when `mv` mates at once.
+    pub defences: Vec<Defence>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defence {
+    pub reply: Move,
+    /// Every move that still forces mate in time, quickest first.
+    pub mates: Vec<MateTree>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MateSolution {
+    /// Every key move forcing mate within the limit, quickest first.
+    pub keys: Vec<MateTree>,
+}
+
+impl MateSolution {
+    pub fn is_mate(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Length of the quickest forced mate, in moves of the attacker.
+    pub fn mate_in(&self) -> Option<u32> {
+        self.keys.first().map(|key| key.mate_in)
+    }
+
+    /// Whether more than one key move works, which spoils a mate problem.
+    pub fn is_cooked(&self) -> bool {
+        self.keys.len() > 1
+    }
+}
+
+/// Legal moves of the side to move with the positions they lead to and
+/// whether they give check. Checks come first: only they mate at once and
+/// they most often force mate later.
+fn candidates(board: &Board) -> Result<Vec<(Move, Board, bool)>, ChessError> {
+    let color = board.get_turn();
+    let mut candidates = Vec::new();
+    for mv in legal_moves(&mut board.clone(), color)? {
+        let mut child = board.clone();
+        child.make_move(mv.from, mv.to, mv.promotion)?;
+        let check = is_in_check(&child, color.opposite())?;
+        candidates.push((mv, child, check));
+    }
+    candidates.sort_by_key(|&(_, _, check)| !check);
+    Ok(candidates)
+}
+
+fn forces_mate(board: &Board, n: u32) -> Result<bool, ChessError> {
+    for (_, mut child, check) in candidates(board)? {
+        if !check && n == 1 {
+            break;
+        }
+        if mates_after(&mut child, check, n)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether the attacker, having just played into `child` with `n` moves
+/// left, mates against every defence.
+fn mates_after(child: &mut Board, check: bool, n: u32) -> Result<bool, ChessError> {
+    let defender = child.get_turn();
+    if check && is_checkmate(child, defender)? {
+        return Ok(true);
+    }
+    if n == 1 {
+        return Ok(false);
+    }
+
+    let replies = legal_moves(child, defender)?;
+    // no replies without check is stalemate
+    if replies.is_empty() {
+        return Ok(false);
+    }
+    for reply in replies {
+        let mut next = child.clone();
+        next.make_move(reply.from, reply.to, reply.promotion)?;
+        if !forces_mate(&next, n - 1)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn mate_trees(board: &Board, n: u32) -> Result<Vec<MateTree>, ChessError> {
+    let mut trees = Vec::new();
+    for (mv, mut child, check) in candidates(board)? {
+        if !check && n == 1 {
+            break;
+        }
+        if !mates_after(&mut child, check, n)? {
+            continue;
+        }
+        let defender = child.get_turn();
+        if check && is_checkmate(&mut child, defender)? {
+            trees.push(MateTree {
+                mv,
+                mate_in: 1,
+                defences: Vec::new(),
+            });
+            continue;
+        }
+
+        let mut defences = Vec::new();
+        for reply in legal_moves(&mut child, defender)? {
+            let mut next = child.clone();
+            next.make_move(reply.from, reply.to, reply.promotion)?;
+            defences.push(Defence {
+                reply,
+                mates: mate_trees(&next, n - 1)?,
+            });
+        }
+        let mate_in = 1 + defences
+            .iter()
+            .filter_map(|defence| defence.mates.first())
+            .map(|tree| tree.mate_in)
+            .max()
+            .unwrap_or(0);
+        trees.push(MateTree {
+            mv,
+            mate_in,
+            defences,
+        });
+    }
+    trees.sort_by_key(|tree| tree.mate_in);
+    Ok(trees)
+}
+
+/// Finds every forced mate in `n` moves or fewer for the side to move, by
+/// exhaustive search trying checks first, along with its whole solution
+/// tree.
+pub fn solve_mate(board: &Board, n: u32) -> Result<MateSolution, ChessError> {
+    if n == 0 {
+        return Ok(MateSolution::default());
+    }
+    Ok(MateSolution {
+        keys: mate_trees(board, n)?,
+    })
+}
+
+/// Whether the side to move can force mate in `n` moves or fewer. Stops at
+/// the first key move, so it is cheaper than `solve_mate`.
+pub fn has_forced_mate(board: &Board, n: u32) -> Result<bool, ChessError> {
+    match n {
+        0 => Ok(false),
+        n => forces_mate(board, n),
+    }
+}
@@ -0,0 +1,227 @@
+use super::board::Board;
+use super::geometry::Geometry;
+use super::piece::{Color, Move, Piece};
+use super::rules::{is_checkmate, is_in_check, legal_moves};
+use super::utils::ChessError;
+
+fn piece_letter(piece: Piece) -> Option<char> {
+    match piece {
+        Piece::Pawn(_) => None,
+        p => Some(p.fen_char().to_ascii_uppercase()),
+    }
+}
+
+fn san_body(board: &Board, mv: Move, legal: &[Move]) -> Result<String, ChessError> {
+    let geometry = board.geometry();
+    let piece = board.get(mv.from.rank, mv.from.file)?.ok_or_else(|| {
+        ChessError::IllegalMove(format!("no piece on {}", geometry.square_name(mv.from)))
+    })?;
+
+    if matches!(piece, Piece::King(_)) && mv.from.file.abs_diff(mv.to.file) >= 2 {
+        return Ok(match mv.to.file > mv.from.file {
+            true => "O-O",
+            false => "O-O-O",
+        }
+        .into());
+    }
+
+    let is_pawn = matches!(piece, Piece::Pawn(_));
+    let capture = board.is_enemy(mv.to.rank, mv.to.file, piece.color())?
+        || (is_pawn && mv.from.file != mv.to.file);
+
+    let mut san = String::new();
+    match piece_letter(piece) {
+        None if capture => san.push(Geometry::file_char(mv.from.file)),
+        None => {}
+        Some(letter) => {
+            san.push(letter);
+
+            let rivals: Vec<&Move> = legal
+                .iter()
+                .filter(|other| {
+                    other.to == mv.to
+                        && other.from != mv.from
+                        && board.get(other.from.rank, other.from.file).ok() == Some(Some(piece))
+                })
+                .collect();
+            if !rivals.is_empty() {
+                let same_file = rivals.iter().any(|other| other.from.file == mv.from.file);
+                let same_rank = rivals.iter().any(|other| other.from.rank == mv.from.rank);
+                if !same_file || same_rank {
+                    san.push(Geometry::file_char(mv.from.file));
+                }
+                if same_file {
+                    san.push_str(&(mv.from.rank + 1).to_string());
+                }
+            }
+        }
+    }
+
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&geometry.square_name(mv.to));
+    if let Some(promotion) = mv.promotion.and_then(piece_letter) {
+        san.push('=');
+        san.push(promotion);
+    }
+
+    Ok(san)
+}
+
+fn scratch_board(board: &Board) -> Board {
+    let mut scratch = board.clone();
+    scratch.set_clock(None);
+    scratch
+}
+
+pub fn to_san(board: &Board, mv: Move) -> Result<String, ChessError> {
+    let color = board.get_turn();
+    let mut scratch = scratch_board(board);
+    let legal = legal_moves(&mut scratch, color)?;
+    if !legal.contains(&mv) {
+        let geometry = board.geometry();
+        return Err(ChessError::IllegalMove(format!(
+            "{} to {} is not legal",
+            geometry.square_name(mv.from),
+            geometry.square_name(mv.to)
+        )));
+    }
+
+    let mut san = san_body(board, mv, &legal)?;
+    scratch.make_move(mv.from, mv.to, mv.promotion)?;
+    if is_checkmate(&mut scratch, color.opposite())? {
+        san.push('#');
+    } else if is_in_check(&scratch, color.opposite())? {
+        san.push('+');
+    }
+
+    Ok(san)
+}
+
+pub fn parse_san(board: &Board, san: &str) -> Result<Move, ChessError> {
+    let mut cleaned = san
+        .trim()
+        .trim_end_matches("e.p.")
+        .trim_end_matches(['+', '#', '!', '?'])
+        .to_string();
+    match cleaned.as_str() {
+        "0-0" => cleaned = "O-O".into(),
+        "0-0-0" => cleaned = "O-O-O".into(),
+        _ => {}
+    }
+
+    // accept promotions written without the '=' sign, e.g. "e8Q"
+    let chars: Vec<char> = cleaned.chars().collect();
+    if let [.., digit, letter] = chars[..] {
+        if digit.is_ascii_digit() && "NBRQ".contains(letter) {
+            cleaned.insert(cleaned.len() - 1, '=');
+        }
+    }
+
+    let mut scratch = scratch_board(board);
+    let legal = legal_moves(&mut scratch, board.get_turn())?;
+    for &mv in legal.iter() {
+        if san_body(board, mv, &legal)? == cleaned {
+            return Ok(mv);
+        }
+    }
+
+    Err(ChessError::IllegalMove(format!(
+        "{} is not a legal move",
+        san
+    )))
+}
+
+pub fn pgn_tag(name: &str, value: &str) -> String {
+    format!(
+        "[{} \"{}\"]\n",
+        name,
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+pub fn wrap_movetext(tokens: Vec<String>) -> String {
+    let mut words: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut open = false;
+    for token in tokens {
+        match token.as_str() {
+            "(" => {
+                open = true;
+                continue;
+            }
+            ")" => {
+                if let Some(last) = words.last_mut() {
+                    last.push(')');
+                }
+                continue;
+            }
+            _ => {}
+        }
+        words.push(match open {
+            true => format!("({}", token),
+            false => token,
+        });
+        open = false;
+    }
+
+    let mut text = String::new();
+    let mut line = String::new();
+    for word in words {
+        if !line.is_empty() && line.len() + word.len() + 1 > 79 {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    text.push_str(&line);
+    text.push('\n');
+    text
+}
+
+impl Move {
+    pub fn to_uci(&self, geometry: Geometry) -> String {
+        let mut uci = format!(
+            "{}{}",
+            geometry.square_name(self.from),
+            geometry.square_name(self.to)
+        );
+        if let Some(promotion) = self.promotion {
+            uci.push(promotion.fen_char().to_ascii_lowercase());
+        }
+        uci
+    }
+
+    pub fn from_uci(text: &str, geometry: Geometry, color: Color) -> Result<Self, ChessError> {
+        let err = || ChessError::ParseError(format!("invalid UCI move: {}", text));
+
+        // the destination starts at the second file letter
+        let split = text
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| c.is_ascii_lowercase())
+            .map(|(idx, _)| idx)
+            .ok_or_else(err)?;
+        let (from, rest) = text.split_at(split);
+        let (to, promotion) = match rest.chars().last() {
+            Some(ch @ ('n' | 'b' | 'r' | 'q')) if rest.len() > 2 => {
+                let fen = match color {
+                    Color::White => ch.to_ascii_uppercase(),
+                    Color::Black => ch,
+                };
+                (&rest[..rest.len() - 1], Some(Piece::from_fen(fen)))
+            }
+            _ => (rest, None),
+        };
+
+        Ok(Self {
+            from: geometry.parse_square(from)?,
+            to: geometry.parse_square(to)?,
+            promotion,
+        })
+    }
+}
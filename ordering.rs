@@ -0,0 +1,317 @@
+use std::cmp::Reverse;
+
+use super::board::Board;
+use super::geometry::MAX_SQUARES;
+use super::piece::{Color, Move, MoveMeta, Piece, Position};
+use super::search::piece_value;
+use super::utils::ChessError;
+
+/// Killer moves remembered per ply.
+pub const KILLER_SLOTS: usize = 2;
+
+const HISTORY_LIMIT: i32 = 1 << 20;
+
+// ordering bands, from the first move tried to the last
+const HASH_MOVE: i32 = 1 << 30;
+const GOOD_CAPTURE: i32 = 1 << 28;
+const KILLER: i32 = 1 << 27;
+const COUNTER_MOVE: i32 = 1 << 26;
+const BAD_CAPTURE: i32 = -(1 << 26);
+
+fn piece_rank(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn(_) => 0,
+        Piece::Knight(_) => 1,
+        Piece::Bishop(_) => 2,
+        Piece::Rook(_) => 3,
+        Piece::Queen(_) => 4,
+        Piece::King(_) => 5,
+    }
+}
+
+/// Most valuable victim, least valuable attacker.
+pub fn mvv_lva(victim: Piece, attacker: Piece) -> i32 {
+    8 * piece_rank(victim) + 5 - piece_rank(attacker)
+}
+
+/// Square of the piece `mv` would capture, not `mv.to` for en passant.
+pub fn capture_square(board: &Board, mv: Move) -> Result<Option<Position>, ChessError> {
+    let Some(piece) = board.get(mv.from.rank, mv.from.file)? else {
+        return Ok(None);
+    };
+    if board.is_enemy(mv.to.rank, mv.to.file, piece.color())? {
+        return Ok(Some(mv.to));
+    }
+
+    // a pawn only moves diagonally onto an empty square en passant
+    let en_passant = matches!(piece, Piece::Pawn(_)) && mv.from.file != mv.to.file;
+    Ok(en_passant.then_some(Position {
+        rank: mv.from.rank,
+        file: mv.to.file,
+    }))
+}
+
+fn exchange_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::King(_) => i32::MAX,
+        piece => piece_value(piece),
+    }
+}
+
+fn least_valuable_attacker(
+    board: &Board,
+    target: Position,
+    color: Color,
+) -> Result<Option<(Position, Piece)>, ChessError> {
+    let mut best: Option<(Position, Piece)> = None;
+    for pos in board.geometry().positions() {
+        let Some(entity) = board.get_entity(pos.rank, pos.file) else {
+            continue;
+        };
+        if entity.piece.color() != color
+            || best.is_some_and(|(_, piece)| exchange_value(piece) <= exchange_value(entity.piece))
+        {
+            continue;
+        }
+        if entity.attacks(board)?.contains(&target) {
+            best = Some((pos, entity.piece));
+        }
+    }
+    Ok(best)
+}
+
+fn relocate(
+    board: &mut Board,
+    from: Position,
+    to: Position,
+    piece: Piece,
+) -> Result<(), ChessError> {
+    let mut entity = board.get_entity(from.rank, from.file).cloned();
+    if let Some(entity) = entity.as_mut() {
+        entity.piece = piece;
+        entity.pos = to;
+    }
+    board.set(from.rank, from.file, None)?;
+    board.set_entity(from.rank, from.file, None);
+    board.set(to.rank, to.file, Some(piece))?;
+    board.set_entity(to.rank, to.file, entity);
+    Ok(())
+}
+
+fn exchange(
+    board: &mut Board,
+    target: Position,
+    victim: i32,
+    color: Color,
+) -> Result<i32, ChessError> {
+    let Some((from, attacker)) = least_valuable_attacker(board, target, color)? else {
+        return Ok(0);
+    };
+    relocate(board, from, target, attacker)?;
+
+    // a king may only take when nothing recaptures
+    if let Piece::King(_) = attacker {
+        return Ok(
+            match least_valuable_attacker(board, target, color.opposite())? {
+                Some(_) => 0,
+                None => victim,
+            },
+        );
+    }
+    let answer = exchange(board, target, piece_value(attacker), color.opposite())?;
+    Ok((victim - answer).max(0))
+}
+
+/// Static exchange evaluation of `mv`, in centipawns, ignoring pins.
+pub fn see(board: &Board, mv: Move) -> Result<i32, ChessError> {
+    let piece = board.get(mv.from.rank, mv.from.file)?.ok_or_else(|| {
+        let square = board.geometry().square_name(mv.from);
+        ChessError::IllegalMove(format!("no piece on {}", square))
+    })?;
+
+    let mut scratch = board.clone();
+    let mut gain = 0;
+    if let Some(captured) = capture_square(board, mv)? {
+        gain += board
+            .get(captured.rank, captured.file)?
+            .map_or(0, piece_value);
+        scratch.set(captured.rank, captured.file, None)?;
+        scratch.set_entity(captured.rank, captured.file, None);
+    }
+    let moved = mv.promotion.unwrap_or(piece);
+    gain += piece_value(moved) - piece_value(piece);
+    relocate(&mut scratch, mv.from, mv.to, moved)?;
+
+    let answer = exchange(
+        &mut scratch,
+        mv.to,
+        piece_value(moved),
+        piece.color().opposite(),
+    )?;
+    Ok(gain - answer)
+}
+
+/// Pieces of `color` the opponent wins material by capturing.
+pub fn hanging_pieces(board: &Board, color: Color) -> Result<Vec<Position>, ChessError> {
+    let mut hanging = Vec::new();
+    for pos in board.geometry().positions() {
+        let piece = match board.get(pos.rank, pos.file)? {
+            Some(piece) if piece.color() == color && !matches!(piece, Piece::King(_)) => piece,
+            _ => continue,
+        };
+        let mut scratch = board.clone();
+        if exchange(&mut scratch, pos, piece_value(piece), color.opposite())? > 0 {
+            hanging.push(pos);
+        }
+    }
+    Ok(hanging)
+}
+
+/// Decides which moves the search tries first.
+pub struct MoveOrderer {
+    killers: Vec<[Option<Move>; KILLER_SLOTS]>,
+    history: Vec<i32>,
+    counters: Vec<Option<Move>>,
+}
+
+impl Default for MoveOrderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveOrderer {
+    pub fn new() -> Self {
+        Self {
+            killers: Vec::new(),
+            history: vec![0; 2 * MAX_SQUARES * MAX_SQUARES],
+            counters: vec![None; MAX_SQUARES * MAX_SQUARES],
+        }
+    }
+
+    /// Forgets killers and fades history before the next search.
+    pub fn new_search(&mut self) -> () {
+        self.killers.clear();
+        for score in self.history.iter_mut() {
+            *score /= 2;
+        }
+    }
+
+    pub fn killers(&self, ply: usize) -> &[Option<Move>] {
+        self.killers
+            .get(ply)
+            .map_or(&[None; KILLER_SLOTS], |killers| killers)
+    }
+
+    fn history_index(board: &Board, color: Color, mv: Move) -> usize {
+        let geometry = board.geometry();
+        let side = match color {
+            Color::White => 0,
+            Color::Black => 1,
+        };
+        (side * MAX_SQUARES + geometry.index(mv.from)) * MAX_SQUARES + geometry.index(mv.to)
+    }
+
+    pub fn history(&self, board: &Board, mv: Move) -> i32 {
+        self.history[Self::history_index(board, board.get_turn(), mv)]
+    }
+
+    fn previous(board: &Board) -> Option<&MoveMeta> {
+        board
+            .get_history()
+            .last()
+            .filter(|meta| meta.piece.color() != board.get_turn())
+    }
+
+    fn counter_index(board: &Board, meta: &MoveMeta) -> usize {
+        let geometry = board.geometry();
+        geometry.index(meta.from) * MAX_SQUARES + geometry.index(meta.to)
+    }
+
+    pub fn counter_move(&self, board: &Board) -> Option<Move> {
+        Self::previous(board).and_then(|meta| self.counters[Self::counter_index(board, meta)])
+    }
+
+    /// Sort key of `mv`; larger keys are searched first.
+    pub fn score(
+        &self,
+        board: &Board,
+        mv: Move,
+        hash_move: Option<Move>,
+        ply: usize,
+    ) -> Result<i32, ChessError> {
+        if Some(mv) == hash_move {
+            return Ok(HASH_MOVE);
+        }
+
+        let attacker = board
+            .get(mv.from.rank, mv.from.file)?
+            .ok_or_else(|| ChessError::IllegalMove(format!("no piece to move for {:?}", mv)))?;
+        let victim = match capture_square(board, mv)? {
+            Some(pos) => board.get(pos.rank, pos.file)?,
+            None => None,
+        };
+        if victim.is_some() || mv.promotion.is_some() {
+            let promotion = mv.promotion.map_or(0, |piece| 8 * piece_rank(piece));
+            let tactical = victim.map_or(0, |victim| mvv_lva(victim, attacker)) + promotion;
+
+            // taking something worth at least the attacker cannot lose material
+            let cheap = victim.is_some_and(|victim| piece_value(victim) >= piece_value(attacker));
+            return Ok(match cheap || see(board, mv)? >= 0 {
+                true => GOOD_CAPTURE + tactical,
+                false => BAD_CAPTURE + tactical,
+            });
+        }
+
+        if let Some(slot) = self.killers(ply).iter().position(|&k| k == Some(mv)) {
+            return Ok(KILLER - slot as i32);
+        }
+        if self.counter_move(board) == Some(mv) {
+            return Ok(COUNTER_MOVE);
+        }
+        Ok(self.history(board, mv))
+    }
+
+    pub fn order(
+        &self,
+        board: &Board,
+        moves: &mut [Move],
+        hash_move: Option<Move>,
+        ply: usize,
+    ) -> Result<(), ChessError> {
+        let mut keyed = Vec::with_capacity(moves.len());
+        for &mv in moves.iter() {
+            keyed.push((self.score(board, mv, hash_move, ply)?, mv));
+        }
+        keyed.sort_by_key(|&(key, _)| Reverse(key));
+
+        for (slot, (_, mv)) in moves.iter_mut().zip(keyed) {
+            *slot = mv;
+        }
+        Ok(())
+    }
+
+    /// Rewards a quiet move that caused a beta cutoff at `ply`.
+    pub fn record_cutoff(&mut self, board: &Board, mv: Move, depth: u32, ply: usize) -> () {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; KILLER_SLOTS]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers.rotate_right(1);
+            killers[0] = Some(mv);
+        }
+
+        let idx = Self::history_index(board, board.get_turn(), mv);
+        self.history[idx] += (depth * depth) as i32;
+        if self.history[idx] > HISTORY_LIMIT {
+            for score in self.history.iter_mut() {
+                *score /= 2;
+            }
+        }
+
+        if let Some(meta) = Self::previous(board) {
+            self.counters[Self::counter_index(board, meta)] = Some(mv);
+        }
+    }
+}
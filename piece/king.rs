@@ -1,5 +1,6 @@
 use super::super::board::Board;
 use super::super::utils::{sliding_moves, step_moves, ChessError};
+use super::meta::MOVED_META_KEY;
 use super::{Castle, Color, MoveMeta, Piece, PieceEntity, Position};
 
 static STEPS: &[(i8, i8)] = &[
@@ -18,10 +19,10 @@ static CASTLE_DIRS: &[(i8, i8)] = &[(1, 0), (-1, 0)];
 pub fn legal_moves(entity: &PieceEntity, board: &Board) -> Result<Vec<Position>, ChessError> {
     let mut moves = step_moves(board, entity.pos, STEPS, entity.piece.color())?;
 
-    let has_moved = match entity.get_meta("moved") {
-        Some(val) if *val == serde_json::Value::Bool(true) => true,
-        _ => false,
-    };
+    let has_moved = board
+        .meta_schema()
+        .get::<bool>(entity, MOVED_META_KEY)?
+        .unwrap_or(false);
     if has_moved {
         return Ok(moves);
     }
@@ -33,17 +34,22 @@ pub fn legal_moves(entity: &PieceEntity, board: &Board) -> Result<Vec<Position>,
             if ent.piece != Piece::Rook(entity.piece.color()) {
                 continue;
             }
-            let rook_has_moved = match ent.get_meta("moved") {
-                Some(val) if *val == serde_json::Value::Bool(true) => true,
-                _ => false,
-            };
+            let rook_has_moved = board
+                .meta_schema()
+                .get::<bool>(ent, MOVED_META_KEY)?
+                .unwrap_or(false);
             if rook_has_moved {
                 continue;
             }
 
             let d_col = (mv.file as i8 - entity.pos.file as i8).signum();
-            if let Some(castle_target) = entity.pos.shifted(d_col * 2, 0) {
-                let path_squares = [entity.pos.shifted(d_col, 0).unwrap(), castle_target];
+            let geometry = board.geometry();
+            if let Some(castle_target) = geometry.shift(entity.pos, d_col * 2, 0) {
+                let path_squares = [
+                    entity.pos,
+                    geometry.shift(entity.pos, d_col, 0).unwrap(),
+                    castle_target,
+                ];
 
                 let under_attack = {
                     let mut attacked = false;
@@ -66,6 +72,13 @@ pub fn legal_moves(entity: &PieceEntity, board: &Board) -> Result<Vec<Position>,
     Ok(moves)
 }
 
+pub fn attacks(entity: &PieceEntity, board: &Board) -> Vec<Position> {
+    STEPS
+        .iter()
+        .filter_map(|&(dx, dy)| board.geometry().shift(entity.pos, dx, dy))
+        .collect()
+}
+
 pub fn on_move(
     entity: &mut PieceEntity,
     new_pos: Position,
@@ -73,10 +86,10 @@ pub fn on_move(
 ) -> Result<MoveMeta, ChessError> {
     let delta_col = new_pos.file as i8 - entity.pos.file as i8;
 
-    let has_moved = match entity.get_meta("moved") {
-        Some(val) if *val == serde_json::Value::Bool(true) => true,
-        _ => false,
-    };
+    let has_moved = board
+        .meta_schema()
+        .get::<bool>(entity, MOVED_META_KEY)?
+        .unwrap_or(false);
 
     let is_castle = delta_col.abs() == 2 && !has_moved;
 
@@ -92,7 +105,7 @@ pub fn on_move(
         black_queen: !is_white && is_castle && new_pos.file < entity.pos.file,
     };
 
-    entity.set_meta("moved", true);
+    entity.set_meta(MOVED_META_KEY, true);
 
     Ok(MoveMeta {
         piece: entity.piece.clone(),
@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::super::utils::ChessError;
+use super::PieceEntity;
+
+pub static MOVED_META_KEY: &str = "moved";
+pub static HOOKS_META_KEY: &str = "hooks";
+pub static SCRIPT_META_KEY: &str = "script";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub enum MetaType {
+    Bool,
+    Int,
+    Float,
+    String,
+    List,
+    Map,
+    Any,
+}
+
+impl MetaType {
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            MetaType::Bool => value.is_boolean(),
+            MetaType::Int => value.is_i64() || value.is_u64(),
+            MetaType::Float => value.is_number(),
+            MetaType::String => value.is_string(),
+            MetaType::List => value.is_array(),
+            MetaType::Map => value.is_object(),
+            MetaType::Any => true,
+        }
+    }
+}
+
+pub trait MetaValue: Sized {
+    const TYPE: MetaType;
+
+    fn from_value(value: &Value) -> Option<Self>;
+    fn into_value(self) -> Value;
+}
+
+impl MetaValue for bool {
+    const TYPE: MetaType = MetaType::Bool;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+
+    fn into_value(self) -> Value {
+        Value::Bool(self)
+    }
+}
+
+impl MetaValue for i64 {
+    const TYPE: MetaType = MetaType::Int;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_i64()
+    }
+
+    fn into_value(self) -> Value {
+        Value::from(self)
+    }
+}
+
+impl MetaValue for f64 {
+    const TYPE: MetaType = MetaType::Float;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_f64()
+    }
+
+    fn into_value(self) -> Value {
+        Value::from(self)
+    }
+}
+
+impl MetaValue for String {
+    const TYPE: MetaType = MetaType::String;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_str().map(String::from)
+    }
+
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl MetaValue for Vec<Value> {
+    const TYPE: MetaType = MetaType::List;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_array().cloned()
+    }
+
+    fn into_value(self) -> Value {
+        Value::Array(self)
+    }
+}
+
+impl MetaValue for serde_json::Map<String, Value> {
+    const TYPE: MetaType = MetaType::Map;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_object().cloned()
+    }
+
+    fn into_value(self) -> Value {
+        Value::Object(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct MetaKey {
+    pub ty: MetaType,
+    pub default: Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct MetaSchema {
+    keys: HashMap<String, MetaKey>,
+    strict: bool,
+}
+
+impl MetaSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn standard() -> Self {
+        let mut schema = Self::new();
+        schema.keys.insert(
+            MOVED_META_KEY.into(),
+            MetaKey {
+                ty: MetaType::Bool,
+                default: Value::Bool(false),
+            },
+        );
+        schema.keys.insert(
+            HOOKS_META_KEY.into(),
+            MetaKey {
+                ty: MetaType::List,
+                default: Value::Array(Vec::new()),
+            },
+        );
+        schema.keys.insert(
+            SCRIPT_META_KEY.into(),
+            MetaKey {
+                ty: MetaType::String,
+                default: Value::Null,
+            },
+        );
+        schema
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        ty: MetaType,
+        default: impl Into<Value>,
+    ) -> Result<(), ChessError> {
+        let name = name.into();
+        let default = default.into();
+        if !default.is_null() && !ty.matches(&default) {
+            return Err(ChessError::ParseError(format!(
+                "default for meta key {} is not a {:?}",
+                name, ty
+            )));
+        }
+
+        self.keys.insert(name, MetaKey { ty, default });
+        Ok(())
+    }
+
+    pub fn key(&self, name: &str) -> Option<&MetaKey> {
+        self.keys.get(name)
+    }
+
+    pub fn validate(&self, entity: &PieceEntity) -> Result<(), ChessError> {
+        for (name, value) in entity.data.iter() {
+            match self.keys.get(name) {
+                Some(key) if !value.is_null() && !key.ty.matches(value) => {
+                    return Err(ChessError::ParseError(format!(
+                        "meta key {} on {} at {}:{} expected {:?}, got {}",
+                        name,
+                        entity.piece.fen_char(),
+                        entity.pos.rank,
+                        entity.pos.file,
+                        key.ty,
+                        value
+                    )))
+                }
+                None if self.strict => {
+                    return Err(ChessError::ParseError(format!(
+                        "unknown meta key {} on {} at {}:{}",
+                        name,
+                        entity.piece.fen_char(),
+                        entity.pos.rank,
+                        entity.pos.file
+                    )))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn value<'a>(&'a self, entity: &'a PieceEntity, name: &str) -> Option<&'a Value> {
+        entity
+            .get_meta(name)
+            .or_else(|| self.keys.get(name).map(|key| &key.default))
+            .filter(|value| !value.is_null())
+    }
+
+    pub fn get<T: MetaValue>(
+        &self,
+        entity: &PieceEntity,
+        name: &str,
+    ) -> Result<Option<T>, ChessError> {
+        self.check_type::<T>(name)?;
+
+        match self.value(entity, name) {
+            Some(value) => T::from_value(value).map(Some).ok_or_else(|| {
+                ChessError::InternalError(format!(
+                    "meta key {} holds {} instead of {:?}",
+                    name,
+                    value,
+                    T::TYPE
+                ))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set<T: MetaValue>(
+        &self,
+        entity: &mut PieceEntity,
+        name: &str,
+        value: T,
+    ) -> Result<(), ChessError> {
+        self.check_type::<T>(name)?;
+        entity.set_meta(name, value.into_value());
+        Ok(())
+    }
+
+    pub fn fill_defaults(&self, entity: &mut PieceEntity) -> () {
+        for (name, key) in self.keys.iter() {
+            if !key.default.is_null() && entity.get_meta(name).is_none() {
+                entity.set_meta(name.clone(), key.default.clone());
+            }
+        }
+    }
+
+    fn check_type<T: MetaValue>(&self, name: &str) -> Result<(), ChessError> {
+        match self.keys.get(name) {
+            Some(key) if key.ty != T::TYPE && key.ty != MetaType::Any => {
+                Err(ChessError::InternalError(format!(
+                    "meta key {} is registered as {:?}, not {:?}",
+                    name,
+                    key.ty,
+                    T::TYPE
+                )))
+            }
+            None if self.strict => Err(ChessError::InternalError(format!(
+                "unknown meta key {}",
+                name
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
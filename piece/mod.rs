@@ -1,6 +1,7 @@
 pub mod bishop;
 pub mod king;
 pub mod knight;
+pub mod meta;
 pub mod pawn;
 pub mod queen;
 pub mod rook;
@@ -12,8 +13,11 @@ use pawn::Promotion;
 
 use super::board::Board;
 use super::utils::ChessError;
+use meta::HOOKS_META_KEY;
+#[cfg(feature = "scripting")]
+use meta::SCRIPT_META_KEY;
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Copy)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Copy)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub enum Color {
     #[default]
@@ -45,7 +49,7 @@ impl Color {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Copy)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub enum Piece {
     Pawn(Color),
@@ -107,7 +111,7 @@ impl Piece {
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub struct Position {
     pub rank: usize,
@@ -115,43 +119,21 @@ pub struct Position {
 }
 
 impl Position {
-    pub fn shifted(&self, dx: i8, dy: i8) -> Option<Self> {
-        let new_file = self.file as i8 + dx;
-        let new_rank = self.rank as i8 + dy;
-
-        if (0..=7).contains(&new_file) && (0..=7).contains(&new_rank) {
-            Some(Position {
-                file: new_file as usize,
-                rank: new_rank as usize,
-            })
-        } else {
-            None
-        }
-    }
-
-    pub fn to_bitboard(self) -> u64 {
-        1u64 << self.to_index()
-    }
-
-    pub fn from_index(index: usize) -> Self {
-        Position {
-            file: index % 8,
-            rank: index / 8,
-        }
-    }
-
-    pub fn to_index(self) -> usize {
-        self.rank * 8 + self.file
-    }
-
-    pub fn from_fen(slice: &str) -> Self {
-        let file_char = slice.chars().nth(0).unwrap();
-        let rank_char = slice.chars().nth(1).unwrap();
-
-        let file = file_char as usize - 'a' as usize;
-        let rank = rank_char.to_digit(10).unwrap() as usize - 1;
-
-        Position { rank, file }
+    pub fn from_fen(slice: &str) -> Result<Self, ChessError> {
+        let err = || ChessError::ParseError(format!("invalid square name: {}", slice));
+        let mut chars = slice.chars();
+        let file = match chars.next() {
+            Some(ch @ 'a'..='z') => ch as usize - 'a' as usize,
+            _ => return Err(err()),
+        };
+        let rank = chars
+            .as_str()
+            .parse::<usize>()
+            .ok()
+            .and_then(|r| r.checked_sub(1))
+            .ok_or_else(err)?;
+
+        Ok(Position { rank, file })
     }
 }
 
@@ -169,16 +151,16 @@ impl Castle {
         let mut vec: Vec<char> = vec![];
 
         if self.white_king {
-            vec[0] = 'K';
+            vec.push('K');
         }
         if self.white_queen {
-            vec[1] = 'Q';
+            vec.push('Q');
         }
         if self.black_king {
-            vec[2] = 'k';
+            vec.push('k');
         }
         if self.black_queen {
-            vec[3] = 'q';
+            vec.push('q');
         }
 
         if vec.is_empty() {
@@ -195,6 +177,7 @@ impl Castle {
                 'Q' => self.white_queen = true,
                 'k' => self.black_king = true,
                 'q' => self.black_queen = true,
+                '-' => continue,
                 _ => {
                     return Err(ChessError::ParseError(
                         "couldnt parse castling rights into char".into(),
@@ -217,9 +200,37 @@ pub struct MoveMeta {
     pub capture: Option<Position>,
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct Move {
+    pub from: Position,
+    pub to: Position,
+    pub promotion: Option<Piece>,
+}
+
+impl Move {
+    pub fn new(from: Position, to: Position) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+}
+
+impl From<&MoveMeta> for Move {
+    fn from(meta: &MoveMeta) -> Self {
+        Self {
+            from: meta.from,
+            to: meta.to,
+            promotion: meta.promotion,
+        }
+    }
+}
+
 pub type PieceEntityData = HashMap<String, serde_json::Value>;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub struct PieceEntity {
     pub piece: Piece,
@@ -244,7 +255,30 @@ impl PieceEntity {
         self.data.get(key)
     }
 
+    pub fn add_hook(&mut self, name: impl Into<String>) -> () {
+        let name = serde_json::Value::String(name.into());
+        match self.data.get_mut(HOOKS_META_KEY) {
+            Some(serde_json::Value::Array(names)) => {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            _ => self.set_meta(HOOKS_META_KEY, vec![name]),
+        }
+    }
+
+    pub fn remove_hook(&mut self, name: &str) -> () {
+        if let Some(serde_json::Value::Array(names)) = self.data.get_mut(HOOKS_META_KEY) {
+            names.retain(|n| n.as_str() != Some(name));
+        }
+    }
+
     pub fn legal_moves(&self, board: &Board) -> Result<Vec<Position>, ChessError> {
+        #[cfg(feature = "scripting")]
+        if let Some(script) = self.get_meta(SCRIPT_META_KEY).and_then(|v| v.as_str()) {
+            return board.scripts().moves(script, self, board);
+        }
+
         Ok(match self.piece {
             Piece::Pawn(_c) => pawn::legal_moves(self, board)?,
             Piece::Knight(_c) => knight::legal_moves(self, board)?,
@@ -255,19 +289,35 @@ impl PieceEntity {
         })
     }
 
+    pub fn attacks(&self, board: &Board) -> Result<Vec<Position>, ChessError> {
+        Ok(match self.piece {
+            Piece::Pawn(_c) => pawn::attacks(self, board),
+            Piece::King(_c) => king::attacks(self, board),
+            _ => self.legal_moves(board)?,
+        })
+    }
+
     pub fn on_move(
         &mut self,
         new_pos: Position,
         board: &mut Board,
         promotion: Option<Promotion>,
     ) -> Result<MoveMeta, ChessError> {
-        Ok(match self.piece {
+        let meta = match self.piece {
             Piece::Pawn(_c) => pawn::on_move(self, new_pos, board, promotion)?,
             Piece::Knight(_c) => knight::on_move(self, new_pos, board)?,
             Piece::Bishop(_c) => bishop::on_move(self, new_pos, board)?,
             Piece::King(_c) => king::on_move(self, new_pos, board)?,
             Piece::Rook(_c) => rook::on_move(self, new_pos, board)?,
             Piece::Queen(_c) => queen::on_move(self, new_pos, board)?,
-        })
+        };
+
+        #[cfg(feature = "scripting")]
+        if let Some(script) = self.get_meta(SCRIPT_META_KEY).and_then(|v| v.as_str()) {
+            let script = script.to_string();
+            board.scripts().on_move(&script, self, new_pos, board)?;
+        }
+
+        Ok(meta)
     }
 }
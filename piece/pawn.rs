@@ -1,5 +1,6 @@
 use super::super::board::Board;
-use super::super::utils::{validate_pos, ChessError};
+use super::super::utils::ChessError;
+use super::meta::MOVED_META_KEY;
 use super::{Color, MoveMeta, Piece, PieceEntity, Position};
 
 pub fn legal_moves(entity: &PieceEntity, board: &Board) -> Result<Vec<Position>, ChessError> {
@@ -10,25 +11,30 @@ pub fn legal_moves(entity: &PieceEntity, board: &Board) -> Result<Vec<Position>,
         Color::Black => -1,
     };
 
+    let geometry = board.geometry();
     let rank_forward = entity.pos.rank as i8 + dir;
-    if board.is_empty(rank_forward as usize, entity.pos.file)?
-        && validate_pos(rank_forward, entity.pos.file as i8)
+    if geometry.contains(rank_forward, entity.pos.file as i8)
+        && board.is_empty(rank_forward as usize, entity.pos.file)?
     {
         moves.push(Position {
             rank: rank_forward as usize,
             file: entity.pos.file,
         });
 
-        if let Some(serde_json::Value::Bool(moved_data)) = entity.get_meta("moved") {
-            if *moved_data == false {
-                let two_forward = rank_forward + dir;
-                if validate_pos(two_forward, entity.pos.file as i8) {
-                    if board.is_empty(two_forward as usize, entity.pos.file)? {
-                        moves.push(Position {
-                            rank: two_forward as usize,
-                            file: entity.pos.file,
-                        });
-                    }
+        let has_moved = board
+            .meta_schema()
+            .get::<bool>(entity, MOVED_META_KEY)?
+            .unwrap_or(false);
+        // pawns without a moved flag may still have left their start rank
+        let on_start = entity.pos.rank == geometry.pawn_rank(entity.piece.color());
+        if on_start && !has_moved {
+            let two_forward = rank_forward + dir;
+            if geometry.contains(two_forward, entity.pos.file as i8) {
+                if board.is_empty(two_forward as usize, entity.pos.file)? {
+                    moves.push(Position {
+                        rank: two_forward as usize,
+                        file: entity.pos.file,
+                    });
                 }
             }
         }
@@ -36,7 +42,7 @@ pub fn legal_moves(entity: &PieceEntity, board: &Board) -> Result<Vec<Position>,
 
     for d_file in [-1, 1] {
         let file = entity.pos.file as i8 + d_file;
-        if validate_pos(rank_forward, file)
+        if geometry.contains(rank_forward, file)
             && board.is_enemy(rank_forward as usize, file as usize, entity.piece.color())?
         {
             moves.push(Position {
@@ -46,13 +52,11 @@ pub fn legal_moves(entity: &PieceEntity, board: &Board) -> Result<Vec<Position>,
         }
 
         if let Some(p_target) = board.get_passant_target() {
-            let file = p_target.file as i8 + d_file;
-
-            if !validate_pos(entity.pos.rank as i8, file) {
+            if !geometry.contains(entity.pos.rank as i8, file) {
                 continue;
             }
 
-            if entity.pos.rank as i8 != p_target.rank as i8 + dir {
+            if p_target.rank as i8 != rank_forward || p_target.file as i8 != file {
                 continue;
             }
 
@@ -77,6 +81,18 @@ pub fn legal_moves(entity: &PieceEntity, board: &Board) -> Result<Vec<Position>,
     Ok(moves)
 }
 
+pub fn attacks(entity: &PieceEntity, board: &Board) -> Vec<Position> {
+    let dir = match entity.piece.color() {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+
+    [-1, 1]
+        .iter()
+        .filter_map(|&d_file| board.geometry().shift(entity.pos, d_file, dir))
+        .collect()
+}
+
 pub struct Promotion {
     pub pawn_pos: Position,
     pub new_piece: Piece,
@@ -91,7 +107,7 @@ pub fn on_move(
     if (entity.pos.rank as i8 - new_pos.rank as i8).abs() == 2 {
         for d_file in [-1, 1] {
             let file = entity.pos.file as i8 + d_file;
-            if validate_pos(new_pos.rank as i8, file) {
+            if board.geometry().contains(new_pos.rank as i8, file) {
                 match board.get(new_pos.rank, file as usize)? {
                     Some(p) => {
                         let p_target_rank: usize = match entity.piece.color() {
@@ -117,7 +133,7 @@ pub fn on_move(
         entity.piece = promotion_data.new_piece.clone();
     }
 
-    entity.set_meta("moved", true);
+    entity.set_meta(MOVED_META_KEY, true);
 
     Ok(MoveMeta {
         piece: prev_piece,
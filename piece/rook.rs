@@ -1,5 +1,6 @@
 use super::super::board::Board;
 use super::super::utils::{sliding_moves, ChessError};
+use super::meta::MOVED_META_KEY;
 use super::{MoveMeta, PieceEntity, Position};
 
 static DIRS: &[(i8, i8)] = &[(1, 0), (-1, 0), (0, 1), (0, -1)];
@@ -18,7 +19,7 @@ pub fn on_move(
     new_pos: Position,
     board: &mut Board,
 ) -> Result<MoveMeta, ChessError> {
-    entity.set_meta("moved", true);
+    entity.set_meta(MOVED_META_KEY, true);
     Ok(MoveMeta {
         piece: entity.piece.clone(),
         from: entity.pos,
@@ -0,0 +1,255 @@
+use super::board::Board;
+use super::piece::Move;
+use super::rules::{is_checkmate, legal_moves};
+use super::utils::ChessError;
+
+/// Columns of the open puzzle database CSV export, used when a file has no
+/// header line.
+const DEFAULT_COLUMNS: [&str; 10] = [
+    "PuzzleId",
+    "FEN",
+    "Moves",
+    "Rating",
+    "RatingDeviation",
+    "Popularity",
+    "NbPlays",
+    "Themes",
+    "GameUrl",
+    "OpeningTags",
+];
+
+/// A tactic to solve: the opponent plays `setup` from `fen`, then the solver
+/// and the opponent alternate through `solution`, the solver moving last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub id: String,
+    pub fen: String,
+    pub setup: Move,
+    pub solution: Vec<Move>,
+    pub themes: Vec<String>,
+    pub rating: u32,
+}
+
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+impl Puzzle {
+    /// `moves` holds the setup move and the solution in UCI notation,
+    /// separated by spaces. Every move is checked to be legal.
+    pub fn new(id: impl Into<String>, fen: &str, moves: &str) -> Result<Self, ChessError> {
+        let id = id.into();
+        let mut board = Board::new();
+        board.from_fen(fen.to_string())?;
+
+        let mut line = Vec::new();
+        for text in moves.split_whitespace() {
+            let mv = Move::from_uci(text, board.geometry(), board.get_turn())?;
+            let turn = board.get_turn();
+            if !legal_moves(&mut board, turn)?.contains(&mv) {
+                return Err(ChessError::IllegalMove(format!(
+                    "puzzle {}: illegal move {}",
+                    id, text
+                )));
+            }
+            board.make_move(mv.from, mv.to, mv.promotion)?;
+            line.push(mv);
+        }
+
+        // the solver has the last word
+        if line.len() < 2 || line.len() % 2 != 0 {
+            return Err(ChessError::ParseError(format!(
+                "puzzle {}: expected a setup move and a solution ending with the solver's move",
+                id
+            )));
+        }
+        Ok(Self {
+            id,
+            fen: fen.to_string(),
+            setup: line[0],
+            solution: line[1..].to_vec(),
+            themes: Vec::new(),
+            rating: 0,
+        })
+    }
+
+    /// Parses puzzles in the CSV layout of the open puzzle database:
+    /// `PuzzleId,FEN,Moves,Rating,...,Themes,...`. A header line may name the
+    /// columns in another order; themes are separated by spaces.
+    pub fn from_csv(csv: &str) -> Result<Vec<Self>, ChessError> {
+        let mut lines = csv
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .peekable();
+        let mut columns: Vec<String> = DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect();
+        if let Some(header) = lines.peek() {
+            let header = csv_fields(header);
+            if header.iter().any(|field| field.trim() == "PuzzleId") {
+                columns = header
+                    .into_iter()
+                    .map(|field| field.trim().to_string())
+                    .collect();
+                lines.next();
+            }
+        }
+        let column = |name: &str| -> Result<usize, ChessError> {
+            columns
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| ChessError::ParseError(format!("missing puzzle column {}", name)))
+        };
+        let (id, fen, moves) = (column("PuzzleId")?, column("FEN")?, column("Moves")?);
+        let (rating, themes) = (column("Rating").ok(), column("Themes").ok());
+
+        let mut puzzles = Vec::new();
+        for line in lines {
+            let fields = csv_fields(line);
+            let field = |index: usize| fields.get(index).map_or("", |field| field.trim());
+
+            let mut puzzle = Self::new(field(id), field(fen), field(moves))?;
+            if let Some(rating) = rating.map(field).filter(|rating| !rating.is_empty()) {
+                puzzle.rating = rating.parse().map_err(|_| {
+                    ChessError::ParseError(format!(
+                        "puzzle {}: invalid rating {}",
+                        puzzle.id, rating
+                    ))
+                })?;
+            }
+            if let Some(themes) = themes.map(field) {
+                puzzle.themes = themes.split_whitespace().map(String::from).collect();
+            }
+            puzzles.push(puzzle);
+        }
+        Ok(puzzles)
+    }
+
+    pub fn load_csv(path: &str) -> Result<Vec<Self>, ChessError> {
+        let csv =
+            std::fs::read_to_string(path).map_err(|e| ChessError::ParseError(e.to_string()))?;
+        Self::from_csv(&csv)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuzzleStatus {
+    Playing,
+    Solved,
+    Failed,
+}
+
+/// Answer to a move played in a `PuzzleSession`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The move was right and the opponent answered with `reply`.
+    Correct {
+        reply: Move,
+    },
+    Solved,
+    /// The move was not played; `expected` was.
+    Wrong {
+        expected: Move,
+    },
+}
+
+/// One attempt at a puzzle. The opponent's moves are played automatically.
+#[derive(Debug, Clone)]
+pub struct PuzzleSession {
+    puzzle: Puzzle,
+    board: Board,
+    /// Index of the next solution move.
+    next: usize,
+    status: PuzzleStatus,
+}
+
+impl PuzzleSession {
+    /// Starts from the puzzle position with the setup move already played.
+    pub fn new(puzzle: Puzzle) -> Result<Self, ChessError> {
+        let mut board = Board::new();
+        board.from_fen(puzzle.fen.clone())?;
+        board.make_move(puzzle.setup.from, puzzle.setup.to, puzzle.setup.promotion)?;
+        Ok(Self {
+            puzzle,
+            board,
+            next: 0,
+            status: PuzzleStatus::Playing,
+        })
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn status(&self) -> PuzzleStatus {
+        self.status
+    }
+
+    /// The move the solution expects next, while the puzzle is being played.
+    pub fn hint(&self) -> Option<Move> {
+        match self.status {
+            PuzzleStatus::Playing => self.puzzle.solution.get(self.next).copied(),
+            _ => None,
+        }
+    }
+
+    /// Checks the solver's move against the solution. On the last move any
+    /// checkmate counts, as puzzles often have several.
+    pub fn play(&mut self, mv: Move) -> Result<Verdict, ChessError> {
+        let Some(expected) = self.hint() else {
+            return Err(ChessError::IllegalMove(format!(
+                "puzzle {} is over",
+                self.puzzle.id
+            )));
+        };
+        let color = self.board.get_turn();
+        if !legal_moves(&mut self.board, color)?.contains(&mv) {
+            return Err(ChessError::IllegalMove(format!(
+                "illegal move {}",
+                mv.to_uci(self.board.geometry())
+            )));
+        }
+
+        let last = self.next + 1 == self.puzzle.solution.len();
+        let mut after = self.board.clone();
+        after.make_move(mv.from, mv.to, mv.promotion)?;
+        let mates = last && is_checkmate(&mut after, color.opposite())?;
+        if mv != expected && !mates {
+            self.status = PuzzleStatus::Failed;
+            return Ok(Verdict::Wrong { expected });
+        }
+
+        self.board = after;
+        self.next += 1;
+        if last {
+            self.status = PuzzleStatus::Solved;
+            return Ok(Verdict::Solved);
+        }
+        let reply = self.puzzle.solution[self.next];
+        self.board
+            .make_move(reply.from, reply.to, reply.promotion)?;
+        self.next += 1;
+        Ok(Verdict::Correct { reply })
+    }
+
+    pub fn play_uci(&mut self, text: &str) -> Result<Verdict, ChessError> {
+        let mv = Move::from_uci(text, self.board.geometry(), self.board.get_turn())?;
+        self.play(mv)
+    }
+}
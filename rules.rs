@@ -1,15 +1,16 @@
 use super::board::Board;
-use super::piece::{Color, Piece, Position};
-use super::utils::{all_positions, ChessError};
+use super::clock::Clock;
+use super::piece::{Color, Move, Piece, PieceEntity, Position};
+use super::utils::ChessError;
 
 pub fn is_in_check(board: &Board, color: Color) -> Result<bool, ChessError> {
     let king_pos = board
         .get_king_pos(color)?
         .ok_or_else(|| ChessError::InternalError(format!("{:?} king not found", color)))?;
 
-    for pos in all_positions() {
+    for pos in board.geometry().positions() {
         if let Some(p) = board.get_entity(pos.rank, pos.file) {
-            if p.piece != Piece::King(color) && p.legal_moves(board)?.contains(&king_pos) {
+            if p.piece.color() != color && p.attacks(board)?.contains(&king_pos) {
                 return Ok(true);
             }
         }
@@ -33,6 +34,15 @@ pub fn filter_moves(
             let mut new_p = p_ent.clone();
             new_p.pos = mv;
 
+            // en passant: a pawn moving diagonally onto an empty square takes the pawn beside it
+            if matches!(p_ent.piece, Piece::Pawn(_))
+                && mv.file != from.file
+                && board.is_empty(mv.rank, mv.file)?
+            {
+                b_clone.set(from.rank, mv.file, None)?;
+                b_clone.set_entity(from.rank, mv.file, None);
+            }
+
             b_clone.set(from.rank, from.file, None)?;
             b_clone.set_entity(from.rank, from.file, None);
             b_clone.set(mv.rank, mv.file, Some(new_p.piece.clone()))?;
@@ -53,7 +63,9 @@ pub fn is_checkmate(board: &mut Board, color: Color) -> Result<bool, ChessError>
         return Ok(false);
     }
 
-    let piece_positions: Vec<Position> = all_positions()
+    let piece_positions: Vec<Position> = board
+        .geometry()
+        .positions()
         .filter(|from| {
             board
                 .get_entity(from.rank, from.file)
@@ -78,7 +90,9 @@ pub fn is_stalemate(board: &mut Board, color: Color) -> Result<bool, ChessError>
         return Ok(false);
     }
 
-    let piece_positions: Vec<Position> = all_positions()
+    let piece_positions: Vec<Position> = board
+        .geometry()
+        .positions()
         .filter(|from| {
             board
                 .get_entity(from.rank, from.file)
@@ -97,3 +111,112 @@ pub fn is_stalemate(board: &mut Board, color: Color) -> Result<bool, ChessError>
 
     Ok(true)
 }
+
+pub fn legal_moves(board: &mut Board, color: Color) -> Result<Vec<Move>, ChessError> {
+    let last_rank = match color {
+        Color::White => board.geometry().height - 1,
+        Color::Black => 0,
+    };
+    let pieces: Vec<PieceEntity> = board
+        .geometry()
+        .positions()
+        .filter_map(|pos| board.get_entity(pos.rank, pos.file))
+        .filter(|entity| entity.piece.color() == color)
+        .cloned()
+        .collect();
+
+    let mut moves = Vec::new();
+    for entity in pieces {
+        let mut targets = entity.legal_moves(board)?;
+        filter_moves(board, &mut targets, entity.pos, color)?;
+
+        for to in targets {
+            if matches!(entity.piece, Piece::Pawn(_)) && to.rank == last_rank {
+                for promotion in [
+                    Piece::Queen(color),
+                    Piece::Rook(color),
+                    Piece::Bishop(color),
+                    Piece::Knight(color),
+                ] {
+                    moves.push(Move {
+                        from: entity.pos,
+                        to,
+                        promotion: Some(promotion),
+                    });
+                }
+            } else {
+                moves.push(Move::new(entity.pos, to));
+            }
+        }
+    }
+
+    Ok(moves)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Checkmate { winner: Color },
+    Timeout { winner: Color },
+    Stalemate,
+    TimeoutVsInsufficientMaterial,
+    InsufficientMaterial,
+    FiftyMoves,
+}
+
+impl Outcome {
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            Outcome::Checkmate { winner } | Outcome::Timeout { winner } => Some(*winner),
+            _ => None,
+        }
+    }
+}
+
+pub fn has_mating_material(board: &Board, color: Color) -> Result<bool, ChessError> {
+    let mut minors = 0;
+    let mut opponent_material = false;
+
+    for pos in board.geometry().positions() {
+        match board.get(pos.rank, pos.file)? {
+            Some(Piece::King(_)) | None => {}
+            Some(p) if p.color() != color => opponent_material = true,
+            Some(Piece::Knight(_) | Piece::Bishop(_)) => minors += 1,
+            Some(_) => return Ok(true),
+        }
+    }
+
+    // a lone minor piece can only mate when the enemy has something to block its king with
+    Ok(minors >= 2 || (minors == 1 && opponent_material))
+}
+
+pub fn is_insufficient_material(board: &Board) -> Result<bool, ChessError> {
+    Ok(!has_mating_material(board, Color::White)? && !has_mating_material(board, Color::Black)?)
+}
+
+pub fn outcome(board: &mut Board) -> Result<Option<Outcome>, ChessError> {
+    if let Some(flagged) = board.clock_mut().and_then(Clock::check_flag) {
+        let winner = flagged.opposite();
+        return Ok(Some(match has_mating_material(board, winner)? {
+            true => Outcome::Timeout { winner },
+            false => Outcome::TimeoutVsInsufficientMaterial,
+        }));
+    }
+
+    let color = board.get_turn();
+    if is_checkmate(board, color)? {
+        return Ok(Some(Outcome::Checkmate {
+            winner: color.opposite(),
+        }));
+    }
+    if is_stalemate(board, color)? {
+        return Ok(Some(Outcome::Stalemate));
+    }
+    if is_insufficient_material(board)? {
+        return Ok(Some(Outcome::InsufficientMaterial));
+    }
+    if board.get_halfmove() >= 100 {
+        return Ok(Some(Outcome::FiftyMoves));
+    }
+
+    Ok(None)
+}
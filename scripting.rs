@@ -0,0 +1,273 @@
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::board::Board;
+use super::geometry::Geometry;
+use super::piece::{Color, Piece, PieceEntity, PieceEntityData, Position};
+use super::utils::ChessError;
+
+pub use super::piece::meta::SCRIPT_META_KEY;
+
+thread_local! {
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub max_time: Duration,
+    pub max_call_levels: usize,
+    pub max_collection_size: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 100_000,
+            max_time: Duration::from_millis(50),
+            max_call_levels: 32,
+            max_collection_size: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BoardView {
+    geometry: Geometry,
+    squares: Vec<Option<Piece>>,
+    turn: Color,
+}
+
+impl BoardView {
+    pub fn new(board: &Board) -> Result<Self, ChessError> {
+        let geometry = board.geometry();
+        let squares = geometry
+            .positions()
+            .map(|pos| board.get(pos.rank, pos.file))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            geometry,
+            squares,
+            turn: board.get_turn(),
+        })
+    }
+
+    fn piece(&self, rank: i64, file: i64) -> Option<Piece> {
+        if !self.in_bounds(rank, file) {
+            return None;
+        }
+        let pos = Position {
+            rank: rank as usize,
+            file: file as usize,
+        };
+        self.squares[self.geometry.index(pos)]
+    }
+
+    fn in_bounds(&self, rank: i64, file: i64) -> bool {
+        (0..self.geometry.height as i64).contains(&rank)
+            && (0..self.geometry.width as i64).contains(&file)
+    }
+
+    fn piece_at(&mut self, rank: i64, file: i64) -> String {
+        self.piece(rank, file)
+            .map_or(String::new(), |p| p.fen_char().to_string())
+    }
+
+    fn is_empty(&mut self, rank: i64, file: i64) -> bool {
+        self.in_bounds(rank, file) && self.piece(rank, file).is_none()
+    }
+
+    fn is_enemy(&mut self, rank: i64, file: i64, color: &str) -> bool {
+        self.piece(rank, file)
+            .is_some_and(|p| color_name(p.color()) != color)
+    }
+
+    fn can_move_to(&mut self, rank: i64, file: i64, color: &str) -> bool {
+        self.is_empty(rank, file) || self.is_enemy(rank, file, color)
+    }
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
+}
+
+fn script_error(name: &str, err: Box<EvalAltResult>) -> ChessError {
+    ChessError::InternalError(format!("script {}: {}", name, err))
+}
+
+pub struct ScriptRegistry {
+    engine: Engine,
+    scripts: HashMap<String, AST>,
+    limits: ScriptLimits,
+}
+
+impl ScriptRegistry {
+    pub fn new() -> Self {
+        Self::with_limits(ScriptLimits::default())
+    }
+
+    pub fn with_limits(limits: ScriptLimits) -> Self {
+        let mut engine = Engine::new();
+
+        engine
+            .set_max_operations(limits.max_operations)
+            .set_max_call_levels(limits.max_call_levels)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(limits.max_collection_size)
+            .set_max_array_size(limits.max_collection_size)
+            .set_max_map_size(limits.max_collection_size)
+            .disable_symbol("eval");
+
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+        engine.on_progress(|_| {
+            DEADLINE.with(|deadline| match deadline.get() {
+                Some(at) if Instant::now() > at => Some(Dynamic::from("script timed out")),
+                _ => None,
+            })
+        });
+
+        engine
+            .register_type_with_name::<BoardView>("BoardView")
+            .register_get("width", |b: &mut BoardView| b.geometry.width as i64)
+            .register_get("height", |b: &mut BoardView| b.geometry.height as i64)
+            .register_get("turn", |b: &mut BoardView| color_name(b.turn).to_string())
+            .register_fn("in_bounds", |b: &mut BoardView, rank: i64, file: i64| {
+                b.in_bounds(rank, file)
+            })
+            .register_fn("piece_at", BoardView::piece_at)
+            .register_fn("is_empty", BoardView::is_empty)
+            .register_fn("is_enemy", BoardView::is_enemy)
+            .register_fn("can_move_to", BoardView::can_move_to);
+
+        Self {
+            engine,
+            scripts: HashMap::new(),
+            limits,
+        }
+    }
+
+    pub fn limits(&self) -> ScriptLimits {
+        self.limits
+    }
+
+    pub fn compile(&mut self, name: impl Into<String>, source: &str) -> Result<(), ChessError> {
+        let name = name.into();
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|err| ChessError::ParseError(format!("script {}: {}", name, err)))?;
+
+        self.scripts.insert(name, ast);
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.scripts.contains_key(name)
+    }
+
+    pub fn moves(
+        &self,
+        name: &str,
+        entity: &PieceEntity,
+        board: &Board,
+    ) -> Result<Vec<Position>, ChessError> {
+        let result = self.call(name, "moves", entity, board, Vec::new())?;
+        let candidates: Vec<Position> =
+            rhai::serde::from_dynamic(&result).map_err(|err| script_error(name, err))?;
+
+        let mut moves = Vec::with_capacity(candidates.len());
+        for pos in candidates {
+            if !board.geometry().contains_pos(pos) || moves.contains(&pos) {
+                continue;
+            }
+            if board.can_move_to(pos, Some(entity.piece.color()))? {
+                moves.push(pos);
+            }
+        }
+
+        Ok(moves)
+    }
+
+    pub fn on_move(
+        &self,
+        name: &str,
+        entity: &mut PieceEntity,
+        new_pos: Position,
+        board: &Board,
+    ) -> Result<(), ChessError> {
+        if !self.has_fn(name, "on_move") {
+            return Ok(());
+        }
+
+        let to = rhai::serde::to_dynamic(new_pos).map_err(|err| script_error(name, err))?;
+        let result = self.call(name, "on_move", entity, board, vec![to])?;
+        if result.is_unit() {
+            return Ok(());
+        }
+
+        let effects: PieceEntityData =
+            rhai::serde::from_dynamic(&result).map_err(|err| script_error(name, err))?;
+        for (key, value) in effects {
+            entity.set_meta(key, value);
+        }
+
+        Ok(())
+    }
+
+    fn has_fn(&self, name: &str, func: &str) -> bool {
+        self.scripts
+            .get(name)
+            .is_some_and(|ast| ast.iter_functions().any(|f| f.name == func))
+    }
+
+    fn call(
+        &self,
+        name: &str,
+        func: &str,
+        entity: &PieceEntity,
+        board: &Board,
+        extra: Vec<Dynamic>,
+    ) -> Result<Dynamic, ChessError> {
+        let ast = self
+            .scripts
+            .get(name)
+            .ok_or_else(|| ChessError::InternalError(format!("script {} not registered", name)))?;
+
+        let mut args = vec![
+            Dynamic::from(BoardView::new(board)?),
+            rhai::serde::to_dynamic(entity).map_err(|err| script_error(name, err))?,
+        ];
+        args.extend(extra);
+
+        let deadline = Instant::now() + self.limits.max_time;
+        let previous = DEADLINE.with(|d| d.replace(Some(deadline)));
+        let result = self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), ast, func, args);
+        DEADLINE.with(|d| d.set(previous));
+
+        result.map_err(|err| script_error(name, err))
+    }
+}
+
+impl Default for ScriptRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ScriptRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptRegistry")
+            .field("scripts", &self.scripts.keys().collect::<Vec<_>>())
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
@@ -0,0 +1,555 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::board::Board;
+use super::eval::{default_params, evaluate_with, EvalParams};
+use super::ordering::MoveOrderer;
+use super::piece::{Color, Move, Piece};
+use super::rules::{is_in_check, legal_moves};
+use super::timeman::{SearchClock, TimeManager};
+use super::tt::{cuts_off, score_from_tt, score_to_tt, Bound, TranspositionTable, TtEntry};
+use super::utils::ChessError;
+
+pub const MATE: i32 = 30_000;
+pub const INFINITY: i32 = 32_000;
+pub const MAX_DEPTH: u32 = 64;
+
+/// Scores beyond this are mates, with the distance encoded in the remainder.
+pub const MATE_BOUND: i32 = MATE - 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+impl Score {
+    pub fn from_value(value: i32) -> Self {
+        match value.abs() >= MATE_BOUND {
+            true => {
+                let moves = (MATE - value.abs() + 1) / 2;
+                Score::Mate(value.signum() * moves)
+            }
+            false => Score::Centipawns(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    pub infinite: bool,
+    /// Search threads; 0 and 1 both run the deterministic single-thread search.
+    pub threads: usize,
+    /// Clock of the side to move, left to the time manager.
+    pub clock: Option<SearchClock>,
+    /// Set while pondering; the clock only starts once it is cleared.
+    pub pondering: Option<Arc<AtomicBool>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchInfo {
+    pub depth: u32,
+    pub score: Score,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub pv: Vec<Move>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: Score,
+    pub depth: u32,
+    pub nodes: u64,
+    pub pv: Vec<Move>,
+}
+
+pub fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn(_) => 100,
+        Piece::Knight(_) => 320,
+        Piece::Bishop(_) => 330,
+        Piece::Rook(_) => 500,
+        Piece::Queen(_) => 900,
+        Piece::King(_) => 0,
+    }
+}
+
+/// Half-width of the first aspiration window around the previous score.
+const ASPIRATION_WINDOW: i32 = 50;
+const NULL_MOVE_REDUCTION: u32 = 2;
+const LMR_MIN_DEPTH: u32 = 3;
+const LMR_MIN_MOVES: usize = 3;
+/// Hard cap on ply for extensions and quiescence.
+const MAX_PLY: i32 = 128;
+/// Upper bound for `SearchLimits::threads`.
+pub const MAX_THREADS: usize = 256;
+
+struct Searcher<'a> {
+    limits: &'a SearchLimits,
+    params: &'a EvalParams,
+    tt: &'a TranspositionTable,
+    orderer: MoveOrderer,
+    stop: &'a AtomicBool,
+    start: Instant,
+    nodes: u64,
+    aborted: bool,
+    root_best: Option<Move>,
+    time: Option<TimeManager>,
+    after_null: bool,
+}
+
+impl<'a> Searcher<'a> {
+    fn new(
+        limits: &'a SearchLimits,
+        params: &'a EvalParams,
+        tt: &'a TranspositionTable,
+        stop: &'a AtomicBool,
+        root_moves: &[Move],
+    ) -> Self {
+        let time = limits.clock.filter(|_| !limits.infinite).map(|control| {
+            TimeManager::new(control, root_moves.len() == 1, limits.pondering.clone())
+        });
+        Self {
+            limits,
+            params,
+            tt,
+            orderer: MoveOrderer::new(),
+            stop,
+            start: Instant::now(),
+            nodes: 0,
+            aborted: false,
+            root_best: root_moves.first().copied(),
+            time,
+            after_null: false,
+        }
+    }
+
+    /// Iterative deepening from `first_depth` with aspiration windows.
+    fn iterate(
+        &mut self,
+        root: &Board,
+        first_depth: u32,
+        mut on_info: impl FnMut(&SearchInfo),
+    ) -> Result<SearchResult, ChessError> {
+        let mut result = SearchResult {
+            best_move: self.root_best,
+            score: Score::Centipawns(0),
+            depth: 0,
+            nodes: 0,
+            pv: self.root_best.into_iter().collect(),
+        };
+
+        let max_depth = self.limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        let mut pv = Vec::new();
+        let mut previous: i32 = 0;
+        for depth in first_depth.min(max_depth)..=max_depth {
+            let (mut alpha, mut beta) = match depth >= 4 && previous.abs() < MATE_BOUND {
+                true => (previous - ASPIRATION_WINDOW, previous + ASPIRATION_WINDOW),
+                false => (-INFINITY, INFINITY),
+            };
+            let mut delta = ASPIRATION_WINDOW;
+
+            // widen the window on the failing side until the score lands inside it
+            let value = loop {
+                let value = self.negamax(root, depth, 0, alpha, beta, &mut pv)?;
+                if self.aborted {
+                    break value;
+                }
+                if value <= alpha {
+                    alpha = (alpha - delta).max(-INFINITY);
+                } else if value >= beta {
+                    beta = (beta + delta).min(INFINITY);
+                } else {
+                    break value;
+                }
+                delta *= 2;
+            };
+            if self.aborted {
+                break;
+            }
+
+            previous = value;
+            result.best_move = pv.first().copied().or(result.best_move);
+            self.root_best = result.best_move;
+            result.score = Score::from_value(value);
+            result.depth = depth;
+            result.nodes = self.nodes;
+            result.pv = pv.clone();
+            on_info(&SearchInfo {
+                depth,
+                score: result.score,
+                nodes: self.nodes,
+                elapsed: self.start.elapsed(),
+                pv: pv.clone(),
+            });
+
+            if value.abs() >= MATE_BOUND {
+                break;
+            }
+            if let Some(time) = self.time.as_mut() {
+                time.update(result.best_move, value);
+                if time.stop_iterating() {
+                    break;
+                }
+            }
+        }
+
+        result.nodes = self.nodes;
+        Ok(result)
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if !self.aborted {
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes)
+                || self
+                    .limits
+                    .movetime
+                    .is_some_and(|movetime| self.start.elapsed() >= movetime)
+                || self
+                    .time
+                    .as_mut()
+                    .is_some_and(|time| time.out_of_time(self.stop));
+        }
+        self.aborted
+    }
+
+    fn negamax(
+        &mut self,
+        board: &Board,
+        mut depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> Result<i32, ChessError> {
+        // a null move may not directly follow another one
+        let allow_null = !std::mem::take(&mut self.after_null);
+        self.nodes += 1;
+        pv.clear();
+        if self.should_stop() {
+            return Ok(0);
+        }
+
+        let color = board.get_turn();
+        let in_check = is_in_check(board, color)?;
+        if in_check && ply < MAX_PLY {
+            depth += 1;
+        }
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(board, ply, alpha, beta, pv);
+        }
+
+        let geometry = board.geometry();
+        let hash = board.zobrist_hash();
+        let entry = self.tt.probe(hash, geometry, color);
+        if let Some(entry) = entry {
+            // only null-window nodes take cutoffs, so the principal variation stays intact
+            let score = score_from_tt(entry.score, ply);
+            let settled = TtEntry { score, ..entry };
+            if ply > 0
+                && beta - alpha == 1
+                && entry.depth >= depth
+                && cuts_off(&settled, alpha, beta)
+            {
+                return Ok(score);
+            }
+        }
+
+        let mut scratch = board.clone();
+        let mut moves = legal_moves(&mut scratch, color)?;
+        if moves.is_empty() {
+            return Ok(match in_check {
+                true => -MATE + ply,
+                false => 0,
+            });
+        }
+        if board.get_halfmove() >= 100 {
+            return Ok(0);
+        }
+
+        let mut child_pv = Vec::new();
+        let null_ok = allow_null && ply > 0 && !in_check && depth >= 3 && beta.abs() < MATE_BOUND;
+        if null_ok && has_pieces(board, color) {
+            let mut child = board.clone();
+            child.set_turn(color.opposite());
+            child.set_passant_target(None);
+
+            self.after_null = true;
+            let reduced = depth - 1 - NULL_MOVE_REDUCTION;
+            let score = -self.negamax(&child, reduced, ply + 1, -beta, -beta + 1, &mut child_pv)?;
+            if self.aborted {
+                return Ok(0);
+            }
+            if score >= beta {
+                return Ok(beta);
+            }
+        }
+
+        let first = match ply {
+            0 => self.root_best,
+            _ => entry.and_then(|entry| entry.best_move),
+        };
+        self.orderer.order(board, &mut moves, first, ply as usize)?;
+        let original_alpha = alpha;
+        let mut best_move = None;
+        for (idx, mv) in moves.into_iter().enumerate() {
+            let mut child = board.clone();
+            let meta = child.make_move(mv.from, mv.to, mv.promotion)?;
+            let quiet = meta.capture.is_none() && meta.promotion.is_none();
+
+            let score = match idx {
+                0 => -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, &mut child_pv)?,
+                _ => {
+                    let reduction = match depth >= LMR_MIN_DEPTH
+                        && idx >= LMR_MIN_MOVES
+                        && quiet
+                        && !in_check
+                    {
+                        true => 1 + (idx >= 2 * LMR_MIN_MOVES) as u32,
+                        false => 0,
+                    };
+                    let reduced = (depth - 1).saturating_sub(reduction);
+
+                    // late moves get a reduced null window first and only earn a
+                    // full search by beating alpha
+                    let mut score = -self.negamax(
+                        &child,
+                        reduced,
+                        ply + 1,
+                        -alpha - 1,
+                        -alpha,
+                        &mut child_pv,
+                    )?;
+                    if score > alpha && reduction > 0 && !self.aborted {
+                        score = -self.negamax(
+                            &child,
+                            depth - 1,
+                            ply + 1,
+                            -alpha - 1,
+                            -alpha,
+                            &mut child_pv,
+                        )?;
+                    }
+                    if score > alpha && score < beta && !self.aborted {
+                        score = -self.negamax(
+                            &child,
+                            depth - 1,
+                            ply + 1,
+                            -beta,
+                            -alpha,
+                            &mut child_pv,
+                        )?;
+                    }
+                    score
+                }
+            };
+            if self.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+                pv.clear();
+                pv.push(mv);
+                pv.extend_from_slice(&child_pv);
+                if alpha >= beta {
+                    if quiet {
+                        self.orderer.record_cutoff(board, mv, depth, ply as usize);
+                    }
+                    break;
+                }
+            }
+        }
+
+        if !self.aborted {
+            let bound = match alpha {
+                a if a >= beta => Bound::Lower,
+                a if a > original_alpha => Bound::Exact,
+                _ => Bound::Upper,
+            };
+            let entry = TtEntry {
+                depth,
+                bound,
+                score: score_to_tt(alpha, ply),
+                best_move,
+                age: 0,
+            };
+            self.tt.store(hash, entry, geometry);
+        }
+
+        Ok(alpha)
+    }
+
+    fn quiescence(
+        &mut self,
+        board: &Board,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> Result<i32, ChessError> {
+        self.nodes += 1;
+        pv.clear();
+        if self.should_stop() {
+            return Ok(0);
+        }
+
+        let color = board.get_turn();
+        let in_check = is_in_check(board, color)?;
+        let mut scratch = board.clone();
+        let mut moves = legal_moves(&mut scratch, color)?;
+        if moves.is_empty() {
+            return Ok(match in_check {
+                true => -MATE + ply,
+                false => 0,
+            });
+        }
+        if ply >= MAX_PLY {
+            return evaluate_with(board, self.params);
+        }
+
+        // in check every evasion is searched, otherwise only captures can improve on standing pat
+        if !in_check {
+            let stand_pat = evaluate_with(board, self.params)?;
+            if stand_pat >= beta {
+                return Ok(stand_pat);
+            }
+            alpha = alpha.max(stand_pat);
+
+            let mut noisy = Vec::with_capacity(moves.len());
+            for mv in moves {
+                if mv.promotion.is_some() || !board.is_empty(mv.to.rank, mv.to.file)? {
+                    noisy.push(mv);
+                }
+            }
+            moves = noisy;
+        }
+
+        self.orderer.order(board, &mut moves, None, ply as usize)?;
+        let mut child_pv = Vec::new();
+        for mv in moves {
+            let mut child = board.clone();
+            child.make_move(mv.from, mv.to, mv.promotion)?;
+
+            let score = -self.quiescence(&child, ply + 1, -beta, -alpha, &mut child_pv)?;
+            if self.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(mv);
+                pv.extend_from_slice(&child_pv);
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        Ok(alpha)
+    }
+}
+
+/// Null-move pruning is unsound in pawn endings, where zugzwang is common.
+fn has_pieces(board: &Board, color: Color) -> bool {
+    [
+        Piece::Knight(color),
+        Piece::Bishop(color),
+        Piece::Rook(color),
+        Piece::Queen(color),
+    ]
+    .into_iter()
+    .any(|piece| board.bitboard(piece) != 0)
+}
+
+pub fn search(
+    board: &Board,
+    limits: &SearchLimits,
+    stop: &AtomicBool,
+    on_info: impl FnMut(&SearchInfo),
+) -> Result<SearchResult, ChessError> {
+    let tt = TranspositionTable::default();
+    search_with(board, limits, default_params(), &tt, stop, on_info)
+}
+
+pub fn search_with(
+    board: &Board,
+    limits: &SearchLimits,
+    params: &EvalParams,
+    tt: &TranspositionTable,
+    stop: &AtomicBool,
+    on_info: impl FnMut(&SearchInfo),
+) -> Result<SearchResult, ChessError> {
+    let mut root = board.clone();
+    root.set_clock(None);
+
+    let color: Color = root.get_turn();
+    let mut scratch = root.clone();
+    let root_moves = legal_moves(&mut scratch, color)?;
+    if root_moves.is_empty() {
+        return Ok(SearchResult {
+            best_move: None,
+            score: Score::Centipawns(0),
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+        });
+    }
+
+    tt.new_search();
+    let threads = limits.threads.clamp(1, MAX_THREADS);
+    if threads == 1 {
+        return Searcher::new(limits, params, tt, stop, &root_moves).iterate(&root, 1, on_info);
+    }
+
+    // Lazy SMP: helpers search the same root and only share the table. They
+    // run without limits until the main thread is done.
+    let halt = AtomicBool::new(false);
+    let helper_limits = SearchLimits {
+        depth: limits.depth,
+        ..SearchLimits::default()
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads - 1)
+        .thread_name(|id| format!("search-helper-{}", id + 1))
+        .build()
+        .map_err(|err| {
+            ChessError::InternalError(format!("fail starting search threads: {}", err))
+        })?;
+    let helpers = Mutex::new(Vec::with_capacity(threads - 1));
+    let result = pool.in_place_scope(|scope| {
+        for id in 1..threads {
+            let (root, root_moves) = (root.clone(), &root_moves);
+            let (limits, halt, helpers) = (&helper_limits, &halt, &helpers);
+            scope.spawn(move |_| {
+                // odd helpers start a ply deeper so the threads spread over depths
+                let first_depth = 1 + (id % 2) as u32;
+                let mut searcher = Searcher::new(limits, params, tt, halt, root_moves);
+                let result = searcher.iterate(&root, first_depth, |_| {});
+                if let Ok(mut helpers) = helpers.lock() {
+                    helpers.push(result);
+                }
+            });
+        }
+
+        let result =
+            Searcher::new(limits, params, tt, stop, &root_moves).iterate(&root, 1, on_info);
+        halt.store(true, Ordering::Relaxed);
+        result
+    });
+
+    let mut result = result?;
+    let helpers = helpers
+        .into_inner()
+        .map_err(|_| ChessError::InternalError("search helper lock poisoned".into()))?;
+    for helper in helpers {
+        result.nodes += helper?.nodes;
+    }
+    Ok(result)
+}
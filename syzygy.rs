@@ -0,0 +1,1205 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::board::Board;
+use super::geometry::Geometry;
+use super::ordering::capture_square;
+use super::piece::{Color, Move, Piece};
+use super::rules::{is_checkmate, legal_moves};
+use super::utils::ChessError;
+
+/// First bytes of every WDL and DTZ table file.
+pub const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+pub const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+const MAX_DTZ: i32 = 1 << 18;
+
+/// Win/draw/loss for the side to move, cursed results being fifty-move draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    pub fn negate(self) -> Self {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::BlessedLoss => Wdl::CursedWin,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::CursedWin => Wdl::BlessedLoss,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+
+    /// Distance to zeroing of a position right after a capture or pawn move.
+    pub fn zeroing_dtz(self) -> i32 {
+        match self {
+            Wdl::Loss => -1,
+            Wdl::BlessedLoss => -101,
+            Wdl::Draw => 0,
+            Wdl::CursedWin => 101,
+            Wdl::Win => 1,
+        }
+    }
+}
+
+/// Source of endgame results, `None` when the position is not covered.
+/// Distance to zeroing is in plies, positive when the side to move wins.
+pub trait Tablebase {
+    fn max_pieces(&self) -> usize;
+    fn probe_wdl(&self, board: &Board) -> Result<Option<Wdl>, ChessError>;
+    fn probe_dtz(&self, board: &Board) -> Result<Option<i32>, ChessError>;
+}
+
+fn piece_letters(board: &Board, color: Color) -> String {
+    [
+        (Piece::King(color), 'K'),
+        (Piece::Queen(color), 'Q'),
+        (Piece::Rook(color), 'R'),
+        (Piece::Bishop(color), 'B'),
+        (Piece::Knight(color), 'N'),
+        (Piece::Pawn(color), 'P'),
+    ]
+    .into_iter()
+    .flat_map(|(piece, letter)| {
+        std::iter::repeat_n(letter, board.bitboard(piece).count_ones() as usize)
+    })
+    .collect()
+}
+
+/// Material of `board`, e.g. `KRPvKR`, white's pieces first.
+pub fn material_key(board: &Board) -> String {
+    format!(
+        "{}v{}",
+        piece_letters(board, Color::White),
+        piece_letters(board, Color::Black)
+    )
+}
+
+pub fn piece_count(board: &Board) -> usize {
+    material_key(board).len() - 1
+}
+
+const SPLIT: u8 = 1;
+const HAS_PAWNS: u8 = 2;
+
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE_DTZ: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+
+const fn file_of(square: usize) -> usize {
+    square % 8
+}
+
+const fn rank_of(square: usize) -> usize {
+    square / 8
+}
+
+// positive above the a1-h8 diagonal
+const fn diagonal(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+const fn flip_diagonal(square: usize) -> usize {
+    file_of(square) * 8 + rank_of(square)
+}
+
+struct Indexes {
+    binomial: [[u64; 64]; 7],
+    triangle: [u64; 64],
+    below_diagonal: [u64; 64],
+    kings: [[u64; 64]; 10],
+    pawns: [u64; 64],
+    lead_pawns: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+static INDEXES: Indexes = Indexes::new();
+
+impl Indexes {
+    const fn new() -> Self {
+        let mut binomial = [[0; 64]; 7];
+        let mut n = 0;
+        while n < 64 {
+            binomial[0][n] = 1;
+            let mut k = 1;
+            while k < 7 && n > 0 {
+                binomial[k][n] = binomial[k - 1][n - 1] + binomial[k][n - 1];
+                k += 1;
+            }
+            n += 1;
+        }
+
+        // the a1-d1-d4 triangle, diagonal squares last
+        let mut triangle = [0; 64];
+        let mut code = 0;
+        let mut square = 0;
+        while square < 64 {
+            if file_of(square) < 4 && rank_of(square) < 4 && diagonal(square) < 0 {
+                triangle[square] = code;
+                code += 1;
+            }
+            square += 1;
+        }
+        square = 0;
+        while square < 64 {
+            if file_of(square) < 4 && diagonal(square) == 0 {
+                triangle[square] = code;
+                code += 1;
+            }
+            square += 1;
+        }
+
+        let mut below_diagonal = [0; 64];
+        code = 0;
+        square = 0;
+        while square < 64 {
+            if diagonal(square) < 0 {
+                below_diagonal[square] = code;
+                code += 1;
+            }
+            square += 1;
+        }
+
+        // both kings on the diagonal come last
+        let mut kings = [[0; 64]; 10];
+        let mut both_on_diagonal = [(0, 0); 64];
+        let mut both = 0;
+        code = 0;
+        let mut first = 0;
+        while first < 10 {
+            let mut king = 0;
+            while king < 64 {
+                let in_triangle = file_of(king) < 4 && rank_of(king) < 4 && diagonal(king) <= 0;
+                if in_triangle && triangle[king] == first {
+                    let mut other = 0;
+                    while other < 64 {
+                        let apart = file_of(king).abs_diff(file_of(other)) > 1
+                            || rank_of(king).abs_diff(rank_of(other)) > 1;
+                        if !apart || (diagonal(king) == 0 && diagonal(other) > 0) {
+                            other += 1;
+                            continue;
+                        }
+                        if diagonal(king) == 0 && diagonal(other) == 0 {
+                            both_on_diagonal[both] = (first as usize, other);
+                            both += 1;
+                        } else {
+                            kings[first as usize][other] = code;
+                            code += 1;
+                        }
+                        other += 1;
+                    }
+                }
+                king += 1;
+            }
+            first += 1;
+        }
+        let mut i = 0;
+        while i < both {
+            let (first, other) = both_on_diagonal[i];
+            kings[first][other] = code;
+            code += 1;
+            i += 1;
+        }
+
+        // pawns nearer the edge and lower on the board lead
+        let mut pawns = [0; 64];
+        let mut lead_pawns = [[0; 64]; 6];
+        let mut lead_pawns_size = [[0; 4]; 6];
+        let mut available: u64 = 47;
+        let mut count = 1;
+        while count < 6 {
+            let mut file = 0;
+            while file < 4 {
+                let mut index = 0;
+                let mut rank = 1;
+                while rank < 7 {
+                    let square = rank * 8 + file;
+                    if count == 1 {
+                        pawns[square] = available;
+                        pawns[square ^ 7] = available.saturating_sub(1);
+                        available = available.saturating_sub(2);
+                    }
+                    lead_pawns[count][square] = index;
+                    index += binomial[count - 1][pawns[square] as usize];
+                    rank += 1;
+                }
+                lead_pawns_size[count][file] = index;
+                file += 1;
+            }
+            count += 1;
+        }
+
+        Self {
+            binomial,
+            triangle,
+            below_diagonal,
+            kings,
+            pawns,
+            lead_pawns,
+            lead_pawns_size,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TableFile {
+    path: PathBuf,
+    file: Mutex<File>,
+    len: u64,
+}
+
+impl TableFile {
+    fn open(path: &Path) -> Result<Self, ChessError> {
+        let file = File::open(path)
+            .map_err(|e| ChessError::ParseError(format!("{}: {}", path.display(), e)))?;
+        let len = file
+            .metadata()
+            .map_err(|e| ChessError::ParseError(format!("{}: {}", path.display(), e)))?
+            .len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+            len,
+        })
+    }
+
+    fn corrupt(&self) -> ChessError {
+        ChessError::ParseError(format!("{} is corrupt", self.path.display()))
+    }
+
+    // blocks may be read past the end of the file, those bytes are zero
+    fn bytes(&self, offset: u64, len: usize) -> Result<Vec<u8>, ChessError> {
+        if offset > self.len {
+            return Err(self.corrupt());
+        }
+        let mut buf = vec![0; len];
+        let available = len.min((self.len - offset) as usize);
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut buf[..available]))
+            .map_err(|e| ChessError::ParseError(format!("{}: {}", self.path.display(), e)))?;
+        Ok(buf)
+    }
+
+    fn u8(&self, offset: u64) -> Result<u8, ChessError> {
+        Ok(self.bytes(offset, 1)?[0])
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16, ChessError> {
+        let bytes = self.bytes(offset, 2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Pairs {
+    flags: u8,
+    pieces: Vec<u8>,
+    group_len: Vec<usize>,
+    group_idx: Vec<u64>,
+    single_value: u16,
+    block_size: u64,
+    span: u64,
+    blocks: u64,
+    min_symlen: u32,
+    lowest_sym: Vec<u16>,
+    base: Vec<u64>,
+    symlen: Vec<u32>,
+    tree: Vec<[u8; 3]>,
+    sparse_index: u64,
+    sparse_index_size: u64,
+    block_lengths: u64,
+    block_lengths_size: u64,
+    data: u64,
+    map_idx: [u64; 4],
+}
+
+fn left(symbol: [u8; 3]) -> usize {
+    symbol[0] as usize | (symbol[1] as usize & 0xf) << 8
+}
+
+fn right(symbol: [u8; 3]) -> usize {
+    (symbol[2] as usize) << 4 | (symbol[1] as usize) >> 4
+}
+
+impl Pairs {
+    fn new(pieces: Vec<u8>, order: [u8; 2], file: usize, table: &Table) -> Self {
+        let mut first_len: i32 = match (table.pawns, table.unique) {
+            (true, _) => 0,
+            (false, true) => 3,
+            (false, false) => 2,
+        };
+        let mut group_len = vec![1];
+        for i in 1..pieces.len() {
+            first_len -= 1;
+            match first_len > 0 || pieces[i] == pieces[i - 1] {
+                true => *group_len.last_mut().unwrap() += 1,
+                false => group_len.push(1),
+            }
+        }
+
+        let groups = group_len.len();
+        let mut group_idx = vec![0; groups + 1];
+        let mut next = 1 + table.both_pawns as usize;
+        let mut free = 64 - group_len[0] - if table.both_pawns { group_len[1] } else { 0 };
+        let mut size = 1;
+        let mut k = 0;
+        while next < groups || k == order[0] || k == order[1] {
+            if k == order[0] {
+                group_idx[0] = size;
+                size *= match (table.pawns, table.unique) {
+                    (true, _) => INDEXES.lead_pawns_size[group_len[0]][file],
+                    (false, true) => 31332,
+                    (false, false) => 462,
+                };
+            } else if k == order[1] {
+                group_idx[1] = size;
+                size *= INDEXES.binomial[group_len[1]][48 - group_len[0]];
+            } else {
+                group_idx[next] = size;
+                size *= INDEXES.binomial[group_len[next]][free];
+                free -= group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        group_idx[groups] = size;
+
+        Self {
+            pieces,
+            group_len,
+            group_idx,
+            ..Self::default()
+        }
+    }
+
+    fn read_sizes(&mut self, file: &TableFile, offset: u64, dtz: bool) -> Result<u64, ChessError> {
+        self.flags = file.u8(offset)?;
+        if self.flags & SINGLE_VALUE != 0 {
+            // DTZ tables leave the byte unused, their single value is zero
+            self.single_value = match dtz {
+                true => 0,
+                false => file.u8(offset + 1)? as u16,
+            };
+            return Ok(offset + 2);
+        }
+
+        let header = file.bytes(offset + 1, 9)?;
+        self.block_size = 1 << header[0];
+        self.span = 1 << header[1];
+        self.sparse_index_size = self.group_idx.last().unwrap().div_ceil(self.span);
+        self.blocks = u32::from_le_bytes([header[3], header[4], header[5], header[6]]) as u64;
+        self.block_lengths_size = self.blocks + header[2] as u64;
+        let (max_symlen, min_symlen) = (header[7] as usize, header[8] as usize);
+        if min_symlen == 0 || max_symlen < min_symlen || max_symlen > 64 {
+            return Err(file.corrupt());
+        }
+        self.min_symlen = min_symlen as u32;
+
+        let lengths = max_symlen - min_symlen + 1;
+        let mut offset = offset + 10;
+        self.lowest_sym = file
+            .bytes(offset, lengths * 2)?
+            .chunks(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        offset += lengths as u64 * 2;
+
+        // canonical code: longer symbols have lower values
+        self.base = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            self.base[i] = self.base[i + 1]
+                .wrapping_add(self.lowest_sym[i] as u64)
+                .wrapping_sub(self.lowest_sym[i + 1] as u64)
+                / 2;
+        }
+        for (i, base) in self.base.iter_mut().enumerate() {
+            *base = base.checked_shl(64 - (i + min_symlen) as u32).unwrap_or(0);
+        }
+
+        let symbols = file.u16(offset)? as usize;
+        offset += 2;
+        self.tree = file
+            .bytes(offset, symbols * 3)?
+            .chunks(3)
+            .map(|symbol| [symbol[0], symbol[1], symbol[2]])
+            .collect();
+        self.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            self.set_symlen(symbol, &mut visited, file)?;
+        }
+        Ok(offset + symbols as u64 * 3 + (symbols as u64 & 1))
+    }
+
+    fn set_symlen(
+        &mut self,
+        symbol: usize,
+        visited: &mut [bool],
+        file: &TableFile,
+    ) -> Result<u32, ChessError> {
+        if visited[symbol] {
+            return Ok(self.symlen[symbol]);
+        }
+        visited[symbol] = true;
+        let pair = self.tree[symbol];
+        if right(pair) == 0xfff {
+            return Ok(0);
+        }
+        if left(pair) >= self.tree.len() || right(pair) >= self.tree.len() {
+            return Err(file.corrupt());
+        }
+        let len = self.set_symlen(left(pair), visited, file)?
+            + self.set_symlen(right(pair), visited, file)?
+            + 1;
+        self.symlen[symbol] = len;
+        Ok(len)
+    }
+
+    fn decompress(&self, file: &TableFile, index: u64) -> Result<u16, ChessError> {
+        if self.flags & SINGLE_VALUE != 0 {
+            return Ok(self.single_value);
+        }
+
+        let entry = file.bytes(self.sparse_index + index / self.span * 6, 6)?;
+        let mut block = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) as u64;
+        let mut offset = u16::from_le_bytes([entry[4], entry[5]]) as i64
+            + (index % self.span) as i64
+            - (self.span / 2) as i64;
+        let block_length = |block: u64| -> Result<i64, ChessError> {
+            match block < self.block_lengths_size {
+                true => Ok(file.u16(self.block_lengths + block * 2)? as i64),
+                false => Err(file.corrupt()),
+            }
+        };
+        while offset < 0 {
+            block = block.checked_sub(1).ok_or_else(|| file.corrupt())?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+        if block >= self.blocks {
+            return Err(file.corrupt());
+        }
+
+        let bytes = file.bytes(
+            self.data + block * self.block_size,
+            self.block_size as usize + 8,
+        )?;
+        let word = |at: usize| {
+            let word: Option<[u8; 4]> = bytes.get(at..at + 4).and_then(|w| w.try_into().ok());
+            word.map(u32::from_be_bytes).ok_or_else(|| file.corrupt())
+        };
+        let mut buf = (word(0)? as u64) << 32 | word(4)? as u64;
+        let mut read = 8;
+        let mut bits = 64;
+        let mut symbol = loop {
+            let mut len = 0;
+            while buf < self.base[len] {
+                len += 1;
+                if len == self.base.len() {
+                    return Err(file.corrupt());
+                }
+            }
+            let shift = 64 - (len as u32 + self.min_symlen);
+            let symbol = ((buf - self.base[len]) >> shift) as usize + self.lowest_sym[len] as usize;
+            let Some(&symlen) = self.symlen.get(symbol) else {
+                return Err(file.corrupt());
+            };
+            if offset <= symlen as i64 {
+                break symbol;
+            }
+            offset -= symlen as i64 + 1;
+
+            let len = len as u32 + self.min_symlen;
+            buf <<= len;
+            bits -= len;
+            if bits <= 32 {
+                bits += 32;
+                buf |= (word(read)? as u64) << (64 - bits);
+                read += 4;
+            }
+        };
+
+        while self.symlen[symbol] != 0 {
+            let left = left(self.tree[symbol]);
+            if offset <= self.symlen[left] as i64 {
+                symbol = left;
+            } else {
+                offset -= self.symlen[left] as i64 + 1;
+                symbol = right(self.tree[symbol]);
+            }
+        }
+        Ok(left(self.tree[symbol]) as u16)
+    }
+}
+
+#[derive(Debug)]
+struct Table {
+    file: TableFile,
+    dtz: bool,
+    pawns: bool,
+    both_pawns: bool,
+    symmetric: bool,
+    unique: bool,
+    // by file of the leading pawn, then by side to move
+    pairs: Vec<Vec<Pairs>>,
+    map: u64,
+}
+
+fn piece_code(piece: Piece, flip: bool) -> u8 {
+    let kind = match piece {
+        Piece::Pawn(_) => 1,
+        Piece::Knight(_) => 2,
+        Piece::Bishop(_) => 3,
+        Piece::Rook(_) => 4,
+        Piece::Queen(_) => 5,
+        Piece::King(_) => 6,
+    };
+    match (piece.color() == Color::Black) != flip {
+        true => kind + 8,
+        false => kind,
+    }
+}
+
+impl Table {
+    fn open(path: &Path, name: &str, dtz: bool) -> Result<Self, ChessError> {
+        let (white, black) = name.split_once('v').unwrap_or((name, ""));
+        let unique = "QRBNP"
+            .chars()
+            .any(|c| white.matches(c).count() == 1 || black.matches(c).count() == 1);
+        let mut table = Self {
+            file: TableFile::open(path)?,
+            dtz,
+            pawns: name.contains('P'),
+            both_pawns: white.contains('P') && black.contains('P'),
+            symmetric: white == black,
+            unique,
+            pairs: Vec::new(),
+            map: 0,
+        };
+        let file = &table.file;
+
+        let flags = file.u8(4)?;
+        if (flags & HAS_PAWNS != 0) != table.pawns {
+            return Err(file.corrupt());
+        }
+        let sides = match !dtz && flags & SPLIT != 0 {
+            true => 2,
+            false => 1,
+        };
+        let files = if table.pawns { 4 } else { 1 };
+        let count = name.len() - 1;
+
+        let mut offset = 5;
+        let mut pairs = Vec::new();
+        for tb_file in 0..files {
+            let order = file.bytes(offset, 2)?;
+            let order = match table.both_pawns {
+                true => [
+                    [order[0] & 0xf, order[1] & 0xf],
+                    [order[0] >> 4, order[1] >> 4],
+                ],
+                false => [[order[0] & 0xf, 0xf], [order[0] >> 4, 0xf]],
+            };
+            offset += 1 + table.both_pawns as u64;
+            let pieces = file.bytes(offset, count)?;
+            offset += count as u64;
+
+            let sides: Vec<Pairs> = (0..sides)
+                .map(|side| {
+                    let pieces = pieces
+                        .iter()
+                        .map(|p| if side == 0 { p & 0xf } else { p >> 4 })
+                        .collect();
+                    Pairs::new(pieces, order[side], tb_file, &table)
+                })
+                .collect();
+            pairs.push(sides);
+        }
+        offset += offset & 1;
+
+        for side in pairs.iter_mut().flatten() {
+            offset = side.read_sizes(file, offset, dtz)?;
+        }
+
+        let map = offset;
+        if dtz {
+            for side in pairs.iter_mut().flatten() {
+                if side.flags & MAPPED == 0 {
+                    continue;
+                }
+                for i in 0..4 {
+                    match side.flags & WIDE_DTZ != 0 {
+                        true => {
+                            offset += offset & 1;
+                            side.map_idx[i] = (offset - map) / 2 + 1;
+                            offset += 2 + 2 * file.u16(offset)? as u64;
+                        }
+                        false => {
+                            side.map_idx[i] = offset - map + 1;
+                            offset += 1 + file.u8(offset)? as u64;
+                        }
+                    }
+                }
+            }
+            offset += offset & 1;
+        }
+
+        for side in pairs.iter_mut().flatten() {
+            side.sparse_index = offset;
+            offset += side.sparse_index_size * 6;
+        }
+        for side in pairs.iter_mut().flatten() {
+            side.block_lengths = offset;
+            offset += side.block_lengths_size * 2;
+        }
+        for side in pairs.iter_mut().flatten() {
+            offset = offset.next_multiple_of(64);
+            side.data = offset;
+            offset += side.blocks * side.block_size;
+        }
+        if offset > file.len {
+            return Err(file.corrupt());
+        }
+
+        table.pairs = pairs;
+        table.map = map;
+        Ok(table)
+    }
+
+    // `None` when a DTZ table only stores the other side to move
+    fn probe(&self, board: &Board, flip: bool) -> Result<Option<(u16, usize)>, ChessError> {
+        let black_to_move = board.get_turn() == Color::Black;
+        let flip = flip || (self.symmetric && black_to_move);
+        let stm = (flip != black_to_move) as usize;
+        let mirror = if flip { 56 } else { 0 };
+
+        let mut pieces = Vec::new();
+        for color in [Color::White, Color::Black] {
+            for piece in [
+                Piece::Pawn(color),
+                Piece::Knight(color),
+                Piece::Bishop(color),
+                Piece::Rook(color),
+                Piece::Queen(color),
+                Piece::King(color),
+            ] {
+                let mut bits = board.bitboard(piece);
+                while bits != 0 {
+                    let square = bits.trailing_zeros() as usize;
+                    pieces.push((piece_code(piece, flip), square ^ mirror));
+                    bits &= bits - 1;
+                }
+            }
+        }
+
+        let mut lead = 0;
+        let mut tb_file = 0;
+        if self.pawns {
+            let lead_code = self.pairs[0][0].pieces[0];
+            pieces.sort_by_key(|&(code, _)| code != lead_code);
+            lead = pieces
+                .iter()
+                .filter(|&&(code, _)| code == lead_code)
+                .count();
+            let leader = (0..lead)
+                .max_by_key(|&i| INDEXES.pawns[pieces[i].1])
+                .ok_or_else(|| self.file.corrupt())?;
+            pieces.swap(0, leader);
+            let file = file_of(pieces[0].1);
+            tb_file = file.min(7 - file);
+        }
+
+        let side = match self.dtz {
+            true => {
+                let stored = (self.pairs[tb_file][0].flags & STM) as usize;
+                // symmetric pawnless tables serve both sides by flipping
+                if stored != stm && (self.pawns || !self.symmetric) {
+                    return Ok(None);
+                }
+                0
+            }
+            false => stm,
+        };
+        let pairs = self.pairs[tb_file]
+            .get(side)
+            .ok_or_else(|| self.file.corrupt())?;
+        if pairs.pieces.len() != pieces.len() {
+            return Err(self.file.corrupt());
+        }
+
+        // match the piece order of the table
+        for i in lead..pieces.len() {
+            let j = (i..pieces.len())
+                .find(|&j| pieces[j].0 == pairs.pieces[i])
+                .ok_or_else(|| self.file.corrupt())?;
+            pieces.swap(i, j);
+        }
+        let mut squares: Vec<usize> = pieces.into_iter().map(|(_, square)| square).collect();
+
+        if file_of(squares[0]) > 3 {
+            squares.iter_mut().for_each(|square| *square ^= 7);
+        }
+
+        let mut index = match self.pawns {
+            true => {
+                squares[1..lead].sort_by_key(|&square| INDEXES.pawns[square]);
+                (1..lead).fold(INDEXES.lead_pawns[lead][squares[0]], |index, i| {
+                    index + INDEXES.binomial[i][INDEXES.pawns[squares[i]] as usize]
+                })
+            }
+            false => {
+                if rank_of(squares[0]) > 3 {
+                    squares.iter_mut().for_each(|square| *square ^= 56);
+                }
+                // the first piece off the diagonal goes below it
+                for i in 0..pairs.group_len[0] {
+                    match diagonal(squares[i]) {
+                        0 => continue,
+                        d if d > 0 => squares[i..]
+                            .iter_mut()
+                            .for_each(|square| *square = flip_diagonal(*square)),
+                        _ => {}
+                    }
+                    break;
+                }
+                match self.unique {
+                    true => self.unique_index(&squares),
+                    false => INDEXES.kings[INDEXES.triangle[squares[0]] as usize][squares[1]],
+                }
+            }
+        };
+
+        index *= pairs.group_idx[0];
+        let mut start = pairs.group_len[0];
+        let mut remaining_pawns = self.both_pawns;
+        for group in 1..pairs.group_len.len() {
+            let end = start + pairs.group_len[group];
+            squares[start..end].sort();
+            let mut n = 0;
+            for i in start..end {
+                let below = squares[..start].iter().filter(|&&s| s < squares[i]).count();
+                let free = squares[i] - below - if remaining_pawns { 8 } else { 0 };
+                n += INDEXES.binomial[i - start + 1][free];
+            }
+            remaining_pawns = false;
+            index += n * pairs.group_idx[group];
+            start = end;
+        }
+
+        Ok(Some((pairs.decompress(&self.file, index)?, tb_file)))
+    }
+
+    fn unique_index(&self, squares: &[usize]) -> u64 {
+        let (first, second, third) = (squares[0], squares[1], squares[2]);
+        let rank = |square: usize| rank_of(square) as u64;
+        let adjust1 = (second > first) as u64;
+        let adjust2 = (third > first) as u64 + (third > second) as u64;
+        if diagonal(first) != 0 {
+            (INDEXES.triangle[first] * 63 + second as u64 - adjust1) * 62 + third as u64 - adjust2
+        } else if diagonal(second) != 0 {
+            (6 * 63 + rank(first) * 28 + INDEXES.below_diagonal[second]) * 62 + third as u64
+                - adjust2
+        } else if diagonal(third) != 0 {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + rank(first) * 7 * 28
+                + (rank(second) - adjust1) * 28
+                + INDEXES.below_diagonal[third]
+        } else {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + 4 * 7 * 28
+                + rank(first) * 7 * 6
+                + (rank(second) - adjust1) * 6
+                + rank(third)
+                - adjust2
+        }
+    }
+
+    fn dtz_plies(&self, value: u16, tb_file: usize, wdl: Wdl) -> Result<i32, ChessError> {
+        let pairs = &self.pairs[tb_file][0];
+        let mut value = value as u64;
+        if pairs.flags & MAPPED != 0 {
+            let map = match wdl {
+                Wdl::Loss => 1,
+                Wdl::CursedWin => 2,
+                Wdl::BlessedLoss => 3,
+                Wdl::Win | Wdl::Draw => 0,
+            };
+            value = match pairs.flags & WIDE_DTZ != 0 {
+                true => self.file.u16(self.map + 2 * (pairs.map_idx[map] + value))? as u64,
+                false => self.file.u8(self.map + pairs.map_idx[map] + value)? as u64,
+            };
+        }
+        let plies = match wdl {
+            Wdl::Win if pairs.flags & WIN_PLIES != 0 => value,
+            Wdl::Loss if pairs.flags & LOSS_PLIES != 0 => value,
+            _ => value * 2,
+        };
+        Ok(plies as i32 + 1)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TableSlot {
+    path: PathBuf,
+    table: OnceLock<Result<Arc<Table>, ChessError>>,
+}
+
+impl TableSlot {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            table: OnceLock::new(),
+        }
+    }
+
+    fn load(&self, name: &str, dtz: bool) -> Result<Arc<Table>, ChessError> {
+        self.table
+            .get_or_init(|| Table::open(&self.path, name, dtz).map(Arc::new))
+            .clone()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct TableFiles {
+    wdl: Option<TableSlot>,
+    dtz: Option<TableSlot>,
+}
+
+/// Syzygy tables found in a local directory, opened on first probe.
+#[derive(Debug, Clone, Default)]
+pub struct SyzygyTablebase {
+    tables: HashMap<String, TableFiles>,
+    max_pieces: usize,
+}
+
+fn check_magic(path: &Path, magic: [u8; 4]) -> Result<(), ChessError> {
+    let mut header = [0; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map_err(|e| ChessError::ParseError(format!("{}: {}", path.display(), e)))?;
+    match header == magic {
+        true => Ok(()),
+        false => Err(ChessError::ParseError(format!(
+            "{} is not a syzygy table",
+            path.display()
+        ))),
+    }
+}
+
+impl SyzygyTablebase {
+    pub fn open(dir: &str) -> Result<Self, ChessError> {
+        let mut tablebase = Self::default();
+        tablebase.add_directory(dir)?;
+        Ok(tablebase)
+    }
+
+    /// Adds every `.rtbw` and `.rtbz` file of `dir`. Returns how many were found.
+    pub fn add_directory(&mut self, dir: &str) -> Result<usize, ChessError> {
+        let entries = std::fs::read_dir(dir).map_err(|e| ChessError::ParseError(e.to_string()))?;
+
+        let mut found = 0;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| ChessError::ParseError(e.to_string()))?
+                .path();
+            let (Some(name), Some(extension)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|s| s.to_str()),
+            ) else {
+                continue;
+            };
+            let valid_name = name.split('v').count() == 2
+                && name.starts_with('K')
+                && name.chars().all(|c| "KQRBNPv".contains(c));
+            if !valid_name {
+                continue;
+            }
+
+            let name = name.to_string();
+            match extension {
+                "rtbw" => {
+                    check_magic(&path, WDL_MAGIC)?;
+                    self.tables.entry(name.clone()).or_default().wdl = Some(TableSlot::new(path));
+                }
+                "rtbz" => {
+                    check_magic(&path, DTZ_MAGIC)?;
+                    self.tables.entry(name.clone()).or_default().dtz = Some(TableSlot::new(path));
+                }
+                _ => continue,
+            }
+            self.max_pieces = self.max_pieces.max(name.len() - 1);
+            found += 1;
+        }
+        Ok(found)
+    }
+
+    /// Material keys with at least one table file, sorted.
+    pub fn tables(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.tables.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    fn covers(&self, board: &Board) -> Result<bool, ChessError> {
+        if board.geometry() != Geometry::STANDARD {
+            return Err(ChessError::InvalidPosition(
+                "syzygy tables need a standard 8x8 board".into(),
+            ));
+        }
+        let castling = board.get_castling();
+        let can_castle = castling.white_king
+            || castling.white_queen
+            || castling.black_king
+            || castling.black_queen;
+        Ok(!can_castle && piece_count(board) <= self.max_pieces.max(2))
+    }
+
+    fn table(&self, board: &Board, dtz: bool) -> Result<Option<(Arc<Table>, bool)>, ChessError> {
+        let key = material_key(board);
+        let (white, black) = key.split_once('v').unwrap_or((&key, ""));
+        let reversed = format!("{}v{}", black, white);
+        let Some((name, files)) = self
+            .tables
+            .get_key_value(&key)
+            .or_else(|| self.tables.get_key_value(&reversed))
+        else {
+            return Ok(None);
+        };
+        let slot = match dtz {
+            true => &files.dtz,
+            false => &files.wdl,
+        };
+        match slot {
+            Some(slot) => Ok(Some((slot.load(name, dtz)?, *name != key))),
+            None => Ok(None),
+        }
+    }
+
+    fn probe_wdl_table(&self, board: &Board) -> Result<Option<Wdl>, ChessError> {
+        if piece_count(board) == 2 {
+            return Ok(Some(Wdl::Draw));
+        }
+        let Some((table, flip)) = self.table(board, false)? else {
+            return Ok(None);
+        };
+        let (value, _) = table
+            .probe(board, flip)?
+            .ok_or_else(|| table.file.corrupt())?;
+        match value {
+            0 => Ok(Some(Wdl::Loss)),
+            1 => Ok(Some(Wdl::BlessedLoss)),
+            2 => Ok(Some(Wdl::Draw)),
+            3 => Ok(Some(Wdl::CursedWin)),
+            4 => Ok(Some(Wdl::Win)),
+            _ => Err(table.file.corrupt()),
+        }
+    }
+
+    // tables are wrong where a capture is best, so those are searched; the
+    // flag tells whether a zeroing move gives the result
+    fn search(&self, board: &Board, zeroing: bool) -> Result<Option<(Wdl, bool)>, ChessError> {
+        let mut scratch = board.clone();
+        let moves = legal_moves(&mut scratch, board.get_turn())?;
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for mv in &moves {
+            let pawn = matches!(board.get(mv.from.rank, mv.from.file)?, Some(Piece::Pawn(_)));
+            if capture_square(board, *mv)?.is_none() && !(zeroing && pawn) {
+                continue;
+            }
+            searched += 1;
+
+            let mut child = board.clone();
+            child.make_move(mv.from, mv.to, mv.promotion)?;
+            let Some((value, _)) = self.search(&child, false)? else {
+                return Ok(None);
+            };
+            best = best.max(value.negate());
+            if best == Wdl::Win {
+                return Ok(Some((best, true)));
+            }
+        }
+
+        let exhausted = searched > 0 && searched == moves.len();
+        let value = match exhausted {
+            true => best,
+            false => match self.probe_wdl_table(board)? {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+        };
+        match best >= value {
+            true => Ok(Some((best, best > Wdl::Draw || exhausted))),
+            false => Ok(Some((value, false))),
+        }
+    }
+}
+
+impl Tablebase for SyzygyTablebase {
+    fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Result<Option<Wdl>, ChessError> {
+        if !self.covers(board)? {
+            return Ok(None);
+        }
+        Ok(self.search(board, false)?.map(|(wdl, _)| wdl))
+    }
+
+    fn probe_dtz(&self, board: &Board) -> Result<Option<i32>, ChessError> {
+        if !self.covers(board)? {
+            return Ok(None);
+        }
+        let Some((wdl, zeroing)) = self.search(board, true)? else {
+            return Ok(None);
+        };
+        if wdl == Wdl::Draw {
+            return Ok(Some(0));
+        }
+        if zeroing {
+            return Ok(Some(wdl.zeroing_dtz()));
+        }
+
+        let Some((table, flip)) = self.table(board, true)? else {
+            return Ok(None);
+        };
+        if let Some((value, tb_file)) = table.probe(board, flip)? {
+            let plies = table.dtz_plies(value, tb_file, wdl)?;
+            let plies = match wdl {
+                Wdl::CursedWin | Wdl::BlessedLoss => plies + 100,
+                _ => plies,
+            };
+            return Ok(Some(plies * wdl.zeroing_dtz().signum()));
+        }
+
+        // the table stores the other side to move: take the best move
+        let sign = wdl.zeroing_dtz().signum();
+        let mut scratch = board.clone();
+        let mut best: Option<i32> = None;
+        for mv in legal_moves(&mut scratch, board.get_turn())? {
+            let pawn = matches!(board.get(mv.from.rank, mv.from.file)?, Some(Piece::Pawn(_)));
+            let zeroing = pawn || capture_square(board, mv)?.is_some();
+
+            let mut child = board.clone();
+            child.make_move(mv.from, mv.to, mv.promotion)?;
+            let dtz = match zeroing {
+                true => match self.search(&child, false)? {
+                    Some((wdl, _)) => wdl.negate().zeroing_dtz(),
+                    None => return Ok(None),
+                },
+                false => match self.probe_dtz(&child)? {
+                    Some(dtz) => -dtz + -dtz.signum(),
+                    None => return Ok(None),
+                },
+            };
+            let child_color = child.get_turn();
+            let dtz = match dtz == 2 && !zeroing && is_checkmate(&mut child, child_color)? {
+                true => 1,
+                false => dtz,
+            };
+            if dtz.signum() == sign && best.is_none_or(|best| dtz < best) {
+                best = Some(dtz);
+            }
+        }
+        Ok(Some(best.unwrap_or(-1)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootMove {
+    pub mv: Move,
+    /// Distance to zeroing from the root, in plies, for the side to move.
+    pub dtz: i32,
+    /// Larger is better; moves of equal rank are equally good.
+    pub rank: i32,
+}
+
+/// Ranks every legal move of `board` by distance to zeroing under the
+/// fifty-move rule. `None` when some move leads out of the tablebase.
+pub fn rank_root_moves(
+    tablebase: &impl Tablebase,
+    board: &Board,
+) -> Result<Option<Vec<RootMove>>, ChessError> {
+    if piece_count(board) > tablebase.max_pieces() {
+        return Ok(None);
+    }
+
+    let halfmove = board.get_halfmove() as i32;
+    let mut scratch = board.clone();
+    let mut ranked = Vec::new();
+    for mv in legal_moves(&mut scratch, board.get_turn())? {
+        let mut child = board.clone();
+        let meta = child.make_move(mv.from, mv.to, mv.promotion)?;
+        let zeroing = meta.capture.is_some() || matches!(meta.piece, Piece::Pawn(_));
+
+        let dtz = match zeroing {
+            true => match tablebase.probe_wdl(&child)? {
+                Some(wdl) => wdl.negate().zeroing_dtz(),
+                None => return Ok(None),
+            },
+            false => match tablebase.probe_dtz(&child)? {
+                Some(dtz) => match -dtz {
+                    dtz if dtz > 0 => dtz + 1,
+                    dtz if dtz < 0 => dtz - 1,
+                    _ => 0,
+                },
+                None => return Ok(None),
+            },
+        };
+        // a mating move is as good as zeroing
+        let child_color = child.get_turn();
+        let dtz = match dtz == 2 && is_checkmate(&mut child, child_color)? {
+            true => 1,
+            false => dtz,
+        };
+
+        let rank = match dtz {
+            d if d > 0 && d + halfmove <= 99 => MAX_DTZ,
+            d if d > 0 => MAX_DTZ - (d + halfmove),
+            d if d < 0 && -d * 2 + halfmove < 100 => -MAX_DTZ,
+            d if d < 0 => -MAX_DTZ + (-d + halfmove),
+            _ => 0,
+        };
+        ranked.push(RootMove { mv, dtz, rank });
+    }
+
+    // quickest wins first and slowest losses first
+    ranked.sort_by_key(|root| (std::cmp::Reverse(root.rank), root.dtz));
+    Ok(Some(ranked))
+}
+
+/// The legal moves that keep the best result the tablebase promises.
+pub fn filter_root_moves(
+    tablebase: &impl Tablebase,
+    board: &Board,
+) -> Result<Option<Vec<Move>>, ChessError> {
+    let Some(ranked) = rank_root_moves(tablebase, board)? else {
+        return Ok(None);
+    };
+    let best = ranked.first().map_or(0, |root| root.rank);
+    Ok(Some(
+        ranked
+            .into_iter()
+            .filter(|root| root.rank == best)
+            .map(|root| root.mv)
+            .collect(),
+    ))
+}
@@ -0,0 +1,447 @@
+use super::board::Board;
+use super::ordering::{capture_square, hanging_pieces};
+use super::piece::{Color, Move, Piece, PieceEntity, Position};
+use super::rules::is_checkmate;
+use super::search::piece_value;
+use super::utils::ChessError;
+
+const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const QUEEN_DIRS: [(i8, i8); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// A tactical pattern a move creates, on the squares after the move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Motif {
+    /// The moved piece attacks several valuable or undefended pieces.
+    Fork {
+        attacker: Position,
+        targets: Vec<Position>,
+    },
+    /// `pinned` cannot move without exposing the more valuable `behind`.
+    Pin {
+        attacker: Position,
+        pinned: Position,
+        behind: Position,
+        absolute: bool,
+    },
+    /// The more valuable `front` has to step aside from `behind`.
+    Skewer {
+        attacker: Position,
+        front: Position,
+        behind: Position,
+    },
+    /// Moving the piece now on `moved` opened a line to `target`.
+    DiscoveredAttack {
+        attacker: Position,
+        target: Position,
+        moved: Position,
+    },
+    DoubleCheck {
+        checkers: Vec<Position>,
+    },
+    /// Mate along the back rank.
+    BackRank {
+        king: Position,
+        attacker: Position,
+    },
+    /// A piece that can be taken for a gain.
+    Hanging {
+        square: Position,
+        piece: Piece,
+    },
+    /// `defender` alone guards every attacked piece in `duties`.
+    Overloaded {
+        defender: Position,
+        duties: Vec<Position>,
+    },
+}
+
+impl Motif {
+    /// Theme name as used by the open puzzle database.
+    pub fn theme(&self) -> &'static str {
+        match self {
+            Motif::Fork { .. } => "fork",
+            Motif::Pin { .. } => "pin",
+            Motif::Skewer { .. } => "skewer",
+            Motif::DiscoveredAttack { .. } => "discoveredAttack",
+            Motif::DoubleCheck { .. } => "doubleCheck",
+            Motif::BackRank { .. } => "backRankMate",
+            Motif::Hanging { .. } => "hangingPiece",
+            Motif::Overloaded { .. } => "overloading",
+        }
+    }
+}
+
+fn worth(piece: Piece) -> i32 {
+    match piece {
+        Piece::King(_) => i32::MAX,
+        piece => piece_value(piece),
+    }
+}
+
+struct Context<'a> {
+    before: &'a Board,
+    after: Board,
+    mv: Move,
+    color: Color,
+}
+
+impl<'a> Context<'a> {
+    fn new(board: &'a Board, mv: Move) -> Result<Self, ChessError> {
+        let color = board
+            .get(mv.from.rank, mv.from.file)?
+            .ok_or_else(|| {
+                let square = board.geometry().square_name(mv.from);
+                ChessError::IllegalMove(format!("no piece on {}", square))
+            })?
+            .color();
+        let mut after = board.clone();
+        after.make_move(mv.from, mv.to, mv.promotion)?;
+        Ok(Self {
+            before: board,
+            after,
+            mv,
+            color,
+        })
+    }
+
+    fn enemy(&self) -> Color {
+        self.color.opposite()
+    }
+
+    fn pieces(board: &Board, color: Color) -> Vec<(Position, Piece)> {
+        board
+            .geometry()
+            .positions()
+            .filter_map(|pos| board.get_entity(pos.rank, pos.file))
+            .filter(|entity| entity.piece.color() == color)
+            .map(|entity| (entity.pos, entity.piece))
+            .collect()
+    }
+
+    fn enemy_king(&self) -> Result<Option<Position>, ChessError> {
+        self.after.get_king_pos(self.enemy())
+    }
+}
+
+fn attackers(board: &Board, target: Position, color: Color) -> Result<Vec<Position>, ChessError> {
+    let mut scratch = board.clone();
+    let stand_in = Piece::Pawn(color.opposite());
+    scratch.set(target.rank, target.file, Some(stand_in))?;
+    scratch.set_entity(
+        target.rank,
+        target.file,
+        Some(PieceEntity::new(stand_in, target)),
+    );
+
+    let mut found = Vec::new();
+    for (pos, _) in Context::pieces(&scratch, color) {
+        let Some(entity) = scratch.get_entity(pos.rank, pos.file) else {
+            continue;
+        };
+        if entity.attacks(&scratch)?.contains(&target) {
+            found.push(pos);
+        }
+    }
+    Ok(found)
+}
+
+fn slider_dirs(piece: Piece) -> &'static [(i8, i8)] {
+    match piece {
+        Piece::Rook(_) => &ROOK_DIRS,
+        Piece::Bishop(_) => &BISHOP_DIRS,
+        Piece::Queen(_) => &QUEEN_DIRS,
+        _ => &[],
+    }
+}
+
+fn first_two(
+    board: &Board,
+    from: Position,
+    (dx, dy): (i8, i8),
+) -> Result<(Option<Position>, Option<Position>), ChessError> {
+    let mut hits = Vec::with_capacity(2);
+    let mut current = from;
+    while let Some(next) = board.geometry().shift(current, dx, dy) {
+        if board.get(next.rank, next.file)?.is_some() {
+            hits.push(next);
+            if hits.len() == 2 {
+                break;
+            }
+        }
+        current = next;
+    }
+    Ok((hits.first().copied(), hits.get(1).copied()))
+}
+
+fn find_fork(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    let Some(entity) = ctx.after.get_entity(ctx.mv.to.rank, ctx.mv.to.file) else {
+        return Ok(Vec::new());
+    };
+    let attacker_worth = worth(entity.piece);
+
+    let mut targets = Vec::new();
+    for target in entity.attacks(&ctx.after)? {
+        let Some(victim) = ctx.after.get(target.rank, target.file)? else {
+            continue;
+        };
+        if victim.color() != ctx.enemy() {
+            continue;
+        }
+        let exposed = matches!(victim, Piece::King(_))
+            || worth(victim) > attacker_worth
+            || attackers(&ctx.after, target, ctx.enemy())?.is_empty();
+        if exposed {
+            targets.push(target);
+        }
+    }
+    Ok(match targets.len() >= 2 {
+        true => vec![Motif::Fork {
+            attacker: ctx.mv.to,
+            targets,
+        }],
+        false => Vec::new(),
+    })
+}
+
+fn find_lines(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    let mut motifs = Vec::new();
+    let Some(piece) = ctx.after.get(ctx.mv.to.rank, ctx.mv.to.file)? else {
+        return Ok(motifs);
+    };
+    for &dir in slider_dirs(piece) {
+        let (Some(front), Some(behind)) = first_two(&ctx.after, ctx.mv.to, dir)? else {
+            continue;
+        };
+        let (Some(front_piece), Some(behind_piece)) = (
+            ctx.after.get(front.rank, front.file)?,
+            ctx.after.get(behind.rank, behind.file)?,
+        ) else {
+            continue;
+        };
+        if front_piece.color() != ctx.enemy() || behind_piece.color() != ctx.enemy() {
+            continue;
+        }
+
+        if worth(front_piece) < worth(behind_piece) {
+            motifs.push(Motif::Pin {
+                attacker: ctx.mv.to,
+                pinned: front,
+                behind,
+                absolute: matches!(behind_piece, Piece::King(_)),
+            });
+        } else if worth(front_piece) > worth(behind_piece) {
+            motifs.push(Motif::Skewer {
+                attacker: ctx.mv.to,
+                front,
+                behind,
+            });
+        }
+    }
+    Ok(motifs)
+}
+
+fn find_pins(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    let mut lines = find_lines(ctx)?;
+    lines.retain(|motif| matches!(motif, Motif::Pin { .. }));
+    Ok(lines)
+}
+
+fn find_skewers(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    let mut lines = find_lines(ctx)?;
+    lines.retain(|motif| matches!(motif, Motif::Skewer { .. }));
+    Ok(lines)
+}
+
+fn find_discovered(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    let mut motifs = Vec::new();
+    for (pos, piece) in Context::pieces(&ctx.after, ctx.color) {
+        if pos == ctx.mv.to || slider_dirs(piece).is_empty() {
+            continue;
+        }
+        let (Some(before), Some(after)) = (
+            ctx.before.get_entity(pos.rank, pos.file),
+            ctx.after.get_entity(pos.rank, pos.file),
+        ) else {
+            continue;
+        };
+        let seen = before.attacks(ctx.before)?;
+        for target in after.attacks(&ctx.after)? {
+            let enemy = ctx
+                .after
+                .get(target.rank, target.file)?
+                .is_some_and(|victim| victim.color() == ctx.enemy());
+            if enemy && !seen.contains(&target) {
+                motifs.push(Motif::DiscoveredAttack {
+                    attacker: pos,
+                    target,
+                    moved: ctx.mv.to,
+                });
+            }
+        }
+    }
+    Ok(motifs)
+}
+
+fn find_double_check(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    let Some(king) = ctx.enemy_king()? else {
+        return Ok(Vec::new());
+    };
+    let checkers = attackers(&ctx.after, king, ctx.color)?;
+    Ok(match checkers.len() >= 2 {
+        true => vec![Motif::DoubleCheck { checkers }],
+        false => Vec::new(),
+    })
+}
+
+fn find_back_rank(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    let Some(king) = ctx.enemy_king()? else {
+        return Ok(Vec::new());
+    };
+    let geometry = ctx.after.geometry();
+    let (back_rank, forward) = match ctx.enemy() {
+        Color::White => (0, 1),
+        Color::Black => (geometry.height - 1, -1),
+    };
+    let moved = ctx.after.get(ctx.mv.to.rank, ctx.mv.to.file)?;
+    let rank_attacker = matches!(moved, Some(Piece::Rook(_) | Piece::Queen(_)));
+    if king.rank != back_rank || ctx.mv.to.rank != back_rank || !rank_attacker {
+        return Ok(Vec::new());
+    }
+    if !attackers(&ctx.after, king, ctx.color)?.contains(&ctx.mv.to) {
+        return Ok(Vec::new());
+    }
+
+    for dx in [-1, 0, 1] {
+        let Some(flight) = geometry.shift(king, dx, forward) else {
+            continue;
+        };
+        let own = ctx
+            .after
+            .get(flight.rank, flight.file)?
+            .is_some_and(|piece| piece.color() == ctx.enemy());
+        if !own && attackers(&ctx.after, flight, ctx.color)?.is_empty() {
+            return Ok(Vec::new());
+        }
+    }
+    if !is_checkmate(&mut ctx.after.clone(), ctx.enemy())? {
+        return Ok(Vec::new());
+    }
+    Ok(vec![Motif::BackRank {
+        king,
+        attacker: ctx.mv.to,
+    }])
+}
+
+fn find_hanging(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    let mut motifs = Vec::new();
+    if let Some(square) = capture_square(ctx.before, ctx.mv)? {
+        if hanging_pieces(ctx.before, ctx.enemy())?.contains(&square) {
+            if let Some(piece) = ctx.before.get(square.rank, square.file)? {
+                motifs.push(Motif::Hanging { square, piece });
+            }
+        }
+    }
+
+    // the mover's pieces the move left en prise
+    let previously = hanging_pieces(ctx.before, ctx.color)?;
+    for square in hanging_pieces(&ctx.after, ctx.color)? {
+        let origin = match square == ctx.mv.to {
+            true => ctx.mv.from,
+            false => square,
+        };
+        if previously.contains(&origin) {
+            continue;
+        }
+        if let Some(piece) = ctx.after.get(square.rank, square.file)? {
+            motifs.push(Motif::Hanging { square, piece });
+        }
+    }
+    Ok(motifs)
+}
+
+fn find_overloaded(ctx: &Context) -> Result<Vec<Motif>, ChessError> {
+    // attacked enemy pieces with a single defender, by defender
+    let mut guarded: Vec<(Position, Vec<Position>)> = Vec::new();
+    let mut involved = Vec::new();
+    for (pos, piece) in Context::pieces(&ctx.after, ctx.enemy()) {
+        if matches!(piece, Piece::King(_)) {
+            continue;
+        }
+        let attacking = attackers(&ctx.after, pos, ctx.color)?;
+        if attacking.is_empty() {
+            continue;
+        }
+        let defenders = attackers(&ctx.after, pos, ctx.enemy())?;
+        let [defender] = defenders[..] else {
+            continue;
+        };
+        if attacking.contains(&ctx.mv.to) {
+            involved.push(pos);
+        }
+        match guarded.iter_mut().find(|(known, _)| *known == defender) {
+            Some((_, duties)) => duties.push(pos),
+            None => guarded.push((defender, vec![pos])),
+        }
+    }
+
+    Ok(guarded
+        .into_iter()
+        .filter(|(_, duties)| duties.len() >= 2 && duties.iter().any(|d| involved.contains(d)))
+        .map(|(defender, duties)| Motif::Overloaded { defender, duties })
+        .collect())
+}
+
+pub fn forks(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    find_fork(&Context::new(board, mv)?)
+}
+
+pub fn pins(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    find_pins(&Context::new(board, mv)?)
+}
+
+pub fn skewers(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    find_skewers(&Context::new(board, mv)?)
+}
+
+pub fn discovered_attacks(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    find_discovered(&Context::new(board, mv)?)
+}
+
+pub fn double_check(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    find_double_check(&Context::new(board, mv)?)
+}
+
+pub fn back_rank(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    find_back_rank(&Context::new(board, mv)?)
+}
+
+pub fn hanging(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    find_hanging(&Context::new(board, mv)?)
+}
+
+pub fn overloaded(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    find_overloaded(&Context::new(board, mv)?)
+}
+
+/// Every motif `mv` creates on `board`, judged from the position after it.
+pub fn motifs(board: &Board, mv: Move) -> Result<Vec<Motif>, ChessError> {
+    let ctx = Context::new(board, mv)?;
+    let mut motifs = find_fork(&ctx)?;
+    motifs.extend(find_lines(&ctx)?);
+    motifs.extend(find_discovered(&ctx)?);
+    motifs.extend(find_double_check(&ctx)?);
+    motifs.extend(find_back_rank(&ctx)?);
+    motifs.extend(find_hanging(&ctx)?);
+    motifs.extend(find_overloaded(&ctx)?);
+    Ok(motifs)
+}
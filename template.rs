@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+use super::board::Board;
+use super::clock::ClockState;
+use super::geometry::Geometry;
+use super::piece::meta::MOVED_META_KEY;
+use super::piece::{Castle, Color, MoveMeta, Piece, PieceEntity, Position};
+use super::utils::ChessError;
+
+pub const TEMPLATE_VERSION: u32 = 2;
+pub static DEFAULT_VARIANT: &str = "standard";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+pub struct BoardTemplate {
+    pub version: u32,
+    pub variant: String,
+    pub geometry: Geometry,
+    pub turn: Color,
+    pub castling: Castle,
+    pub passant_target: Option<Position>,
+    pub halfmove: u8,
+    pub fullmove: u8,
+    pub history: Vec<MoveMeta>,
+    pub entities: Vec<PieceEntity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockState>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum VersionedTemplate {
+    Versioned(BoardTemplate),
+    V1(Vec<Option<PieceEntity>>),
+}
+
+impl TryFrom<VersionedTemplate> for BoardTemplate {
+    type Error = ChessError;
+
+    fn try_from(template: VersionedTemplate) -> Result<Self, Self::Error> {
+        match template {
+            VersionedTemplate::Versioned(template) if template.version != TEMPLATE_VERSION => {
+                Err(ChessError::ParseError(format!(
+                    "unsupported template version {}, expected {}",
+                    template.version, TEMPLATE_VERSION
+                )))
+            }
+            VersionedTemplate::Versioned(template) => Ok(template),
+            VersionedTemplate::V1(entities) => Ok(Self::migrate_v1(entities)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+impl BoardTemplate {
+    pub fn parse(template: &str) -> Result<Self, ChessError> {
+        if let Ok(probe) = serde_json::from_str::<VersionProbe>(template) {
+            if probe.version != TEMPLATE_VERSION {
+                return Err(ChessError::ParseError(format!(
+                    "unsupported template version {}, expected {}",
+                    probe.version, TEMPLATE_VERSION
+                )));
+            }
+        }
+
+        let parsed: VersionedTemplate = serde_json::from_str(template)
+            .map_err(|err| ChessError::ParseError(format!("fail parsing template: {}", err)))?;
+
+        parsed.try_into()
+    }
+
+    pub fn migrate_v1(entities: Vec<Option<PieceEntity>>) -> Self {
+        let entities: Vec<PieceEntity> = entities.into_iter().flatten().collect();
+        let geometry = Geometry::STANDARD;
+
+        let unmoved = |piece: Piece, rank: usize, file: usize| {
+            entities.iter().any(|e| {
+                e.piece == piece
+                    && e.pos == Position { rank, file }
+                    && e.get_meta(MOVED_META_KEY) != Some(&serde_json::Value::Bool(true))
+            })
+        };
+        let (last_rank, last_file) = (geometry.height - 1, geometry.width - 1);
+        let white_king = unmoved(Piece::King(Color::White), 0, 4);
+        let black_king = unmoved(Piece::King(Color::Black), last_rank, 4);
+
+        let castling = Castle {
+            white_king: white_king && unmoved(Piece::Rook(Color::White), 0, last_file),
+            white_queen: white_king && unmoved(Piece::Rook(Color::White), 0, 0),
+            black_king: black_king && unmoved(Piece::Rook(Color::Black), last_rank, last_file),
+            black_queen: black_king && unmoved(Piece::Rook(Color::Black), last_rank, 0),
+        };
+
+        Self {
+            version: TEMPLATE_VERSION,
+            variant: DEFAULT_VARIANT.into(),
+            geometry,
+            turn: Color::White,
+            castling,
+            passant_target: None,
+            halfmove: 0,
+            fullmove: 1,
+            history: Vec::new(),
+            entities,
+            clock: None,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, ChessError> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| ChessError::InternalError(format!("fail writing template: {}", err)))
+    }
+}
+
+impl From<Board> for BoardTemplate {
+    fn from(board: Board) -> Self {
+        board.template()
+    }
+}
+
+impl TryFrom<VersionedTemplate> for Board {
+    type Error = ChessError;
+
+    fn try_from(template: VersionedTemplate) -> Result<Self, Self::Error> {
+        let mut board = Board::new();
+        board.load_template(template.try_into()?)?;
+        Ok(board)
+    }
+}
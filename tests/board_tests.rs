@@ -66,3 +66,80 @@ fn template_parsing() -> Result<(), ChessError> {
     );
     Ok(())
 }
+
+#[test]
+fn make_move() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".into())?;
+
+    let e2 = piece::Position { rank: 1, file: 4 };
+    let e4 = piece::Position { rank: 3, file: 4 };
+    assert!(board
+        .make_move(e2, piece::Position { rank: 4, file: 4 }, None)
+        .is_err());
+
+    board.make_move(e2, e4, None)?;
+    assert_eq!(
+        board.generate_fen()?,
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+    );
+    assert!(board
+        .make_move(e4, piece::Position { rank: 4, file: 4 }, None)
+        .is_err());
+    assert_eq!(board.get_history().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn make_move_castling() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 10".into())?;
+
+    board.make_move(
+        piece::Position { rank: 0, file: 4 },
+        piece::Position { rank: 0, file: 6 },
+        None,
+    )?;
+    assert_eq!(board.generate_fen()?, "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 4 10");
+
+    board.make_move(
+        piece::Position { rank: 7, file: 4 },
+        piece::Position { rank: 7, file: 2 },
+        None,
+    )?;
+    assert_eq!(board.generate_fen()?, "2kr3r/8/8/8/8/8/8/R4RK1 w - - 5 11");
+    Ok(())
+}
+
+#[test]
+fn make_move_en_passant() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("4k3/8/8/4P3/8/8/8/4K3 b - - 0 1".into())?;
+    board.set(6, 3, Some(piece::Piece::Pawn(piece::Color::Black)))?;
+    let mut pawn = piece::PieceEntity::new(
+        piece::Piece::Pawn(piece::Color::Black),
+        piece::Position { rank: 6, file: 3 },
+    );
+    pawn.set_meta("moved", false);
+    board.set_entity(6, 3, Some(pawn));
+
+    board.make_move(
+        piece::Position { rank: 6, file: 3 },
+        piece::Position { rank: 4, file: 3 },
+        None,
+    )?;
+    assert_eq!(
+        board.get_passant_target(),
+        Some(piece::Position { rank: 5, file: 3 })
+    );
+
+    let meta = board.make_move(
+        piece::Position { rank: 4, file: 4 },
+        piece::Position { rank: 5, file: 3 },
+        None,
+    )?;
+    assert_eq!(meta.capture, Some(piece::Position { rank: 4, file: 3 }));
+    assert_eq!(board.get(4, 3)?, None);
+    assert_eq!(board.generate_fen()?, "4k3/8/3P4/8/8/8/8/4K3 b - - 0 2");
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use super::super::*;
+use clock::{Clock, ClockMode, ManualTimeSource, TimeControl, TimeStage};
+use piece::{Color, Position};
+use rules::Outcome;
+use std::sync::Arc;
+use std::time::Duration;
+use utils::ChessError;
+
+fn secs(s: u64) -> Duration {
+    Duration::from_secs(s)
+}
+
+fn manual_clock(control: TimeControl) -> Result<(Clock, Arc<ManualTimeSource>), ChessError> {
+    let source = Arc::new(ManualTimeSource::new());
+    let mut clock = Clock::with_source(control, source.clone())?;
+    clock.start(Color::White);
+    Ok((clock, source))
+}
+
+#[test]
+fn clock_modes() -> Result<(), ChessError> {
+    let (mut fischer, time) = manual_clock(TimeControl::fischer(secs(60), secs(2)))?;
+    time.advance(secs(10));
+    assert_eq!(fischer.remaining(Color::White), secs(50));
+    assert_eq!(fischer.press()?, secs(10));
+    assert_eq!(fischer.remaining(Color::White), secs(52));
+    assert_eq!(fischer.running(), Some(Color::Black));
+
+    let bronstein = TimeControl::sudden_death(ClockMode::Bronstein, secs(60), secs(5));
+    let (mut bronstein, time) = manual_clock(bronstein)?;
+    time.advance(secs(3));
+    bronstein.press()?;
+    assert_eq!(bronstein.remaining(Color::White), secs(60));
+    time.advance(secs(8));
+    bronstein.press()?;
+    assert_eq!(bronstein.remaining(Color::Black), secs(57));
+
+    let delay = TimeControl::sudden_death(ClockMode::SimpleDelay, secs(60), secs(5));
+    let (mut delay, time) = manual_clock(delay)?;
+    time.advance(secs(4));
+    assert_eq!(delay.remaining(Color::White), secs(60));
+    time.advance(secs(4));
+    assert_eq!(delay.remaining(Color::White), secs(57));
+    delay.press()?;
+    assert_eq!(delay.remaining(Color::White), secs(57));
+
+    let (mut hourglass, time) = manual_clock(TimeControl::hourglass(secs(60)))?;
+    time.advance(secs(15));
+    assert_eq!(hourglass.remaining(Color::Black), secs(75));
+    hourglass.press()?;
+    assert_eq!(hourglass.remaining(Color::White), secs(45));
+    assert_eq!(hourglass.remaining(Color::Black), secs(75));
+    Ok(())
+}
+
+#[test]
+fn clock_stages() -> Result<(), ChessError> {
+    let control = TimeControl::from_pgn("2/5400+30:1800+30")?;
+    assert_eq!(
+        control.stages[0],
+        TimeStage {
+            moves: Some(2),
+            time: secs(5400),
+            bonus: secs(30),
+        }
+    );
+    assert_eq!(control.to_pgn(), "2/5400+30:1800+30");
+    assert_eq!(TimeControl::from_pgn("*180")?.mode, ClockMode::Hourglass);
+    assert_eq!(TimeControl::from_pgn("300d5")?.mode, ClockMode::SimpleDelay);
+    assert_eq!(TimeControl::from_pgn("300b5")?.mode, ClockMode::Bronstein);
+    let bronstein = TimeControl::sudden_death(ClockMode::Bronstein, secs(300), secs(5));
+    assert_eq!(TimeControl::from_pgn(&bronstein.to_pgn())?, bronstein);
+    assert!(TimeControl::from_pgn("40/").is_err());
+    assert!(TimeControl::from_pgn("0/60").is_err());
+    let empty = TimeControl {
+        mode: ClockMode::Fischer,
+        stages: Vec::new(),
+    };
+    assert!(Clock::with_source(empty, Arc::new(ManualTimeSource::new())).is_err());
+
+    let (mut clock, time) = manual_clock(control)?;
+    for _ in 0..2 {
+        time.advance(secs(100));
+        clock.press()?;
+        clock.press()?;
+    }
+    assert_eq!(clock.remaining(Color::White), secs(5400 - 200 + 60 + 1800));
+    assert_eq!(clock.remaining(Color::Black), secs(5400 + 60 + 1800));
+    Ok(())
+}
+
+#[test]
+fn clock_flag_fall() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1".to_string())?;
+
+    let (clock, time) = manual_clock(TimeControl::fischer(secs(10), Duration::ZERO))?;
+    board.set_clock(Some(clock));
+
+    time.advance(secs(4));
+    board.make_move(
+        Position { rank: 1, file: 4 },
+        Position { rank: 2, file: 4 },
+        None,
+    )?;
+    let clock = board.clock().unwrap();
+    assert_eq!(clock.remaining(Color::White), secs(6));
+    assert_eq!(clock.running(), Some(Color::Black));
+
+    time.advance(secs(11));
+    assert!(board
+        .make_move(
+            Position { rank: 7, file: 4 },
+            Position { rank: 7, file: 3 },
+            None
+        )
+        .is_err());
+    assert_eq!(board.clock().unwrap().flagged(), Some(Color::Black));
+    assert_eq!(
+        rules::outcome(&mut board)?,
+        Some(Outcome::Timeout {
+            winner: Color::White
+        })
+    );
+
+    board.from_fen("4k3/8/8/8/8/8/8/4KB2 w - - 0 1".to_string())?;
+    assert_eq!(
+        rules::outcome(&mut board)?,
+        Some(Outcome::TimeoutVsInsufficientMaterial)
+    );
+
+    board.set_clock(None);
+    assert_eq!(
+        rules::outcome(&mut board)?,
+        Some(Outcome::InsufficientMaterial)
+    );
+    Ok(())
+}
+
+#[test]
+fn clock_state_round_trip() -> Result<(), ChessError> {
+    let (mut clock, time) = manual_clock(TimeControl::fischer(secs(60), secs(1)))?;
+    time.advance(secs(20));
+    clock.press()?;
+    time.advance(secs(5));
+
+    let mut board = board::Board::new();
+    board.set_clock(Some(clock));
+    let json = serde_json::to_string(&board).unwrap();
+    let loaded: board::Board = serde_json::from_str(&json).unwrap();
+
+    let clock = loaded.clock().unwrap();
+    assert_eq!(clock.running(), None);
+    assert_eq!(clock.remaining(Color::White), secs(41));
+    assert_eq!(clock.remaining(Color::Black), secs(55));
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use super::super::*;
+use encoding::{decode_moves, encode_moves};
+use geometry::Geometry;
+use piece::{Color, Move, Piece, Position};
+use utils::ChessError;
+
+fn round_trip(fen: &str, geometry: Geometry) -> Result<Vec<u8>, ChessError> {
+    let mut board = board::Board::with_geometry(geometry);
+    board.from_fen(fen.to_string())?;
+
+    let bytes = board.encode_position()?;
+    let decoded = board::Board::decode_position(&bytes, geometry)?;
+    assert_eq!(decoded.generate_fen()?, fen);
+    assert_eq!(decoded.encode_position()?, bytes);
+    Ok(bytes)
+}
+
+#[test]
+fn position_encoding() -> Result<(), ChessError> {
+    let start = round_trip(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        Geometry::STANDARD,
+    )?;
+    assert_eq!(start.len(), 27);
+
+    let ep = round_trip(
+        "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3",
+        Geometry::STANDARD,
+    )?;
+    assert!(ep.len() <= 32);
+
+    round_trip("4k3/8/8/8/8/8/8/4K3 b - - 12 40", Geometry::STANDARD)?;
+    round_trip("rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1", Geometry::GARDNER)?;
+    round_trip(
+        "r8r/10/10/10/10/10/10/10/10/R4K3R b - - 0 1",
+        Geometry::GRAND,
+    )?;
+    round_trip(
+        "r8r/10/10/10/10/4P5/10/10/10/R4K3R b - e4 0 1",
+        Geometry::GRAND,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn decoded_position_is_playable() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".into())?;
+
+    let mut decoded = board::Board::decode_position(&board.encode_position()?, Geometry::STANDARD)?;
+    decoded.make_move(
+        Position { rank: 1, file: 4 },
+        Position { rank: 3, file: 4 },
+        None,
+    )?;
+    assert_eq!(decoded.get(3, 4)?, Some(Piece::Pawn(Color::White)));
+    Ok(())
+}
+
+#[test]
+fn invalid_position_bytes() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1".into())?;
+    let bytes = board.encode_position()?;
+
+    assert!(board::Board::decode_position(&bytes[..bytes.len() - 1], Geometry::STANDARD).is_err());
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(board::Board::decode_position(&trailing, Geometry::STANDARD).is_err());
+
+    let mut bad_code = bytes.clone();
+    bad_code[8] = 0x77;
+    assert!(board::Board::decode_position(&bad_code, Geometry::STANDARD).is_err());
+
+    // an odd piece count leaves the high nibble of the last code unused
+    board.from_fen("4k3/8/8/8/8/8/8/4KQ2 w - - 0 1".into())?;
+    let mut padded = board.encode_position()?;
+    padded[9] |= 0x10;
+    assert!(board::Board::decode_position(&padded, Geometry::STANDARD).is_err());
+    Ok(())
+}
+
+#[test]
+fn move_packing() -> Result<(), ChessError> {
+    let e2e4 = Move::new(Position { rank: 1, file: 4 }, Position { rank: 3, file: 4 });
+    assert_eq!(e2e4.pack(Geometry::STANDARD)?, 12 | 28 << 6);
+    assert_eq!(
+        Move::unpack(
+            e2e4.pack(Geometry::STANDARD)?,
+            Geometry::STANDARD,
+            Color::White
+        )?,
+        e2e4
+    );
+
+    let promotion = Move {
+        from: Position { rank: 1, file: 0 },
+        to: Position { rank: 0, file: 0 },
+        promotion: Some(Piece::Knight(Color::Black)),
+    };
+    let moves = vec![e2e4, promotion];
+    let bytes = encode_moves(&moves, Geometry::STANDARD)?;
+    assert_eq!(bytes.len(), 4);
+    assert_eq!(
+        decode_moves(&bytes, Geometry::STANDARD, Color::White)?,
+        moves
+    );
+
+    assert!(e2e4.pack(Geometry::GRAND).is_err());
+    assert!(Move {
+        promotion: Some(Piece::King(Color::White)),
+        ..e2e4
+    }
+    .pack(Geometry::STANDARD)
+    .is_err());
+    assert!(Move::unpack(0xf000, Geometry::STANDARD, Color::White).is_err());
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use super::super::*;
+use super::board;
+use board::Board;
+use endgame::{Dtm, Endgames};
+use piece::Move;
+use rules::is_checkmate;
+use utils::ChessError;
+
+/// Plays the tables' best moves for both sides until the game ends.
+fn play_out(endgames: &Endgames, position: &mut Board) -> Result<u32, ChessError> {
+    let mut plies = 0;
+    while let Some(mv) = endgames.best_move(position)? {
+        position.make_move(mv.from, mv.to, mv.promotion)?;
+        plies += 1;
+    }
+    Ok(plies)
+}
+
+#[test]
+fn endgame_pawnless() -> Result<(), ChessError> {
+    let mut endgames = Endgames::new();
+    endgames.solve("KQvK")?;
+    endgames.solve("KRvK")?;
+    assert_eq!(endgames.materials(), vec!["KQvK", "KRvK", "KvK"]);
+    assert!(endgames.solve("KQRBvK").is_err());
+    assert!(endgames.solve("KQK").is_err());
+
+    // the known longest mates: ten moves with a queen, sixteen with a rook
+    assert_eq!(endgames.table("KQvK").unwrap().longest_mate(), Some(19));
+    assert_eq!(endgames.table("KRvK").unwrap().longest_mate(), Some(31));
+
+    let mut mate_in_one = board("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1")?;
+    assert_eq!(endgames.dtm(&mate_in_one)?, Some(Dtm::Win(1)));
+    play_out(&endgames, &mut mate_in_one)?;
+    assert!(is_checkmate(&mut mate_in_one, piece::Color::Black)?);
+
+    // the defender takes the rook when it can
+    let hanging = board("8/8/8/8/8/3k4/3R4/7K b - - 0 1")?;
+    assert_eq!(endgames.dtm(&hanging)?, Some(Dtm::Draw));
+    let take = Move::from_uci("d3d2", hanging.geometry(), hanging.get_turn())?;
+    assert_eq!(endgames.best_move(&hanging)?, Some(take));
+
+    // black to move in a queen ending is covered by the same table
+    let mut defending = board("8/8/8/4k3/8/8/8/K6Q b - - 0 1")?;
+    let Some(Dtm::Loss(plies)) = endgames.dtm(&defending)? else {
+        panic!("black should be lost");
+    };
+    assert_eq!(play_out(&endgames, &mut defending)?, plies);
+    assert!(is_checkmate(&mut defending, piece::Color::Black)?);
+
+    let uncovered = board("4k3/8/8/8/8/8/8/RB2K3 w - - 0 1")?;
+    assert_eq!(endgames.dtm(&uncovered)?, None);
+    assert_eq!(endgames.best_move(&uncovered)?, None);
+    Ok(())
+}
+
+#[test]
+fn endgame_pawn() -> Result<(), ChessError> {
+    let mut endgames = Endgames::new();
+    endgames.solve("KPvK")?;
+    assert_eq!(
+        endgames.materials(),
+        vec!["KBvK", "KNvK", "KPvK", "KQvK", "KRvK", "KvK"]
+    );
+
+    // opposition decides who wins
+    let opposition = board("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1")?;
+    assert_eq!(endgames.dtm(&opposition)?, Some(Dtm::Draw));
+    let mut winning = board("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1")?;
+    let Some(Dtm::Loss(plies)) = endgames.dtm(&winning)? else {
+        panic!("black should be lost");
+    };
+    assert_eq!(play_out(&endgames, &mut winning)?, plies);
+    assert!(is_checkmate(&mut winning, piece::Color::Black)?);
+
+    // the rook pawn cannot win against a king in the corner
+    let rook_pawn = board("k7/8/1K6/P7/8/8/8/8 w - - 0 1")?;
+    assert_eq!(endgames.dtm(&rook_pawn)?, Some(Dtm::Draw));
+
+    // black's pawn is looked up in the white pawn table
+    let reversed = board("8/8/8/4p3/4k3/8/4K3/8 b - - 0 1")?;
+    assert_eq!(endgames.dtm(&reversed)?, Some(Dtm::Draw));
+    let reversed = board("8/8/8/4p3/4k3/8/4K3/8 w - - 0 1")?;
+    assert!(matches!(endgames.dtm(&reversed)?, Some(Dtm::Loss(_))));
+    Ok(())
+}
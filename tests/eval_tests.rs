@@ -0,0 +1,114 @@
+use super::super::*;
+use super::board;
+use eval::{evaluate, evaluate_with, EvalParams, PieceSquareTable, Tapered};
+use utils::ChessError;
+
+fn mirror(fen: &str) -> String {
+    let parts: Vec<&str> = fen.split_whitespace().collect();
+    let placement: Vec<String> = parts[0]
+        .split('/')
+        .rev()
+        .map(|rank| {
+            rank.chars()
+                .map(|c| match c.is_ascii_uppercase() {
+                    true => c.to_ascii_lowercase(),
+                    false => c.to_ascii_uppercase(),
+                })
+                .collect()
+        })
+        .collect();
+    let turn = match parts[1] {
+        "w" => "b",
+        _ => "w",
+    };
+    format!("{} {} - - 0 1", placement.join("/"), turn)
+}
+
+fn zeroed() -> EvalParams {
+    EvalParams {
+        material: [Tapered::default(); 6],
+        psqt: std::array::from_fn(|_| PieceSquareTable {
+            mg: vec![0; 64],
+            eg: vec![0; 64],
+        }),
+        mobility: [Tapered::default(); 6],
+        doubled_pawn: Tapered::default(),
+        isolated_pawn: Tapered::default(),
+        backward_pawn: Tapered::default(),
+        passed_pawn: [Tapered::default(); 8],
+        king_shield: Tapered::default(),
+        king_open_file: Tapered::default(),
+        king_attack: Tapered::default(),
+        bishop_pair: Tapered::default(),
+    }
+}
+
+#[test]
+fn eval_is_symmetric() -> Result<(), ChessError> {
+    assert_eq!(evaluate(&board(game::START_FEN)?)?, 0);
+
+    for fen in [
+        "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 w kq - 0 1",
+        "8/5pk1/6p1/3P4/1r6/6P1/5PK1/3R4 w - - 0 1",
+        "4k3/8/8/8/8/8/3P4/4K3 b - - 0 1",
+    ] {
+        let score = evaluate(&board(fen)?)?;
+        assert_eq!(evaluate(&board(&mirror(fen))?)?, score, "{}", fen);
+    }
+
+    let up = "4k3/8/8/8/8/8/8/3NK3 w - - 0 1";
+    let score = evaluate(&board(up)?)?;
+    assert!(score > 250);
+    assert_eq!(evaluate(&board("4k3/8/8/8/8/8/8/3NK3 b - - 0 1")?)?, -score);
+    Ok(())
+}
+
+#[test]
+fn eval_terms() -> Result<(), ChessError> {
+    let mut params = zeroed();
+    params.doubled_pawn = Tapered(-10, -20);
+    let doubled = board("4k3/8/8/8/4P3/4P3/8/4K3 w - - 0 1")?;
+    assert_eq!(evaluate_with(&doubled, &params)?, -20);
+
+    let mut params = zeroed();
+    params.isolated_pawn = Tapered(-10, -15);
+    let isolated = board("4k3/8/8/8/8/8/P4P2/4K3 w - - 0 1")?;
+    assert_eq!(evaluate_with(&isolated, &params)?, -30);
+    let connected = board("4k3/8/8/8/8/8/PP6/4K3 w - - 0 1")?;
+    assert_eq!(evaluate_with(&connected, &params)?, 0);
+
+    let mut params = zeroed();
+    params.passed_pawn = EvalParams::default().passed_pawn;
+    let passed = board("4k3/8/4P3/8/8/8/8/4K3 w - - 0 1")?;
+    assert_eq!(evaluate_with(&passed, &params)?, 100);
+    let stopped = board("4k3/3p4/4P3/8/8/8/8/4K3 w - - 0 1")?;
+    assert_eq!(evaluate_with(&stopped, &params)?, 0);
+
+    let mut params = zeroed();
+    params.backward_pawn = Tapered(-8, -10);
+    let unguarded = board("4k3/8/8/8/8/1P6/P7/4K3 w - - 0 1")?;
+    assert_eq!(evaluate_with(&unguarded, &params)?, 0);
+    let backward = board("4k3/8/8/8/1p6/1P6/P7/4K3 w - - 0 1")?;
+    assert_eq!(evaluate_with(&backward, &params)?, -10);
+
+    // phase 2 of 24 blends 30 and 50
+    let mut params = zeroed();
+    params.bishop_pair = Tapered(30, 50);
+    let pair = board("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1")?;
+    assert_eq!(evaluate_with(&pair, &params)?, (30 * 2 + 50 * 22) / 24);
+    Ok(())
+}
+
+#[test]
+fn eval_params_json() -> Result<(), ChessError> {
+    let params = EvalParams::default();
+    let json = params.to_json()?;
+    assert_eq!(EvalParams::parse(&json)?, params);
+    assert!(json.contains("\"bishop_pair\": [\n    30,\n    50\n  ]"));
+
+    let mut short = params.clone();
+    short.psqt[2].eg.pop();
+    assert!(EvalParams::parse(&short.to_json()?).is_err());
+    assert!(EvalParams::parse(&json.replace("bishop_pair", "bishops")).is_err());
+    Ok(())
+}
@@ -0,0 +1,29 @@
+#!/bin/sh
+# Scripted UCI engine for the client tests. It ignores the position and always
+# reports the same search from the starting position.
+while read -r line; do
+    case "$line" in
+        uci)
+            echo "id name Fake Engine 1.0"
+            echo "id author The Tests"
+            echo "option name Hash type spin default 16 min 1 max 1024"
+            echo "option name Ponder type check default false"
+            echo "option name Style type combo default Normal var Solid var Normal var Risky"
+            echo "option name Clear Hash type button"
+            echo "option name Book File type string default <empty>"
+            echo "uciok"
+            ;;
+        isready)
+            echo "readyok"
+            ;;
+        go*)
+            echo "info string searching $line"
+            echo "info depth 1 seldepth 2 multipv 1 score cp 20 nodes 20 nps 2000 time 10 pv e2e4 e7e5"
+            echo "info depth 2 multipv 2 score mate -3 upperbound hashfull 5 pv d2d4"
+            echo "bestmove e2e4 ponder e7e5"
+            ;;
+        quit)
+            exit 0
+            ;;
+    esac
+done
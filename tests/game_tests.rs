@@ -0,0 +1,84 @@
+use super::super::*;
+use clock::{Clock, ManualTimeSource, TimeControl};
+use game::{Game, GameResult, Player, Termination};
+use piece::Color;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use utils::ChessError;
+
+#[test]
+fn game_checkmate_and_pgn() -> Result<(), ChessError> {
+    let mut game = Game::new()?;
+    game.set_player(Color::White, Player::rated("Anderssen", 2600));
+    game.set_player(Color::Black, Player::new("Kieseritzky"));
+    game.set_event("Casual \"Immortal\" game");
+    game.set_started_at(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+
+    let time = Arc::new(ManualTimeSource::new());
+    game.set_clock(Some(Clock::with_source(
+        TimeControl::fischer(Duration::from_secs(300), Duration::from_secs(2)),
+        time.clone(),
+    )?));
+
+    for san in ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6"] {
+        time.advance(Duration::from_secs(5));
+        game.play_san(san)?;
+    }
+    assert_eq!(game.play_san("Qxf7")?.san, "Qxf7#");
+    assert_eq!(game.result(), GameResult::WhiteWins);
+    assert_eq!(game.termination(), Some(Termination::Checkmate));
+    assert!(game.play_san("Kxf7").is_err());
+
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[Event \"Casual \\\"Immortal\\\" game\"]\n"));
+    assert!(pgn.contains("[Date \"2023.11.14\"]\n"));
+    assert!(pgn.contains("[UTCTime \"22:13:20\"]\n"));
+    assert!(pgn.contains("[WhiteElo \"2600\"]\n"));
+    assert!(!pgn.contains("BlackElo"));
+    assert!(pgn.contains("[TimeControl \"300+2\"]\n"));
+    assert!(pgn.contains("[Termination \"normal\"]\n"));
+    assert!(pgn.contains("1. e4 {[%clk 0:05:02]} e5 {[%clk 0:04:57]}"));
+    assert!(pgn.contains("4. Qxf7#"));
+    assert!(pgn.ends_with("{[%clk 0:04:58]} 1-0\n"));
+    assert!(pgn.lines().all(|line| line.len() < 80));
+    Ok(())
+}
+
+#[test]
+fn game_actions() -> Result<(), ChessError> {
+    let mut game = Game::new()?;
+    game.play_san("d4")?;
+    game.abort()?;
+    assert!(game.is_over());
+    assert!(game.play_san("d5").is_err());
+
+    let mut game = Game::new()?;
+    game.play_san("d4")?;
+    game.play_san("d5")?;
+    assert!(game.abort().is_err());
+
+    game.offer_draw(Color::White)?;
+    assert!(game.accept_draw(Color::White).is_err());
+    game.play_san("c4")?;
+    assert_eq!(game.draw_offer(), Some(Color::White));
+    game.play_san("e6")?;
+    assert_eq!(game.draw_offer(), None);
+
+    game.offer_draw(Color::White)?;
+    game.accept_draw(Color::Black)?;
+    assert_eq!(game.result(), GameResult::Draw);
+    assert_eq!(game.termination(), Some(Termination::DrawAgreement));
+
+    let mut board = board::Board::new();
+    board.from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30".to_string())?;
+    let mut game = Game::from_board(board)?;
+    game.play_san("Kd7")?;
+    game.resign(Color::White)?;
+    assert_eq!(game.result(), GameResult::BlackWins);
+
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[SetUp \"1\"]\n"));
+    assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]\n"));
+    assert!(pgn.contains("30... Kd7 0-1"));
+    Ok(())
+}
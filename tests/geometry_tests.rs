@@ -0,0 +1,143 @@
+use super::super::*;
+use geometry::Geometry;
+use piece::{Color, Piece, PieceEntity, Position};
+use utils::ChessError;
+
+#[test]
+fn geometry_bounds() -> Result<(), ChessError> {
+    assert!(Geometry::new(0, 8).is_err());
+    assert!(Geometry::new(27, 4).is_err());
+    assert!(Geometry::new(12, 12).is_err());
+    assert_eq!(Geometry::new(10, 10)?, Geometry::GRAND);
+
+    let grand = Geometry::GRAND;
+    assert_eq!(grand.squares(), 100);
+    assert_eq!(grand.mask(9, 9)?, 1 << 99);
+    assert_eq!(grand.unmask(1 << 99)?, (9, 9));
+    assert!(grand.mask(10, 0).is_err());
+    assert_eq!(grand.full_mask().count_ones(), 100);
+
+    let gardner = Geometry::GARDNER;
+    assert_eq!(gardner.shift(Position { rank: 4, file: 4 }, 1, 0), None);
+    assert_eq!(
+        gardner.shift(Position { rank: 0, file: 0 }, 1, 1),
+        Some(Position { rank: 1, file: 1 })
+    );
+    Ok(())
+}
+
+#[test]
+fn square_names() -> Result<(), ChessError> {
+    let grand = Geometry::GRAND;
+    assert_eq!(grand.square_name(Position { rank: 9, file: 9 }), "j10");
+    assert_eq!(grand.parse_square("j10")?, Position { rank: 9, file: 9 });
+    assert!(Geometry::STANDARD.parse_square("j10").is_err());
+    assert!(Geometry::STANDARD.parse_square("e0").is_err());
+    Ok(())
+}
+
+#[test]
+fn capablanca_fen() -> Result<(), ChessError> {
+    let fen = "r1b1kq1b1r/pppppppppp/10/10/10/10/PPPPPPPPPP/R1B1KQ1B1R w KQkq - 0 1";
+
+    let mut board = board::Board::with_geometry(Geometry::CAPABLANCA);
+    board.from_fen(fen.to_string())?;
+
+    assert_eq!(board.get(0, 9)?, Some(Piece::Rook(Color::White)));
+    assert_eq!(board.get(7, 4)?, Some(Piece::King(Color::Black)));
+    assert_eq!(board.generate_fen()?, fen);
+
+    let mut standard = board::Board::new();
+    assert!(standard.from_fen(fen.to_string()).is_err());
+    Ok(())
+}
+
+#[test]
+fn grand_fen_passant() -> Result<(), ChessError> {
+    let fen = "10/10/10/10/10/3pP5/10/10/10/4K4k b - e4 0 1";
+
+    let mut board = board::Board::with_geometry(Geometry::GRAND);
+    board.from_fen(fen.to_string())?;
+
+    let e4 = Position { rank: 3, file: 4 };
+    assert_eq!(board.get_passant_target(), Some(e4));
+    assert_eq!(board.generate_fen()?, fen);
+
+    let pawn = board.get_entity(4, 3).unwrap();
+    assert!(pawn.legal_moves(&board)?.contains(&e4));
+
+    board.make_move(Position { rank: 4, file: 3 }, e4, None)?;
+    assert!(board.get_entity(4, 4).is_none());
+    assert!(board.get_entity(3, 4).is_some());
+    assert_eq!(board.get_passant_target(), None);
+    Ok(())
+}
+
+#[test]
+fn position_from_fen() -> Result<(), ChessError> {
+    assert_eq!(Position::from_fen("j10")?, Position { rank: 9, file: 9 });
+    for name in ["", "z", "e", "e0", "E4", "4e"] {
+        assert!(Position::from_fen(name).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn grand_fen_pawn_double_step() -> Result<(), ChessError> {
+    let mut board = board::Board::with_geometry(Geometry::GRAND);
+    board.from_fen(
+        "r8r/1nbqk1bn2/pppppppppp/10/10/10/10/PPPPPPPPPP/1NBQK1BN2/R8R w - - 0 1".to_string(),
+    )?;
+
+    let white = board.get_entity(2, 4).unwrap();
+    let moves = white.legal_moves(&board)?;
+    assert!(moves.contains(&Position { rank: 4, file: 4 }));
+
+    let black = board.get_entity(7, 4).unwrap();
+    let moves = black.legal_moves(&board)?;
+    assert!(moves.contains(&Position { rank: 5, file: 4 }));
+    Ok(())
+}
+
+#[test]
+fn generators_respect_geometry() -> Result<(), ChessError> {
+    let mut board = board::Board::with_geometry(Geometry::GARDNER);
+
+    let rook = PieceEntity::new(Piece::Rook(Color::White), Position { rank: 0, file: 0 });
+    board.set(0, 0, Some(rook.piece))?;
+    board.set_entity(0, 0, Some(rook.clone()));
+
+    let moves = rook.legal_moves(&board)?;
+    assert_eq!(moves.len(), 8);
+    assert!(moves.iter().all(|m| m.rank < 5 && m.file < 5));
+
+    let pawn = PieceEntity::new(Piece::Pawn(Color::White), Position { rank: 4, file: 2 });
+    board.set(4, 2, Some(pawn.piece))?;
+    board.set_entity(4, 2, Some(pawn.clone()));
+
+    assert!(pawn.legal_moves(&board)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn rank_and_file_masks() -> Result<(), ChessError> {
+    let geometry = Geometry::LOS_ALAMOS;
+    assert_eq!(geometry.file_mask(0).count_ones(), 6);
+    assert_eq!(
+        geometry.file_mask(0) & geometry.mask(5, 0)?,
+        geometry.mask(5, 0)?
+    );
+
+    assert_eq!(geometry.ranks_mask(0, false), 0);
+    assert_eq!(geometry.ranks_mask(2, false), 0xfff);
+    assert_eq!(geometry.ranks_mask(5, true), 0);
+    assert_eq!(
+        geometry.ranks_mask(1, true) | geometry.ranks_mask(2, false),
+        geometry.full_mask()
+    );
+
+    let grand = Geometry::GRAND;
+    assert_eq!(grand.ranks_mask(8, true).count_ones(), 10);
+    assert_eq!(grand.ranks_mask(9, false).count_ones(), 90);
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use super::super::*;
+use hooks::{HookEvent, HookFlow, HookRegistry};
+use piece::{Color, Piece, Position};
+use utils::ChessError;
+
+fn pos(rank: usize, file: usize) -> Position {
+    Position { rank, file }
+}
+
+fn frozen_registry() -> HookRegistry {
+    let mut hooks = HookRegistry::new();
+
+    hooks.register("frozen", HookEvent::Move, |board, ctx| {
+        let entity = board.get_entity(ctx.pos.rank, ctx.pos.file).unwrap();
+        match entity.get_meta("frozen").and_then(|v| v.as_u64()) {
+            Some(turns) if turns > 0 => Ok(HookFlow::Cancel),
+            _ => Ok(HookFlow::Continue),
+        }
+    });
+    hooks.register("frozen", HookEvent::TurnStart, |board, ctx| {
+        let entity = board.get_entity_mut(ctx.pos.rank, ctx.pos.file).unwrap();
+        if let Some(turns) = entity.get_meta("frozen").and_then(|v| v.as_u64()) {
+            entity.set_meta("frozen", turns.saturating_sub(1));
+        }
+        Ok(HookFlow::Continue)
+    });
+
+    hooks
+}
+
+#[test]
+fn frozen_piece() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1".into())?;
+    board.set_hooks(frozen_registry());
+
+    let rook = board.get_entity_mut(0, 0).unwrap();
+    rook.add_hook("frozen");
+    rook.set_meta("frozen", 1);
+
+    assert!(matches!(
+        board.make_move(pos(0, 0), pos(1, 0), None),
+        Err(ChessError::IllegalMove(_))
+    ));
+
+    board.make_move(pos(0, 4), pos(1, 4), None)?;
+    board.make_move(pos(7, 4), pos(6, 4), None)?;
+
+    assert_eq!(
+        board.get_entity(0, 0).unwrap().get_meta("frozen"),
+        Some(&serde_json::Value::from(0))
+    );
+    board.make_move(pos(0, 0), pos(5, 0), None)?;
+    Ok(())
+}
+
+#[test]
+fn shield_absorbs_capture() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("4k3/8/8/8/8/8/r7/R3K3 w - - 0 1".into())?;
+
+    let mut hooks = HookRegistry::new();
+    hooks.register("shield", HookEvent::Captured, |board, ctx| {
+        let entity = board.get_entity_mut(ctx.pos.rank, ctx.pos.file).unwrap();
+        entity.remove_hook("shield");
+        Ok(HookFlow::Cancel)
+    });
+    board.set_hooks(hooks);
+    board.get_entity_mut(1, 0).unwrap().add_hook("shield");
+
+    assert!(board.make_move(pos(0, 0), pos(1, 0), None).is_err());
+    assert_eq!(board.get(1, 0)?, Some(Piece::Rook(Color::Black)));
+
+    let meta = board.make_move(pos(0, 0), pos(1, 0), None)?;
+    assert_eq!(meta.capture, Some(pos(1, 0)));
+    assert_eq!(board.get(1, 0)?, Some(Piece::Rook(Color::White)));
+    Ok(())
+}
+
+#[test]
+fn piece_type_hooks() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1".into())?;
+
+    let mut hooks = HookRegistry::new();
+    hooks.on_piece(
+        Piece::Queen(Color::White),
+        HookEvent::Promote,
+        |board, ctx| {
+            let entity = board.get_entity_mut(ctx.pos.rank, ctx.pos.file).unwrap();
+            entity.set_meta("promoted_from", ctx.from.unwrap().file);
+            Ok(HookFlow::Continue)
+        },
+    );
+    board.set_hooks(hooks);
+
+    assert!(board.make_move(pos(6, 0), pos(7, 0), None).is_err());
+    board.make_move(pos(6, 0), pos(7, 0), Some(Piece::Queen(Color::White)))?;
+
+    assert_eq!(
+        board.get_entity(7, 0).unwrap().get_meta("promoted_from"),
+        Some(&serde_json::Value::from(0))
+    );
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use super::super::*;
+use super::board;
+use board::Board;
+use mate::{has_forced_mate, solve_mate};
+use piece::Move;
+use utils::ChessError;
+
+fn uci(board: &Board, text: &str) -> Result<Move, ChessError> {
+    Move::from_uci(text, board.geometry(), board.get_turn())
+}
+
+#[test]
+fn mate_in_one() -> Result<(), ChessError> {
+    let corner = board("k7/8/1K6/8/8/8/8/7R w - - 0 1")?;
+    let solution = solve_mate(&corner, 1)?;
+    assert_eq!(solution.mate_in(), Some(1));
+    assert!(!solution.is_cooked());
+    assert_eq!(solution.keys[0].mv, uci(&corner, "h1h8")?);
+    assert!(solution.keys[0].defences.is_empty());
+
+    // either rook mates on the back rank
+    let back_rank = board("6k1/5ppp/8/8/8/8/8/R3R1K1 w - - 0 1")?;
+    let solution = solve_mate(&back_rank, 1)?;
+    assert!(solution.is_cooked());
+    let keys: Vec<Move> = solution.keys.iter().map(|key| key.mv).collect();
+    assert!(keys.contains(&uci(&back_rank, "a1a8")?));
+    assert!(keys.contains(&uci(&back_rank, "e1e8")?));
+
+    // a quiet move leaving no reply is stalemate, not mate
+    let stalemate = board("7k/8/6K1/8/8/8/8/5Q2 w - - 0 1")?;
+    let solution = solve_mate(&stalemate, 2)?;
+    assert_eq!(solution.mate_in(), Some(1));
+    let stalemating = uci(&stalemate, "f1f7")?;
+    assert!(solution.keys.iter().all(|key| key.mv != stalemating));
+    assert!(!solve_mate(&corner, 0)?.is_mate());
+    Ok(())
+}
+
+#[test]
+fn mate_in_two() -> Result<(), ChessError> {
+    let ladder = board("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1")?;
+    assert!(!has_forced_mate(&ladder, 1)?);
+    assert!(has_forced_mate(&ladder, 2)?);
+
+    let solution = solve_mate(&ladder, 2)?;
+    assert_eq!(solution.mate_in(), Some(2));
+    assert!(solution.keys.iter().all(|key| key.mate_in == 2));
+
+    // cutting off the seventh rank leaves the king a single square
+    let key = solution
+        .keys
+        .iter()
+        .find(|key| key.mv == uci(&ladder, "b1b7").unwrap())
+        .expect("b1b7 mates in two");
+    assert_eq!(key.defences.len(), 1);
+    let defence = &key.defences[0];
+    assert_eq!(defence.reply.to, ladder.geometry().parse_square("g8")?);
+    assert!(defence
+        .mates
+        .iter()
+        .any(|mate| mate.mv.to == ladder.geometry().parse_square("a8").unwrap()));
+    assert!(defence.mates.iter().all(|mate| mate.mate_in == 1));
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use super::super::*;
+use piece::meta::{MetaSchema, MetaType, HOOKS_META_KEY, MOVED_META_KEY};
+use piece::{Color, Piece, PieceEntity, Position};
+use utils::ChessError;
+
+#[test]
+fn typed_meta() -> Result<(), ChessError> {
+    let mut schema = MetaSchema::standard();
+    schema.register("frozen", MetaType::Int, 0)?;
+    assert!(schema.register("shield", MetaType::Bool, "yes").is_err());
+
+    let mut entity = PieceEntity::new(Piece::Rook(Color::White), Position { rank: 0, file: 0 });
+    assert_eq!(schema.get::<bool>(&entity, MOVED_META_KEY)?, Some(false));
+    assert_eq!(schema.get::<i64>(&entity, "frozen")?, Some(0));
+    assert_eq!(schema.get::<String>(&entity, "script")?, None);
+    assert!(schema.get::<i64>(&entity, MOVED_META_KEY).is_err());
+
+    entity.add_hook("explode");
+    let hooks = schema.get::<Vec<serde_json::Value>>(&entity, HOOKS_META_KEY)?;
+    assert_eq!(hooks, Some(vec![serde_json::Value::from("explode")]));
+    assert!(schema.get::<String>(&entity, HOOKS_META_KEY).is_err());
+
+    schema.set(&mut entity, "frozen", 3i64)?;
+    assert_eq!(schema.get::<i64>(&entity, "frozen")?, Some(3));
+    assert!(schema.set(&mut entity, "frozen", true).is_err());
+
+    schema.fill_defaults(&mut entity);
+    assert_eq!(
+        entity.get_meta(MOVED_META_KEY),
+        Some(&serde_json::Value::Bool(false))
+    );
+    Ok(())
+}
+
+#[test]
+fn template_validation() -> Result<(), ChessError> {
+    let template = r#"[
+        { "piece": { "pawn": "white" }, "pos": { "rank": 1, "file": 0 }, "data": { "moved": "no" } }
+    ]"#;
+
+    let mut board = board::Board::new();
+    assert!(matches!(
+        board.from_template(template.to_string()),
+        Err(ChessError::ParseError(_))
+    ));
+    assert_eq!(board.get(1, 0)?, None);
+
+    let template = r#"[
+        { "piece": { "pawn": "white" }, "pos": { "rank": 1, "file": 0 }, "data": { "spell": 1 } }
+    ]"#;
+    board.from_template(template.to_string())?;
+
+    let mut board = board::Board::new();
+    board.set_meta_schema(MetaSchema::standard().strict(true));
+    assert!(board.from_template(template.to_string()).is_err());
+    Ok(())
+}
+
+#[test]
+fn missing_moved_means_unmoved() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+
+    let pawn = PieceEntity::new(Piece::Pawn(Color::White), Position { rank: 1, file: 0 });
+    board.set(1, 0, Some(pawn.piece))?;
+    board.set_entity(1, 0, Some(pawn.clone()));
+
+    assert_eq!(
+        pawn.legal_moves(&board)?,
+        vec![Position { rank: 2, file: 0 }, Position { rank: 3, file: 0 }]
+    );
+    Ok(())
+}
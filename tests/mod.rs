@@ -1,12 +1,47 @@
 #[cfg(test)]
 mod bishop_tests;
 mod board_tests;
+mod clock_tests;
+mod encoding_tests;
+mod endgame_tests;
 mod entities_tests;
+mod eval_tests;
+mod game_tests;
+mod geometry_tests;
+mod hooks_tests;
 mod king_tests;
 mod knight_tests;
+mod mate_tests;
+mod meta_tests;
+mod notation_tests;
+mod ordering_tests;
 mod pawn_tests;
 mod piece_tests;
+mod polyglot_tests;
+mod puzzle_tests;
 mod queen_tests;
 mod rook_tests;
 mod rules_tests;
+#[cfg(feature = "scripting")]
+mod scripting_tests;
+mod search_tests;
+mod serde_tests;
+mod syzygy_tests;
+mod tactics_tests;
+mod template_tests;
+mod timeman_tests;
+mod tree_tests;
+mod tt_tests;
+mod uci_client_tests;
+mod uci_tests;
 mod utils_tests;
+mod xboard_tests;
+
+use super::board::Board;
+use super::utils::ChessError;
+
+fn board(fen: &str) -> Result<Board, ChessError> {
+    let mut board = Board::new();
+    board.from_fen(fen.to_string())?;
+    Ok(board)
+}
@@ -0,0 +1,56 @@
+use super::super::*;
+use super::board;
+use notation::{parse_san, to_san};
+use piece::{Color, Move, Piece};
+use utils::ChessError;
+
+fn san(board: &board::Board, uci: &str) -> Result<String, ChessError> {
+    let geometry = board.geometry();
+    let mut mv = Move::new(
+        geometry.parse_square(&uci[0..2])?,
+        geometry.parse_square(&uci[2..4])?,
+    );
+    if uci.len() > 4 {
+        mv.promotion = Some(Piece::from_fen(uci.chars().nth(4).unwrap()));
+    }
+    to_san(board, mv)
+}
+
+#[test]
+fn san_generation() -> Result<(), ChessError> {
+    let start = board("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")?;
+    assert_eq!(san(&start, "e2e4")?, "e4");
+    assert_eq!(san(&start, "g1f3")?, "Nf3");
+    assert!(san(&start, "e2e5").is_err());
+
+    let rooks = board("4k3/8/8/8/R6R/8/8/R3K3 w Q - 0 1")?;
+    assert_eq!(san(&rooks, "a1a3")?, "R1a3");
+    assert_eq!(san(&rooks, "a4d4")?, "Rad4");
+    assert_eq!(san(&rooks, "e1c1")?, "O-O-O");
+
+    let queens = board("4k3/8/8/8/Q2Q4/8/8/Q3K3 w - - 0 1")?;
+    assert_eq!(san(&queens, "a4d1")?, "Qa4d1");
+
+    let promotion = board("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1")?;
+    assert_eq!(san(&promotion, "e7d8Q")?, "exd8=Q+");
+    assert_eq!(san(&promotion, "e7e8N")?, "e8=N");
+
+    let mate = board("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1")?;
+    assert_eq!(san(&mate, "a1a8")?, "Ra8#");
+    Ok(())
+}
+
+#[test]
+fn san_parsing() -> Result<(), ChessError> {
+    let rooks = board("4k3/8/8/8/R6R/8/8/R3K3 w Q - 0 1")?;
+    let mv = parse_san(&rooks, "R1a3")?;
+    assert_eq!(rooks.geometry().square_name(mv.from), "a1");
+    assert_eq!(parse_san(&rooks, "0-0-0")?, parse_san(&rooks, "O-O-O")?);
+    assert!(parse_san(&rooks, "Ra3").is_err());
+    assert!(parse_san(&rooks, "Kd4").is_err());
+
+    let promotion = board("3r3k/4P3/8/8/8/8/8/4K3 w - - 0 1")?;
+    let mv = parse_san(&promotion, "exd8Q+!")?;
+    assert_eq!(mv.promotion, Some(Piece::Queen(Color::White)));
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use super::super::*;
+use super::board;
+use board::Board;
+use geometry::Geometry;
+use ordering::{capture_square, hanging_pieces, mvv_lva, see, MoveOrderer};
+use piece::{Color, Move, Piece};
+use rules::legal_moves;
+use utils::ChessError;
+
+fn uci(board: &Board, text: &str) -> Result<Move, ChessError> {
+    Move::from_uci(text, board.geometry(), board.get_turn())
+}
+
+#[test]
+fn ordering_mvv_lva() -> Result<(), ChessError> {
+    let white = Color::White;
+    assert!(
+        mvv_lva(Piece::Queen(white), Piece::King(white))
+            > mvv_lva(Piece::Rook(white), Piece::Pawn(white))
+    );
+    assert!(
+        mvv_lva(Piece::Rook(white), Piece::Pawn(white))
+            > mvv_lva(Piece::Rook(white), Piece::Queen(white))
+    );
+
+    // en passant takes the pawn beside the target square
+    let passant = board("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1")?;
+    let geometry = Geometry::STANDARD;
+    assert_eq!(
+        capture_square(&passant, uci(&passant, "e5d6")?)?,
+        Some(geometry.parse_square("d5")?)
+    );
+    assert_eq!(capture_square(&passant, uci(&passant, "e5e6")?)?, None);
+    Ok(())
+}
+
+#[test]
+fn ordering_static_exchange() -> Result<(), ChessError> {
+    let free = board("4k3/8/8/4p3/8/8/8/4R1K1 w - - 0 1")?;
+    assert_eq!(see(&free, uci(&free, "e1e5")?)?, 100);
+
+    let defended = board("4k3/8/3p4/4p3/8/8/8/4R1K1 w - - 0 1")?;
+    assert_eq!(see(&defended, uci(&defended, "e1e5")?)?, -400);
+
+    // the rook behind joins in once the first one has traded
+    let battery = board("4r1k1/8/8/4p3/8/8/4R3/4R1K1 w - - 0 1")?;
+    assert_eq!(see(&battery, uci(&battery, "e2e5")?)?, 100);
+
+    let quiet = board("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1")?;
+    assert_eq!(see(&quiet, uci(&quiet, "e1e5")?)?, 0);
+    assert!(see(&quiet, uci(&quiet, "e4e5")?).is_err());
+
+    let knight = board("4k3/8/2p5/3n4/4P3/8/8/4K3 w - - 0 1")?;
+    let geometry = Geometry::STANDARD;
+    assert_eq!(
+        hanging_pieces(&knight, Color::Black)?,
+        vec![geometry.parse_square("d5")?]
+    );
+    assert!(hanging_pieces(&knight, Color::White)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn ordering_heuristics() -> Result<(), ChessError> {
+    let mut position = board("4k3/8/3p4/4p3/8/6b1/5P2/4RK2 w - - 0 1")?;
+    let mut moves = legal_moves(&mut position, Color::White)?;
+    let mut orderer = MoveOrderer::new();
+
+    let killer = uci(&position, "f1e2")?;
+    orderer.record_cutoff(&position, killer, 3, 0);
+    assert_eq!(orderer.killers(0)[0], Some(killer));
+    assert_eq!(orderer.killers(1)[0], None);
+    assert_eq!(orderer.history(&position, killer), 9);
+
+    // hash move, winning capture, killer, ..., losing capture
+    let hash_move = uci(&position, "f1g1")?;
+    orderer.order(&position, &mut moves, Some(hash_move), 0)?;
+    assert_eq!(moves[0], hash_move);
+    assert_eq!(moves[1], uci(&position, "f2g3")?);
+    assert_eq!(moves[2], killer);
+    assert_eq!(moves.last(), Some(&uci(&position, "e1e5")?));
+
+    // killers are kept per ply and answer the opponent's last move
+    let mut game = board(game::START_FEN)?;
+    let reply = uci(&game, "e2e4")?;
+    game.make_move(reply.from, reply.to, None)?;
+    let counter = uci(&game, "c7c5")?;
+    orderer.record_cutoff(&game, counter, 2, 1);
+    assert_eq!(orderer.counter_move(&game), Some(counter));
+    assert_eq!(orderer.killers(0)[0], Some(killer));
+
+    orderer.new_search();
+    assert_eq!(orderer.killers(1)[0], None);
+    assert_eq!(orderer.history(&position, killer), 4);
+    Ok(())
+}
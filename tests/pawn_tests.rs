@@ -60,6 +60,23 @@ fn pawn_legal_moves() -> Result<(), ChessError> {
     Ok(())
 }
 
+#[test]
+fn pawn_double_step_from_start_rank_only() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    let pawn = piece::PieceEntity::new(
+        piece::Piece::Pawn(piece::Color::White),
+        piece::Position { rank: 3, file: 0 },
+    );
+    board.set_entity(3, 0, Some(pawn.clone()));
+
+    // no moved flag, but off the start rank
+    assert_eq!(
+        piece::pawn::legal_moves(&pawn, &board)?,
+        vec![piece::Position { rank: 4, file: 0 }]
+    );
+    Ok(())
+}
+
 #[test]
 fn pawn_on_move() -> Result<(), ChessError> {
     let mut board = board::Board::new();
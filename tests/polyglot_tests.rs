@@ -0,0 +1,188 @@
+use super::super::*;
+use super::board;
+use board::Board;
+use polyglot::{
+    decode_move, encode_move, Book, BookBuilder, BookEntry, PolyglotKeys, Selection, RANDOM_COUNT,
+};
+use utils::ChessError;
+
+fn uci(board: &Board, text: &str) -> Result<piece::Move, ChessError> {
+    piece::Move::from_uci(text, board.geometry(), board.get_turn())
+}
+
+const GAMES: &str = r#"[Event "one"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 1-0
+
+[Event "two"]
+[Result "0-1"]
+
+1. e4 c5 0-1
+
+[Event "three"]
+[Result "1/2-1/2"]
+
+1. d4 d5 1/2-1/2
+
+[Event "unfinished"]
+[Result "*"]
+
+1. c4 *
+"#;
+
+#[test]
+fn polyglot_moves_and_entries() -> Result<(), ChessError> {
+    let castling = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")?;
+    let short = uci(&castling, "e1g1")?;
+    let raw = encode_move(&castling, short)?;
+    assert_eq!(raw, 4 << 6 | 7);
+    assert_eq!(decode_move(&castling, raw)?, short);
+    let long = uci(&castling, "e1c1")?;
+    assert_eq!(decode_move(&castling, encode_move(&castling, long)?)?, long);
+
+    let promotion = board("8/4P3/8/8/8/8/k7/4K3 w - - 0 1")?;
+    let knight = uci(&promotion, "e7e8n")?;
+    assert_eq!(
+        decode_move(&promotion, encode_move(&promotion, knight)?)?,
+        knight
+    );
+
+    let entry = BookEntry {
+        key: 0x0123_4567_89ab_cdef,
+        raw_move: raw,
+        weight: 300,
+        learn: 7,
+    };
+    let bytes = entry.to_bytes();
+    assert_eq!(bytes[..2], [0x01, 0x23]);
+    assert_eq!(BookEntry::from_bytes(&bytes), entry);
+    Ok(())
+}
+
+#[test]
+fn polyglot_keys() -> Result<(), ChessError> {
+    let keys = PolyglotKeys::generate(7);
+    assert!(!keys.is_standard()?);
+
+    let listing: Vec<String> = (0..RANDOM_COUNT)
+        .map(|idx| format!("0x{:016X}ULL", idx * 31 + 1))
+        .collect();
+    let parsed = PolyglotKeys::parse(&format!("{{ {} }};", listing.join(",\n")))?;
+    assert_ne!(parsed, keys);
+    assert!(PolyglotKeys::parse("0x1, 0x2").is_err());
+
+    // same position, different move orders
+    let play = |moves: &[&str]| -> Result<Board, ChessError> {
+        let mut position = board(game::START_FEN)?;
+        for text in moves {
+            let mv = uci(&position, text)?;
+            position.make_move(mv.from, mv.to, mv.promotion)?;
+        }
+        Ok(position)
+    };
+    let one = play(&["g1f3", "g8f6", "b1c3"])?;
+    let other = play(&["b1c3", "g8f6", "g1f3"])?;
+    assert_eq!(keys.hash(&one)?, keys.hash(&other)?);
+    assert_ne!(keys.hash(&one)?, keys.hash(&play(&["g1f3"])?)?);
+
+    // an en passant square only counts when a pawn can take
+    let idle = board("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1")?;
+    let without = board("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1")?;
+    assert_eq!(keys.hash(&idle)?, keys.hash(&without)?);
+    let live = board("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1")?;
+    let dead = board("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1")?;
+    assert_ne!(keys.hash(&live)?, keys.hash(&dead)?);
+
+    let small = Board::with_geometry(geometry::Geometry::GARDNER);
+    assert!(keys.hash(&small).is_err());
+    Ok(())
+}
+
+#[test]
+fn polyglot_standard_keys() -> Result<(), ChessError> {
+    let keys = PolyglotKeys::default();
+    assert_eq!(keys, PolyglotKeys::standard());
+    assert!(keys.is_standard()?);
+
+    // reference keys from the Polyglot book format description
+    for (fen, key) in [
+        (game::START_FEN, polyglot::START_KEY),
+        (
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            0x823c_9b50_fd11_4196,
+        ),
+        (
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+            0x0756_b944_61c5_0fb0,
+        ),
+        (
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            0x22a4_8b5a_8e47_ff78,
+        ),
+        (
+            "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 2 4",
+            0x00fd_d303_c946_bdd9,
+        ),
+        (
+            "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+            0x3c81_23ea_7b06_7637,
+        ),
+        (
+            "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 1 4",
+            0x5c3f_9b82_9b27_9560,
+        ),
+    ] {
+        assert_eq!(keys.hash(&board(fen)?)?, key, "{}", fen);
+    }
+    Ok(())
+}
+
+#[test]
+fn polyglot_build_and_probe() -> Result<(), ChessError> {
+    let keys = PolyglotKeys::generate(1);
+    let start = board(game::START_FEN)?;
+
+    let mut builder = BookBuilder::new(keys.clone());
+    assert_eq!(builder.add_pgn(GAMES)?, 3);
+    let book = builder.build();
+    let moves = book.moves(&start)?;
+    let played: Vec<(String, u16)> = moves
+        .iter()
+        .map(|book_move| (book_move.mv.to_uci(start.geometry()), book_move.weight))
+        .collect();
+    assert_eq!(played, vec![("e2e4".into(), 2), ("d2d4".into(), 1)]);
+
+    // the written book is sorted by key and reads back the same
+    let bytes = book.to_bytes();
+    assert_eq!(bytes.len(), book.len() * polyglot::ENTRY_BYTES);
+    let read = Book::from_bytes(&bytes, keys.clone())?;
+    assert_eq!(read.entries(), book.entries());
+    assert!(read.entries().windows(2).all(|w| w[0].key <= w[1].key));
+    assert!(Book::from_bytes(&bytes[1..], keys.clone()).is_err());
+
+    let path = std::env::temp_dir().join("chess_polyglot_test.bin");
+    let path = path.to_str().unwrap();
+    book.save(path)?;
+    assert_eq!(Book::open(path, keys.clone())?.entries(), book.entries());
+    let _ = std::fs::remove_file(path);
+
+    let e4 = uci(&start, "e2e4")?;
+    assert_eq!(book.choose(&start, Selection::Best)?, Some(e4));
+    let weighted = book.choose(&start, Selection::Weighted(42))?;
+    assert_eq!(book.choose(&start, Selection::Weighted(42))?, weighted);
+    assert!(moves.iter().any(|book_move| Some(book_move.mv) == weighted));
+    assert!(book.choose(&start, Selection::Uniform(3))?.is_some());
+
+    let after = board("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2")?;
+    assert_eq!(book.choose(&after, Selection::Best)?, None);
+
+    builder.min_games = 2;
+    let frequent = builder.build();
+    assert_eq!(frequent.len(), 1);
+    assert_eq!(frequent.choose(&start, Selection::Best)?, Some(e4));
+
+    builder.min_score = 0.6;
+    assert!(builder.build().is_empty());
+    Ok(())
+}
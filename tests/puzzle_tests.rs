@@ -0,0 +1,64 @@
+use super::super::*;
+use puzzle::{Puzzle, PuzzleSession, PuzzleStatus, Verdict};
+use utils::ChessError;
+
+const CSV: &str = "\
+PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+00001,6k1/8/8/8/8/8/R7/1R4K1 b - - 0 1,g8h8 b1b7 h8g8 a2a8,1210,75,90,1000,mate mateIn2,,
+00002,6k1/5ppp/8/8/8/7r/5PPP/R3R1K1 b - - 0 1,h3h5 e1e8,640,80,95,50,\"backRankMate mate mateIn1\",,
+";
+
+fn uci(session: &PuzzleSession, text: &str) -> Result<piece::Move, ChessError> {
+    let board = session.board();
+    piece::Move::from_uci(text, board.geometry(), board.get_turn())
+}
+
+#[test]
+fn puzzle_csv() -> Result<(), ChessError> {
+    let puzzles = Puzzle::from_csv(CSV)?;
+    assert_eq!(puzzles.len(), 2);
+    assert_eq!(puzzles[0].id, "00001");
+    assert_eq!(puzzles[0].rating, 1210);
+    assert_eq!(puzzles[0].solution.len(), 3);
+    assert_eq!(puzzles[1].themes, vec!["backRankMate", "mate", "mateIn1"]);
+
+    // without a header the columns keep their usual order
+    let headless = CSV.lines().nth(2).unwrap();
+    assert_eq!(Puzzle::from_csv(headless)?, vec![puzzles[1].clone()]);
+
+    // the solver must move last, and every move must be legal
+    let fen = "6k1/8/8/8/8/8/R7/1R4K1 b - - 0 1";
+    assert!(Puzzle::new("x", fen, "g8h8 b1b7 h8g8").is_err());
+    assert!(Puzzle::new("x", fen, "g8h8 b1b9").is_err());
+    assert!(Puzzle::new("x", fen, "g8h8 a2b3").is_err());
+    Ok(())
+}
+
+#[test]
+fn puzzle_session() -> Result<(), ChessError> {
+    let puzzles = Puzzle::from_csv(CSV)?;
+
+    let mut session = PuzzleSession::new(puzzles[0].clone())?;
+    assert_eq!(session.status(), PuzzleStatus::Playing);
+    assert!(session.play_uci("b1c2").is_err());
+    let reply = uci(&session, "h8g8")?;
+    assert_eq!(session.play_uci("b1b7")?, Verdict::Correct { reply });
+    assert_eq!(session.play_uci("a2a8")?, Verdict::Solved);
+    assert_eq!(session.status(), PuzzleStatus::Solved);
+    assert!(session.play_uci("g1f1").is_err());
+
+    // another mate in two still fails before the final move
+    let mut session = PuzzleSession::new(puzzles[0].clone())?;
+    let expected = uci(&session, "b1b7")?;
+    assert_eq!(session.hint(), Some(expected));
+    assert_eq!(session.play_uci("a2a7")?, Verdict::Wrong { expected });
+    assert_eq!(session.status(), PuzzleStatus::Failed);
+    assert_eq!(session.hint(), None);
+
+    // any mate solves on the final move
+    let mut session = PuzzleSession::new(puzzles[1].clone())?;
+    assert_eq!(session.play_uci("a1a8")?, Verdict::Solved);
+    let mut session = PuzzleSession::new(puzzles[1].clone())?;
+    assert!(matches!(session.play_uci("a1a7")?, Verdict::Wrong { .. }));
+    Ok(())
+}
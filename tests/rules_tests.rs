@@ -132,3 +132,33 @@ fn is_stalemate() -> Result<(), ChessError> {
     assert!(rules::is_stalemate(&mut board, piece::Color::White)?);
     Ok(())
 }
+
+#[test]
+fn cant_castle_out_of_check() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("4r2k/8/8/8/8/8/8/4K2R w K - 0 1".into())?;
+
+    let moves = rules::legal_moves(&mut board, piece::Color::White)?;
+    let castle = piece::Move::new(Position { rank: 0, file: 4 }, Position { rank: 0, file: 6 });
+    assert!(!moves.contains(&castle));
+    assert!(moves.contains(&piece::Move::new(
+        Position { rank: 0, file: 4 },
+        Position { rank: 0, file: 5 },
+    )));
+    Ok(())
+}
+
+#[test]
+fn en_passant_exposing_king() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("7k/8/8/KPp4r/8/8/8/8 w - c6 0 2".into())?;
+
+    let moves = rules::legal_moves(&mut board, piece::Color::White)?;
+    let capture = piece::Move::new(Position { rank: 4, file: 1 }, Position { rank: 5, file: 2 });
+    assert!(!moves.contains(&capture));
+    assert!(moves.contains(&piece::Move::new(
+        Position { rank: 4, file: 1 },
+        Position { rank: 5, file: 1 },
+    )));
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use super::super::*;
+use clock::{Clock, ManualTimeSource, TimeControl};
+use piece::{Color, Piece, PieceEntity, Position};
+use scripting::{ScriptLimits, ScriptRegistry, SCRIPT_META_KEY};
+use std::sync::Arc;
+use std::time::Duration;
+use utils::ChessError;
+
+static CAMEL: &str = r#"
+    fn moves(board, piece) {
+        let out = [];
+        for d in [[3, 1], [3, -1], [-3, 1], [-3, -1], [1, 3], [1, -3], [-1, 3], [-1, -3]] {
+            let rank = piece.pos.rank + d[0];
+            let file = piece.pos.file + d[1];
+            if board.can_move_to(rank, file, "white") {
+                out.push(#{ rank: rank, file: file });
+            }
+        }
+        out
+    }
+
+    fn on_move(board, piece, to) {
+        let jumps = piece.data.jumps ?? 0;
+        #{ jumps: jumps + 1 }
+    }
+"#;
+
+fn camel_board(scripts: ScriptRegistry) -> Result<board::Board, ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1".into())?;
+    board.set_scripts(scripts);
+    board
+        .get_entity_mut(0, 1)
+        .unwrap()
+        .set_meta(SCRIPT_META_KEY, "camel");
+    Ok(board)
+}
+
+#[test]
+fn scripted_moves() -> Result<(), ChessError> {
+    let mut scripts = ScriptRegistry::new();
+    scripts.compile("camel", CAMEL)?;
+    let mut board = camel_board(scripts)?;
+
+    let camel = board.get_entity(0, 1).unwrap().clone();
+    let moves = camel.legal_moves(&board)?;
+    assert_eq!(
+        moves,
+        vec![
+            Position { rank: 3, file: 2 },
+            Position { rank: 3, file: 0 },
+            Position { rank: 1, file: 4 },
+        ]
+    );
+
+    board.make_move(
+        Position { rank: 0, file: 1 },
+        Position { rank: 3, file: 2 },
+        None,
+    )?;
+    let moved = board.get_entity(3, 2).unwrap();
+    assert_eq!(moved.piece, Piece::Knight(Color::White));
+    assert_eq!(moved.get_meta("jumps"), Some(&serde_json::Value::from(1)));
+    Ok(())
+}
+
+#[test]
+fn runaway_scripts_are_stopped() -> Result<(), ChessError> {
+    let mut scripts = ScriptRegistry::with_limits(ScriptLimits {
+        max_operations: 10_000,
+        max_time: Duration::from_secs(5),
+        ..ScriptLimits::default()
+    });
+    scripts.compile("camel", "fn moves(board, piece) { loop {} }")?;
+    let board = camel_board(scripts)?;
+
+    let camel = board.get_entity(0, 1).unwrap().clone();
+    assert!(matches!(
+        camel.legal_moves(&board),
+        Err(ChessError::InternalError(_))
+    ));
+
+    let mut scripts = ScriptRegistry::with_limits(ScriptLimits {
+        max_operations: 0,
+        max_time: Duration::from_millis(10),
+        ..ScriptLimits::default()
+    });
+    scripts.compile("camel", "fn moves(board, piece) { loop {} }")?;
+    let board = camel_board(scripts)?;
+    assert!(camel.legal_moves(&board).is_err());
+    Ok(())
+}
+
+#[test]
+fn script_compile_errors() {
+    let mut scripts = ScriptRegistry::new();
+    assert!(matches!(
+        scripts.compile("broken", "fn moves(board, piece) {"),
+        Err(ChessError::ParseError(_))
+    ));
+    assert!(!scripts.contains("broken"));
+    assert!(matches!(
+        scripts.compile("sneaky", "fn moves(board, piece) { eval(\"[]\") }"),
+        Err(ChessError::ParseError(_))
+    ));
+}
+
+#[test]
+fn missing_script() -> Result<(), ChessError> {
+    let board = camel_board(ScriptRegistry::new())?;
+    let camel = PieceEntity::new(Piece::Knight(Color::White), Position { rank: 0, file: 1 });
+    assert!(!camel.legal_moves(&board)?.is_empty());
+
+    let camel = board.get_entity(0, 1).unwrap().clone();
+    assert!(camel.legal_moves(&board).is_err());
+    Ok(())
+}
+
+#[test]
+fn failed_move_leaves_clock_running() -> Result<(), ChessError> {
+    let mut scripts = ScriptRegistry::new();
+    scripts.compile(
+        "camel",
+        &CAMEL.replace("#{ jumps: jumps + 1 }", "throw \"lame\""),
+    )?;
+    let mut board = camel_board(scripts)?;
+
+    let time = Arc::new(ManualTimeSource::new());
+    let control = TimeControl::fischer(Duration::from_secs(60), Duration::from_secs(2));
+    let mut clock = Clock::with_source(control, time.clone())?;
+    clock.start(Color::White);
+    board.set_clock(Some(clock));
+
+    time.advance(Duration::from_secs(5));
+    assert!(board
+        .make_move(
+            Position { rank: 0, file: 1 },
+            Position { rank: 3, file: 2 },
+            None,
+        )
+        .is_err());
+    let clock = board.clock().unwrap();
+    assert_eq!(clock.running(), Some(Color::White));
+    assert_eq!(clock.remaining(Color::White), Duration::from_secs(55));
+    Ok(())
+}
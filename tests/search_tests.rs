@@ -0,0 +1,127 @@
+use super::super::*;
+use search::{search, search_with, Score, SearchLimits};
+use std::sync::atomic::AtomicBool;
+use utils::ChessError;
+
+fn best(fen: &str, depth: u32) -> Result<search::SearchResult, ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen(fen.to_string())?;
+    let limits = SearchLimits {
+        depth: Some(depth),
+        ..SearchLimits::default()
+    };
+    search(&board, &limits, &AtomicBool::new(false), |_| {})
+}
+
+#[test]
+fn search_finds_mate_and_material() -> Result<(), ChessError> {
+    let mate = best("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", 3)?;
+    let geometry = geometry::Geometry::STANDARD;
+    assert_eq!(mate.best_move.unwrap().to_uci(geometry), "a1a8");
+    assert_eq!(mate.score, Score::Mate(1));
+
+    let hanging = best("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", 2)?;
+    assert_eq!(hanging.best_move.unwrap().to_uci(geometry), "d2d5");
+
+    let stalemate = best("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 2)?;
+    assert_eq!(stalemate.best_move, None);
+    Ok(())
+}
+
+#[test]
+fn search_respects_stop_flag() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen(game::START_FEN.to_string())?;
+
+    let result = search(
+        &board,
+        &SearchLimits::default(),
+        &AtomicBool::new(true),
+        |_| {},
+    )?;
+    assert!(result.best_move.is_some());
+    assert_eq!(result.depth, 0);
+    Ok(())
+}
+
+#[test]
+fn search_sees_through_exchanges() -> Result<(), ChessError> {
+    let geometry = geometry::Geometry::STANDARD;
+
+    // the d5 pawn is defended, quiescence has to notice the recapture
+    let poisoned = best("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", 1)?;
+    assert_ne!(poisoned.best_move.unwrap().to_uci(geometry), "d1d5");
+
+    let mate = best("6k1/5ppp/8/8/8/8/1r6/R5K1 w - - 0 1", 4)?;
+    assert_eq!(mate.best_move.unwrap().to_uci(geometry), "a1a8");
+    assert_eq!(mate.score, Score::Mate(2));
+    assert_eq!(mate.pv.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn search_uses_given_eval_and_limits() -> Result<(), ChessError> {
+    let geometry = geometry::Geometry::STANDARD;
+    let mut board = board::Board::new();
+    board.from_fen("4k3/8/8/1n3q2/8/3B4/8/4K3 w - - 0 1".to_string())?;
+    let limits = SearchLimits {
+        depth: Some(2),
+        ..SearchLimits::default()
+    };
+
+    let queen = search(&board, &limits, &AtomicBool::new(false), |_| {})?;
+    assert_eq!(queen.best_move.unwrap().to_uci(geometry), "d3f5");
+
+    let mut params = eval::EvalParams::default();
+    params.material[1] = eval::Tapered(2000, 2000);
+    let tt = tt::TranspositionTable::new(1);
+    let knight = search_with(
+        &board,
+        &limits,
+        &params,
+        &tt,
+        &AtomicBool::new(false),
+        |_| {},
+    )?;
+    assert_eq!(knight.best_move.unwrap().to_uci(geometry), "d3b5");
+
+    let capped = SearchLimits {
+        nodes: Some(200),
+        ..SearchLimits::default()
+    };
+    let result = search(&board, &capped, &AtomicBool::new(false), |_| {})?;
+    assert!(result.nodes <= 200);
+    assert!(result.best_move.is_some());
+    Ok(())
+}
+
+#[test]
+fn search_with_helper_threads() -> Result<(), ChessError> {
+    let geometry = geometry::Geometry::STANDARD;
+    let mut board = board::Board::new();
+    board.from_fen("6k1/5ppp/8/8/8/8/1r6/R5K1 w - - 0 1".to_string())?;
+    let limits = SearchLimits {
+        depth: Some(4),
+        threads: 3,
+        ..SearchLimits::default()
+    };
+
+    let mut depths = Vec::new();
+    let result = search(&board, &limits, &AtomicBool::new(false), |info| {
+        depths.push(info.depth)
+    })?;
+    assert_eq!(result.best_move.unwrap().to_uci(geometry), "a1a8");
+    assert_eq!(result.score, Score::Mate(2));
+    assert_eq!(depths, (1..=result.depth).collect::<Vec<_>>());
+
+    // a single thread searches the same tree every time
+    let single = SearchLimits {
+        depth: Some(3),
+        ..SearchLimits::default()
+    };
+    let first = search(&board, &single, &AtomicBool::new(false), |_| {})?;
+    let second = search(&board, &single, &AtomicBool::new(false), |_| {})?;
+    assert_eq!(first.nodes, second.nodes);
+    assert_eq!(first.pv, second.pv);
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use super::super::*;
+use piece::Position;
+use utils::ChessError;
+
+fn played_board() -> Result<board::Board, ChessError> {
+    let mut board = board::Board::new();
+    board.from_template(utils::file_to_str("chess/templates/default.json")?)?;
+    board.make_move(
+        Position { rank: 1, file: 3 },
+        Position { rank: 3, file: 3 },
+        None,
+    )?;
+    board.make_move(
+        Position { rank: 6, file: 4 },
+        Position { rank: 4, file: 4 },
+        None,
+    )?;
+    board
+        .get_entity_mut(3, 3)
+        .unwrap()
+        .set_meta("buffs", vec!["haste", "ward"]);
+    Ok(board)
+}
+
+#[test]
+fn board_json() -> Result<(), ChessError> {
+    let board = played_board()?;
+
+    let json = serde_json::to_value(&board).unwrap();
+    assert_eq!(json["version"], 2);
+    assert_eq!(json["turn"], "white");
+    assert_eq!(json["history"].as_array().unwrap().len(), 2);
+
+    let restored: board::Board = serde_json::from_value(json).unwrap();
+    assert_eq!(restored.generate_fen()?, board.generate_fen()?);
+    assert_eq!(restored.template(), board.template());
+    Ok(())
+}
+
+#[test]
+fn board_json_versions() -> Result<(), ChessError> {
+    let v1 = utils::file_to_str("chess/templates/default.json")?;
+    let board: board::Board = serde_json::from_str(&v1).unwrap();
+    assert_eq!(
+        board.generate_fen()?,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    );
+
+    let mut json = serde_json::to_value(&board).unwrap();
+    json["version"] = 99.into();
+    assert!(serde_json::from_value::<board::Board>(json).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "binary")]
+#[test]
+fn board_binary() -> Result<(), ChessError> {
+    let board = played_board()?;
+
+    let bytes = board.to_bytes()?;
+    assert_eq!(&bytes[..4], b"CHSB");
+
+    let restored = board::Board::from_bytes(&bytes)?;
+    assert_eq!(restored.template(), board.template());
+
+    let mut v1 = bytes[..bytes.len() - 1].to_vec();
+    v1[4] = 1;
+    assert_eq!(board::Board::from_bytes(&v1)?.template(), board.template());
+
+    let mut future = bytes.clone();
+    future[4] = 3;
+    assert!(board::Board::from_bytes(&future).is_err());
+    assert!(board::Board::from_bytes(b"FEN?").is_err());
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use super::super::*;
+use super::board;
+use board::Board;
+use piece::{Color, Piece};
+use std::path::Path;
+use syzygy::{
+    filter_root_moves, material_key, rank_root_moves, SyzygyTablebase, Tablebase, Wdl, DTZ_MAGIC,
+    WDL_MAGIC,
+};
+use utils::ChessError;
+
+fn fixtures() -> Result<SyzygyTablebase, ChessError> {
+    let dir = Path::new(file!()).with_file_name("fixtures/syzygy");
+    SyzygyTablebase::open(dir.to_str().unwrap())
+}
+
+/// Every position with a white rook is lost for black, in ten plies per file
+/// the rook stands on.
+struct RookFiles;
+
+impl Tablebase for RookFiles {
+    fn max_pieces(&self) -> usize {
+        4
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Result<Option<Wdl>, ChessError> {
+        Ok(Some(match board.bitboard(Piece::Rook(Color::White)) {
+            0 => Wdl::Draw,
+            _ => Wdl::Loss,
+        }))
+    }
+
+    fn probe_dtz(&self, board: &Board) -> Result<Option<i32>, ChessError> {
+        let rook = board.bitboard(Piece::Rook(Color::White));
+        Ok(Some(match rook {
+            0 => 0,
+            _ => -10 * (rook.trailing_zeros() as i32 % 8 + 1),
+        }))
+    }
+}
+
+#[test]
+fn syzygy_root_moves() -> Result<(), ChessError> {
+    let geometry = geometry::Geometry::STANDARD;
+    let position = board("7k/8/8/p7/8/8/8/R3K3 w - - 0 1")?;
+    assert_eq!(material_key(&position), "KRvKP");
+
+    // taking the pawn zeroes the counter at once
+    let ranked = rank_root_moves(&RookFiles, &position)?.unwrap();
+    assert_eq!(ranked[0].mv.to_uci(geometry), "a1a5");
+    assert_eq!(ranked[0].dtz, 1);
+    let kept = filter_root_moves(&RookFiles, &position)?.unwrap();
+    assert_eq!(kept.len(), ranked.len());
+
+    // late in the count only wins that finish before move fifty still win
+    let late = board("7k/8/8/8/8/8/8/R3K3 w - - 80 60")?;
+    let kept = filter_root_moves(&RookFiles, &late)?.unwrap();
+    assert!(!kept.is_empty());
+    let ranked = rank_root_moves(&RookFiles, &late)?.unwrap();
+    assert!(ranked.len() > kept.len());
+    for root in ranked {
+        // only moves keeping the rook on the a-file win in time
+        let rook_leaves = root.mv.from.file == 0 && root.mv.to.file != 0;
+        assert_eq!(kept.contains(&root.mv), !rook_leaves);
+    }
+
+    let crowded = board("4k3/pppp4/8/8/8/8/8/R3K3 w - - 0 1")?;
+    assert_eq!(rank_root_moves(&RookFiles, &crowded)?, None);
+    assert_eq!(Wdl::CursedWin.negate(), Wdl::BlessedLoss);
+    Ok(())
+}
+
+#[test]
+fn syzygy_directory() -> Result<(), ChessError> {
+    let dir = std::env::temp_dir().join("chess_syzygy_tables");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let write = |name: &str, header: [u8; 4]| {
+        std::fs::write(dir.join(name), [&header[..], &[0; 12]].concat()).unwrap()
+    };
+    write("KQvK.rtbw", WDL_MAGIC);
+    write("KQvK.rtbz", DTZ_MAGIC);
+    write("KRvKP.rtbw", WDL_MAGIC);
+    std::fs::write(dir.join("README.txt"), "not a table").unwrap();
+
+    let tablebase = SyzygyTablebase::open(dir.to_str().unwrap())?;
+    assert_eq!(tablebase.tables(), vec!["KQvK", "KRvKP"]);
+    assert_eq!(tablebase.max_pieces(), 4);
+
+    // tables without a file are not covered, the reversed colours are
+    let uncovered = board("4k3/8/8/8/8/8/8/R3K3 w - - 0 1")?;
+    assert_eq!(tablebase.probe_wdl(&uncovered)?, None);
+    let reversed = board("4k3/8/8/8/8/8/8/q3K3 w - - 0 1")?;
+    assert!(tablebase.probe_wdl(&reversed).is_err());
+
+    // a WDL table alone gives results but no distances
+    let fixture = Path::new(file!()).with_file_name("fixtures/syzygy/KQvK.rtbw");
+    std::fs::copy(fixture, dir.join("KQvK.rtbw")).unwrap();
+    std::fs::remove_file(dir.join("KQvK.rtbz")).unwrap();
+    let tablebase = SyzygyTablebase::open(dir.to_str().unwrap())?;
+    assert_eq!(tablebase.probe_wdl(&reversed)?, Some(Wdl::Loss));
+    assert_eq!(tablebase.probe_dtz(&reversed)?, None);
+
+    write("KRvK.rtbz", WDL_MAGIC);
+    assert!(SyzygyTablebase::open(dir.to_str().unwrap()).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+    Ok(())
+}
+
+#[test]
+fn syzygy_probe_fixtures() -> Result<(), ChessError> {
+    let tablebase = fixtures()?;
+    assert_eq!(tablebase.max_pieces(), 3);
+
+    for (fen, wdl, dtz) in [
+        ("8/8/k7/8/8/K7/2Q5/8 w - - 0 1", Wdl::Win, 11),
+        ("8/8/1k6/5K2/6Q1/8/8/8 b - - 0 1", Wdl::Loss, -12),
+        ("3K4/6k1/7Q/8/8/8/8/8 b - - 0 1", Wdl::Draw, 0),
+        ("8/8/8/5K2/3q4/8/7k/8 b - - 0 1", Wdl::Win, 11),
+        ("8/8/8/7K/5k2/8/8/q7 w - - 0 1", Wdl::Loss, -10),
+        ("8/8/8/2R5/1K6/8/5k2/8 w - - 0 1", Wdl::Win, 21),
+        ("8/8/8/4k3/8/8/6R1/K7 w - - 0 1", Wdl::Win, 31),
+        ("5r2/3K4/8/8/8/8/8/1k6 w - - 0 1", Wdl::Loss, -30),
+        ("8/K7/8/5k2/8/3P4/8/8 w - - 0 1", Wdl::Win, 7),
+        ("6k1/8/8/8/1P6/8/3K4/8 b - - 0 1", Wdl::Loss, -10),
+        ("8/5p2/6k1/K7/8/8/8/8 w - - 0 1", Wdl::Loss, -2),
+        ("8/8/8/2K5/5kp1/8/8/8 b - - 0 1", Wdl::Win, 1),
+        ("8/3k4/8/8/8/8/4P3/3K4 w - - 0 1", Wdl::Draw, 0),
+        ("6k1/8/8/8/8/4n3/8/K7 b - - 0 1", Wdl::Draw, 0),
+    ] {
+        let position = board(fen)?;
+        assert_eq!(tablebase.probe_wdl(&position)?, Some(wdl), "{}", fen);
+        assert_eq!(tablebase.probe_dtz(&position)?, Some(dtz), "{}", fen);
+    }
+
+    let bare_kings = board("8/8/3k4/8/8/3K4/8/8 w - - 0 1")?;
+    assert_eq!(tablebase.probe_wdl(&bare_kings)?, Some(Wdl::Draw));
+    let missing = board("8/8/3k4/8/8/3K4/8/q2R4 w - - 0 1")?;
+    assert_eq!(tablebase.probe_wdl(&missing)?, None);
+    let castling = board("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1")?;
+    assert_eq!(tablebase.probe_dtz(&castling)?, None);
+    Ok(())
+}
+
+#[test]
+fn syzygy_root_moves_from_tables() -> Result<(), ChessError> {
+    let tablebase = fixtures()?;
+    let geometry = geometry::Geometry::STANDARD;
+
+    // the best move keeps the distance the root promises
+    let position = board("8/8/8/4k3/8/8/6R1/K7 w - - 0 1")?;
+    let ranked = rank_root_moves(&tablebase, &position)?.unwrap();
+    assert_eq!(ranked[0].dtz, tablebase.probe_dtz(&position)?.unwrap());
+    for mv in filter_root_moves(&tablebase, &position)?.unwrap() {
+        let mut child = position.clone();
+        child.make_move(mv.from, mv.to, mv.promotion)?;
+        assert_eq!(tablebase.probe_wdl(&child)?, Some(Wdl::Loss));
+    }
+
+    // the pawn must run, the king cannot stop it
+    let race = board("8/8/8/8/8/8/5kP1/K7 w - - 0 1")?;
+    let kept = filter_root_moves(&tablebase, &race)?.unwrap();
+    assert_eq!(
+        kept.iter()
+            .map(|mv| mv.to_uci(geometry))
+            .collect::<Vec<_>>(),
+        vec!["g2g4"]
+    );
+    Ok(())
+}
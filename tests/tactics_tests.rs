@@ -0,0 +1,121 @@
+use super::super::*;
+use super::board;
+use board::Board;
+use piece::{Color, Move, Piece, Position};
+use tactics::{
+    back_rank, discovered_attacks, double_check, forks, hanging, motifs, overloaded, pins, skewers,
+    Motif,
+};
+use utils::ChessError;
+
+fn uci(board: &Board, text: &str) -> Result<Move, ChessError> {
+    Move::from_uci(text, board.geometry(), board.get_turn())
+}
+
+fn sq(name: &str) -> Position {
+    geometry::Geometry::STANDARD.parse_square(name).unwrap()
+}
+
+#[test]
+fn tactics_attacks() -> Result<(), ChessError> {
+    let fork = board("r3k3/8/8/1N6/8/8/8/4K3 w - - 0 1")?;
+    assert_eq!(
+        forks(&fork, uci(&fork, "b5c7")?)?,
+        vec![Motif::Fork {
+            attacker: sq("c7"),
+            targets: vec![sq("e8"), sq("a8")],
+        }]
+    );
+    assert!(forks(&fork, uci(&fork, "b5d6")?)?.is_empty());
+
+    let pin = board("4k3/8/2n5/8/8/8/8/3BK3 w - - 0 1")?;
+    assert_eq!(
+        pins(&pin, uci(&pin, "d1a4")?)?,
+        vec![Motif::Pin {
+            attacker: sq("a4"),
+            pinned: sq("c6"),
+            behind: sq("e8"),
+            absolute: true,
+        }]
+    );
+
+    let skewer = board("4q3/8/8/4k3/8/8/8/R5K1 w - - 0 1")?;
+    let mv = uci(&skewer, "a1e1")?;
+    assert_eq!(
+        skewers(&skewer, mv)?,
+        vec![Motif::Skewer {
+            attacker: sq("e1"),
+            front: sq("e5"),
+            behind: sq("e8"),
+        }]
+    );
+    assert!(pins(&skewer, mv)?.is_empty());
+
+    let double = board("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1")?;
+    let mv = uci(&double, "e4f6")?;
+    assert_eq!(
+        double_check(&double, mv)?,
+        vec![Motif::DoubleCheck {
+            checkers: vec![sq("e1"), sq("f6")],
+        }]
+    );
+    assert_eq!(
+        discovered_attacks(&double, mv)?,
+        vec![Motif::DiscoveredAttack {
+            attacker: sq("e1"),
+            target: sq("e8"),
+            moved: sq("f6"),
+        }]
+    );
+    let themes: Vec<&str> = motifs(&double, mv)?.iter().map(Motif::theme).collect();
+    assert_eq!(themes, vec!["discoveredAttack", "doubleCheck"]);
+    Ok(())
+}
+
+#[test]
+fn tactics_weaknesses() -> Result<(), ChessError> {
+    let back = board("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")?;
+    assert_eq!(
+        back_rank(&back, uci(&back, "a1a8")?)?,
+        vec![Motif::BackRank {
+            king: sq("g8"),
+            attacker: sq("a8"),
+        }]
+    );
+    let airy = board("6k1/5pp1/7p/8/8/8/8/R5K1 w - - 0 1")?;
+    assert!(back_rank(&airy, uci(&airy, "a1a8")?)?.is_empty());
+    let covered = board("6k1/5ppp/1n6/8/8/8/8/R5K1 w - - 0 1")?;
+    assert!(back_rank(&covered, uci(&covered, "a1a8")?)?.is_empty());
+
+    // taking a free knight, and putting the queen where a pawn takes it
+    let free = board("4k3/8/8/3n4/8/8/8/3RK3 w - - 0 1")?;
+    assert_eq!(
+        hanging(&free, uci(&free, "d1d5")?)?,
+        vec![Motif::Hanging {
+            square: sq("d5"),
+            piece: Piece::Knight(Color::Black),
+        }]
+    );
+    let blunder = board("4k3/8/8/3p4/8/8/8/2Q1K3 w - - 0 1")?;
+    assert_eq!(
+        hanging(&blunder, uci(&blunder, "c1c4")?)?,
+        vec![Motif::Hanging {
+            square: sq("c4"),
+            piece: Piece::Queen(Color::White),
+        }]
+    );
+    assert!(hanging(&blunder, uci(&blunder, "c1c2")?)?.is_empty());
+
+    // the rook alone guards both the knight and the bishop
+    let overload = board("k2r1b2/8/8/3n4/8/8/8/2BRK3 w - - 0 1")?;
+    assert_eq!(
+        overloaded(&overload, uci(&overload, "c1h6")?)?,
+        vec![Motif::Overloaded {
+            defender: sq("d8"),
+            duties: vec![sq("d5"), sq("f8")],
+        }]
+    );
+    assert!(overloaded(&overload, uci(&overload, "e1f2")?)?.is_empty());
+    assert!(motifs(&overload, uci(&overload, "e4e5")?).is_err());
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use super::super::*;
+use geometry::Geometry;
+use piece::{Castle, Color, Piece, Position};
+use template::{BoardTemplate, TEMPLATE_VERSION};
+use utils::ChessError;
+
+#[test]
+fn migrate_v1_template() -> Result<(), ChessError> {
+    let v1 = utils::file_to_str("chess/templates/default.json")?;
+    let template = BoardTemplate::parse(&v1)?;
+
+    assert_eq!(template.version, TEMPLATE_VERSION);
+    assert_eq!(template.geometry, Geometry::STANDARD);
+    assert_eq!(template.entities.len(), 32);
+    assert_eq!(
+        template.castling,
+        Castle {
+            white_king: true,
+            white_queen: true,
+            black_king: true,
+            black_queen: true,
+        }
+    );
+
+    let mut board = board::Board::new();
+    board.from_template(v1)?;
+    assert_eq!(
+        board.generate_fen()?,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    );
+    Ok(())
+}
+
+#[test]
+fn template_round_trip() -> Result<(), ChessError> {
+    let mut board = board::Board::new();
+    board.from_template(utils::file_to_str("chess/templates/default.json")?)?;
+    board.make_move(
+        Position { rank: 1, file: 4 },
+        Position { rank: 3, file: 4 },
+        None,
+    )?;
+    board.make_move(
+        Position { rank: 7, file: 6 },
+        Position { rank: 5, file: 5 },
+        None,
+    )?;
+    board
+        .get_entity_mut(3, 4)
+        .unwrap()
+        .set_meta("spell", "haste");
+
+    let saved = board.to_template()?;
+
+    let mut restored = board::Board::new();
+    restored.from_template(saved)?;
+
+    assert_eq!(restored.generate_fen()?, board.generate_fen()?);
+    assert_eq!(restored.get_history(), board.get_history());
+    assert_eq!(restored.get_turn(), Color::White);
+    assert_eq!(
+        restored.get_entity(3, 4).unwrap().get_meta("spell"),
+        Some(&serde_json::Value::from("haste"))
+    );
+    assert_eq!(restored.template(), board.template());
+    Ok(())
+}
+
+#[test]
+fn template_replaces_board() -> Result<(), ChessError> {
+    let mut board = board::Board::with_geometry(Geometry::GARDNER);
+    board.set(4, 4, Some(Piece::Queen(Color::Black)))?;
+
+    board.from_template(utils::file_to_str("chess/templates/default.json")?)?;
+    assert_eq!(board.geometry(), Geometry::STANDARD);
+    assert_eq!(board.get(4, 4)?, None);
+    Ok(())
+}
+
+#[test]
+fn template_versions() -> Result<(), ChessError> {
+    let board = board::Board::with_geometry(Geometry::LOS_ALAMOS);
+    let mut template = board.template();
+    template.version = TEMPLATE_VERSION + 1;
+
+    let mut loaded = board::Board::new();
+    assert!(matches!(
+        loaded.from_template(template.to_json()?),
+        Err(ChessError::ParseError(_))
+    ));
+
+    template.version = TEMPLATE_VERSION;
+    template.entities.push(piece::PieceEntity::new(
+        Piece::King(Color::White),
+        Position { rank: 6, file: 0 },
+    ));
+    assert!(loaded.from_template(template.to_json()?).is_err());
+
+    template.entities.clear();
+    loaded.from_template(template.to_json()?)?;
+    assert_eq!(loaded.geometry(), Geometry::LOS_ALAMOS);
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use super::super::*;
+use board::Board;
+use search::{search, SearchLimits};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use timeman::{SearchClock, TimeManager};
+use utils::ChessError;
+
+fn clock(remaining: u64, increment: u64, moves_to_go: Option<u32>) -> SearchClock {
+    SearchClock {
+        remaining: Duration::from_millis(remaining),
+        increment: Duration::from_millis(increment),
+        moves_to_go,
+    }
+}
+
+#[test]
+fn timeman_allocation() -> Result<(), ChessError> {
+    let (soft, hard) = clock(60_000, 1_000, Some(20)).allocate();
+    assert_eq!(soft, Duration::from_millis(3_500));
+    assert_eq!(hard, Duration::from_millis(14_000));
+
+    // the last move before the time control may use everything but the overhead
+    let (soft, hard) = clock(5_000, 0, Some(1)).allocate();
+    assert_eq!(soft, Duration::from_millis(4_950));
+    assert_eq!(hard, soft);
+
+    let (soft, hard) = clock(40, 0, None).allocate();
+    assert_eq!((soft, hard), (Duration::ZERO, Duration::ZERO));
+    Ok(())
+}
+
+#[test]
+fn timeman_extensions() -> Result<(), ChessError> {
+    let mut board = Board::new();
+    board.from_fen(game::START_FEN.to_string())?;
+    let geometry = board.geometry();
+    let e4 = piece::Move::from_uci("e2e4", geometry, piece::Color::White)?;
+    let d4 = piece::Move::from_uci("d2d4", geometry, piece::Color::White)?;
+
+    let mut time = TimeManager::new(clock(30_000, 0, None), false, None);
+    time.update(Some(e4), 20);
+    time.update(Some(e4), 20);
+    assert_eq!(time.soft_limit(), Duration::from_millis(1_000));
+
+    // a new best move and a falling score both buy time, up to the hard limit
+    time.update(Some(d4), 20);
+    assert_eq!(time.soft_limit(), Duration::from_millis(1_500));
+    time.update(Some(d4), -80);
+    assert_eq!(time.soft_limit(), Duration::from_millis(2_500));
+    time.update(Some(e4), -400);
+    assert_eq!(time.soft_limit(), Duration::from_millis(3_250));
+    assert!(!time.stop_iterating());
+
+    let mut last = TimeManager::new(clock(5_000, 0, Some(1)), false, None);
+    last.update(Some(e4), 20);
+    last.update(Some(d4), -200);
+    assert_eq!(last.soft_limit(), last.hard_limit());
+
+    let mut forced = TimeManager::new(clock(30_000, 0, None), true, None);
+    assert!(forced.stop_iterating());
+    Ok(())
+}
+
+#[test]
+fn timeman_pondering() -> Result<(), ChessError> {
+    let stop = AtomicBool::new(false);
+    let pondering = Arc::new(AtomicBool::new(true));
+    let mut time = TimeManager::new(clock(0, 0, None), false, Some(pondering.clone()));
+    assert!(time.is_pondering());
+    assert!(!time.out_of_time(&stop));
+    assert!(!time.stop_iterating());
+
+    pondering.store(false, Ordering::Relaxed);
+    assert!(time.out_of_time(&stop));
+    assert!(stop.load(Ordering::Relaxed));
+    Ok(())
+}
+
+#[test]
+fn timeman_search_under_clock() -> Result<(), ChessError> {
+    // Kxg2 is the only move, one iteration settles it
+    let mut board = Board::new();
+    board.from_fen("7k/8/8/8/8/8/6q1/7K w - - 0 1".to_string())?;
+    let limits = SearchLimits {
+        clock: Some(clock(60_000, 0, None)),
+        ..SearchLimits::default()
+    };
+    let result = search(&board, &limits, &AtomicBool::new(false), |_| {})?;
+    assert_eq!(result.depth, 1);
+    assert_eq!(result.best_move.unwrap().to_uci(board.geometry()), "h1g2");
+
+    let mut board = Board::new();
+    board.from_fen(game::START_FEN.to_string())?;
+    let limits = SearchLimits {
+        clock: Some(clock(1_000, 0, None)),
+        ..SearchLimits::default()
+    };
+    let start = Instant::now();
+    let result = search(&board, &limits, &AtomicBool::new(false), |_| {})?;
+    assert!(result.best_move.is_some());
+    assert!(start.elapsed() < Duration::from_millis(1_000));
+    Ok(())
+}
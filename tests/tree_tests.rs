@@ -0,0 +1,118 @@
+use super::super::*;
+use game::START_FEN;
+use tree::{Eval, GameTree, MarkColor, NAG_BLUNDER, NAG_GOOD, ROOT};
+use utils::ChessError;
+
+fn start_tree() -> Result<GameTree, ChessError> {
+    let mut board = board::Board::new();
+    board.from_fen(START_FEN.to_string())?;
+    Ok(GameTree::new(board))
+}
+
+#[test]
+fn tree_variations_and_navigation() -> Result<(), ChessError> {
+    let mut tree = start_tree()?;
+    let e4 = tree.play_san("e4")?;
+    let e5 = tree.play_san("e5")?;
+    tree.back()?;
+    let c5 = tree.play_san("c5")?;
+    assert_eq!(tree.node(e4)?.children, vec![e5, c5]);
+    assert_eq!(tree.mainline(), vec![e4, e5]);
+    assert_eq!(
+        tree.board().generate_fen()?,
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+    );
+
+    tree.goto(ROOT)?;
+    assert!(tree.forward()? && tree.forward()?);
+    assert_eq!(tree.current(), e5);
+    assert!(!tree.forward()?);
+
+    tree.back()?;
+    assert_eq!(tree.play_san("c5")?, c5);
+    assert_eq!(tree.node(e4)?.children.len(), 2);
+
+    tree.promote_variation(c5)?;
+    assert_eq!(tree.mainline(), vec![e4, c5]);
+
+    let nf3 = tree.play_san("Nf3")?;
+    tree.goto(e5)?;
+    tree.delete_from(c5)?;
+    assert_eq!(tree.mainline(), vec![e4, e5]);
+    assert!(tree.node(nf3).is_err());
+    assert!(tree.delete_from(ROOT).is_err());
+    Ok(())
+}
+
+#[test]
+fn tree_pgn_round_trip() -> Result<(), ChessError> {
+    let pgn = "[Event \"Study\"]\n[Result \"*\"]\n\n\
+        {Open game} 1. e4 {[%clk 0:05:00] [%eval 0.30] [%cal Ge2e4,Rd7d5] [%csl Yd4] best by test} \
+        e5! (1... c5 2. Nf3 (2. c3?! d5) 2... d6) 2. Nf3 $4 ; a line comment\n\
+        Nc6 (2... Qh4?? 3. Nxh4) 3. Bb5 *";
+
+    let tree = GameTree::from_pgn(pgn)?;
+    assert_eq!(tree.header("Event"), Some("Study"));
+    assert_eq!(tree.node(ROOT)?.comment.as_deref(), Some("Open game"));
+
+    let mainline = tree.mainline();
+    assert_eq!(mainline.len(), 5);
+    let e4 = tree.node(mainline[0])?;
+    assert_eq!(e4.comment.as_deref(), Some("best by test"));
+    assert_eq!(e4.eval, Some(Eval::Centipawns(30)));
+    assert_eq!(e4.clock.map(|c| c.as_secs()), Some(300));
+    assert_eq!(e4.arrows.len(), 2);
+    assert_eq!(e4.arrows[1].color, MarkColor::Red);
+    assert_eq!(e4.highlights[0].color, MarkColor::Yellow);
+    assert_eq!(tree.node(mainline[1])?.nags, vec![NAG_GOOD]);
+    assert_eq!(tree.node(mainline[2])?.nags, vec![NAG_BLUNDER]);
+    assert_eq!(tree.node(mainline[0])?.children.len(), 2);
+
+    let written = tree.to_pgn()?;
+    let flat = written.split_whitespace().collect::<Vec<_>>().join(" ");
+    assert!(written.lines().all(|line| line.len() < 80));
+    assert!(flat.contains(
+        "{Open game} 1. e4 {[%clk 0:05:00] [%eval 0.30] [%cal Ge2e4,Rd7d5] [%csl Yd4] best by test} 1... e5!"
+    ));
+    assert!(flat.contains("(1... c5 2. Nf3 (2. c3?! d5) 2... d6)"));
+    assert!(flat.ends_with("2. Nf3?? Nc6 (2... Qh4?? 3. Nxh4) 3. Bb5 *"));
+
+    let reparsed = GameTree::from_pgn(&written)?;
+    assert_eq!(reparsed.to_pgn()?, written);
+    Ok(())
+}
+
+#[test]
+fn tree_malformed_annotations() -> Result<(), ChessError> {
+    for comment in [
+        "[%csl G]",
+        "[%csl Gé4]",
+        "[%cal R]",
+        "[%clk inf]",
+        "[%clk NaN]",
+        "[%clk -0:01:00]",
+        "[%clk 1e300]",
+    ] {
+        let pgn = format!("1. e4 {{{}}} *", comment);
+        assert!(
+            matches!(GameTree::from_pgn(&pgn), Err(ChessError::ParseError(_))),
+            "{}",
+            comment
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn tree_from_game() -> Result<(), ChessError> {
+    let mut game = game::Game::new()?;
+    for san in ["f3", "e5", "g4", "Qh4"] {
+        game.play_san(san)?;
+    }
+
+    let tree = GameTree::from_game(&game)?;
+    assert_eq!(tree.mainline().len(), 4);
+    assert_eq!(tree.header("Result"), Some("0-1"));
+    assert!(tree.to_pgn()?.ends_with("1. f3 e5 2. g4 Qh4# 0-1\n"));
+    Ok(())
+}
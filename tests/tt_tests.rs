@@ -0,0 +1,142 @@
+use super::super::*;
+use board::Board;
+use geometry::Geometry;
+use notation::parse_san;
+use piece::{Color, Move, Piece, Position};
+use search::{search_with, SearchLimits, MATE};
+use std::sync::atomic::AtomicBool;
+use tt::{score_from_tt, score_to_tt, Bound, TranspositionTable, TtEntry};
+use utils::ChessError;
+
+fn play(sans: &[&str]) -> Result<Board, ChessError> {
+    let mut board = Board::new();
+    board.from_fen(game::START_FEN.to_string())?;
+    for san in sans {
+        let mv = parse_san(&board, san)?;
+        board.make_move(mv.from, mv.to, mv.promotion)?;
+    }
+    Ok(board)
+}
+
+fn entry(depth: u32, score: i32) -> TtEntry {
+    TtEntry {
+        depth,
+        bound: Bound::Exact,
+        score,
+        best_move: None,
+        age: 0,
+    }
+}
+
+#[test]
+fn zobrist_transpositions() -> Result<(), ChessError> {
+    let one = play(&["Nf3", "Nf6", "Nc3"])?;
+    let other = play(&["Nc3", "Nf6", "Nf3"])?;
+    assert_eq!(one.zobrist_hash(), other.zobrist_hash());
+
+    let mut turn = one.clone();
+    turn.set_turn(Color::White);
+    assert_ne!(turn.zobrist_hash(), one.zobrist_hash());
+
+    let mut castling = one.clone();
+    castling.set_castling(Default::default());
+    assert_ne!(castling.zobrist_hash(), one.zobrist_hash());
+
+    let knights = play(&["Nf3", "Nf6", "Ng1", "Ng8"])?;
+    assert_eq!(knights.zobrist_hash(), play(&[])?.zobrist_hash());
+    Ok(())
+}
+
+#[test]
+fn tt_store_and_probe() -> Result<(), ChessError> {
+    let table = TranspositionTable::new(1);
+    assert_eq!(table.size_mb(), 1);
+    assert_eq!(table.probe(42, Geometry::GRAND, Color::White), None);
+
+    // squares past 64 and promotions survive packing
+    let mv = Move {
+        from: Position { rank: 8, file: 9 },
+        to: Position { rank: 9, file: 9 },
+        promotion: Some(Piece::Knight(Color::White)),
+    };
+    let stored = TtEntry {
+        depth: 7,
+        bound: Bound::Lower,
+        score: -MATE + 12,
+        best_move: Some(mv),
+        age: 0,
+    };
+    table.store(42, stored, Geometry::GRAND);
+    assert_eq!(table.probe(42, Geometry::GRAND, Color::White), Some(stored));
+
+    // a later store without a move keeps the known one
+    table.store(42, entry(9, 15), Geometry::GRAND);
+    let probed = table.probe(42, Geometry::GRAND, Color::White).unwrap();
+    assert_eq!((probed.depth, probed.score), (9, 15));
+    assert_eq!(probed.best_move, Some(mv));
+
+    let stats = table.stats();
+    assert_eq!((stats.probes, stats.hits, stats.stores), (3, 2, 2));
+    assert!((stats.hit_rate() - 2.0 / 3.0).abs() < 1e-9);
+
+    table.clear();
+    assert_eq!(table.probe(42, Geometry::GRAND, Color::White), None);
+    assert_eq!(table.stats().stores, 0);
+
+    assert_eq!(score_from_tt(score_to_tt(MATE - 5, 3), 3), MATE - 5);
+    assert_eq!(score_to_tt(MATE - 5, 3), MATE - 2);
+    assert_eq!(score_to_tt(-MATE + 5, 3), -MATE + 2);
+    assert_eq!(score_to_tt(120, 3), 120);
+    Ok(())
+}
+
+#[test]
+fn tt_replacement() -> Result<(), ChessError> {
+    let table = TranspositionTable::new(1);
+    let geometry = Geometry::STANDARD;
+    let probe = |hash: u64| table.probe(hash, geometry, Color::White).map(|e| e.depth);
+    // hashes one table length apart share a bucket
+    let buckets = 1024 * 1024 / 32;
+    let [deep, shallow, newer, later] = [7, 7 + buckets, 7 + 2 * buckets, 7 + 3 * buckets];
+
+    table.store(deep, entry(8, 0), geometry);
+    table.store(shallow, entry(2, 0), geometry);
+    assert_eq!((probe(deep), probe(shallow)), (Some(8), Some(2)));
+
+    table.store(newer, entry(1, 0), geometry);
+    assert_eq!(
+        (probe(deep), probe(shallow), probe(newer)),
+        (Some(8), None, Some(1))
+    );
+
+    // entries from an older search give way even to shallow ones
+    table.new_search();
+    table.store(later, entry(1, 0), geometry);
+    assert_eq!((probe(deep), probe(later)), (None, Some(1)));
+
+    assert_eq!(table.hashfull(), 1);
+    for hash in 0..500 {
+        table.store(hash, entry(3, 0), geometry);
+    }
+    assert_eq!(table.hashfull(), 500);
+    Ok(())
+}
+
+#[test]
+fn tt_speeds_up_search() -> Result<(), ChessError> {
+    let board = play(&["e4", "e5", "Nf3", "Nc6"])?;
+    let limits = SearchLimits {
+        depth: Some(3),
+        ..SearchLimits::default()
+    };
+    let table = TranspositionTable::new(1);
+    let params = eval::EvalParams::default();
+    let stop = AtomicBool::new(false);
+
+    let first = search_with(&board, &limits, &params, &table, &stop, |_| {})?;
+    assert!(table.stats().hits > 0);
+    let again = search_with(&board, &limits, &params, &table, &stop, |_| {})?;
+    assert!(again.nodes < first.nodes);
+    assert_eq!(table.age(), 2);
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use super::super::*;
+use board::Board;
+use game::START_FEN;
+use notation::parse_san;
+use search::Score;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::Duration;
+use uci::GoParams;
+use uci_client::{EngineEvent, EngineOption, InfoLine, OptionKind, ScoreBound, UciEngine};
+use utils::ChessError;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn fake_engine() -> Result<UciEngine, ChessError> {
+    let script = Path::new(file!()).with_file_name("fixtures/fake_uci.sh");
+    UciEngine::spawn(OsStr::new("sh"), &[script.as_os_str()])
+}
+
+fn start_board() -> Result<Board, ChessError> {
+    let mut board = Board::new();
+    board.from_fen(START_FEN.to_string())?;
+    Ok(board)
+}
+
+#[test]
+fn uci_client_option_parse() -> Result<(), ChessError> {
+    let option = EngineOption::parse("option name Skill Level type spin default 20 min 0 max 20")?;
+    assert_eq!(option.name, "Skill Level");
+    assert_eq!(
+        option.kind,
+        OptionKind::Spin {
+            default: 20,
+            min: 0,
+            max: 20
+        }
+    );
+    assert!(option.validate("7").is_ok());
+    assert!(option.validate("21").is_err());
+    assert!(option.validate("high").is_err());
+
+    let option =
+        EngineOption::parse("option name Style type combo default Normal var Solid var Normal")?;
+    assert!(option.validate("solid").is_ok());
+    assert!(option.validate("Wild").is_err());
+
+    assert!(EngineOption::parse("option name Broken type spin default 1").is_err());
+    assert!(EngineOption::parse("option type check").is_err());
+    Ok(())
+}
+
+#[test]
+fn uci_client_info_parse() -> Result<(), ChessError> {
+    let board = start_board()?;
+
+    let info = InfoLine::parse(
+        "info depth 3 seldepth 5 score cp -15 lowerbound nodes 1200 nps 60000 time 20 pv g1f3 d7d5",
+        &board,
+    )?;
+    assert_eq!(info.depth, Some(3));
+    assert_eq!(info.seldepth, Some(5));
+    assert_eq!(info.score, Some(Score::Centipawns(-15)));
+    assert_eq!(info.bound, ScoreBound::Lower);
+    assert_eq!(info.nodes, Some(1200));
+    assert_eq!(info.time, Some(Duration::from_millis(20)));
+    assert_eq!(info.pv.len(), 2);
+    assert_eq!(info.pv[0], parse_san(&board, "Nf3")?);
+
+    let info = InfoLine::parse("info string hello there", &board)?;
+    assert_eq!(info.string.as_deref(), Some("hello there"));
+
+    // pv moves must be legal in sequence
+    assert!(InfoLine::parse("info depth 1 pv e2e4 e2e4", &board).is_err());
+    assert!(InfoLine::parse("info depth x", &board).is_err());
+    Ok(())
+}
+
+#[test]
+fn uci_client_session() -> Result<(), ChessError> {
+    let mut engine = fake_engine()?;
+    assert_eq!(engine.name(), Some("Fake Engine 1.0"));
+    assert_eq!(engine.author(), Some("The Tests"));
+    assert_eq!(engine.options().len(), 5);
+    assert_eq!(
+        engine.option("ponder").map(|o| o.kind.clone()),
+        Some(OptionKind::Check { default: false })
+    );
+    assert_eq!(
+        engine.option("Clear Hash").map(|o| o.kind.clone()),
+        Some(OptionKind::Button)
+    );
+
+    engine.set_option("Hash", "64")?;
+    engine.set_option("Clear Hash", "")?;
+    assert!(engine.set_option("Hash", "4096").is_err());
+    assert!(engine.set_option("Threads", "2").is_err());
+    engine.is_ready(TIMEOUT)?;
+
+    engine.new_game()?;
+    let start = start_board()?;
+    engine.set_position(&start, &[])?;
+    let params = GoParams {
+        depth: Some(2),
+        ..GoParams::default()
+    };
+    let outcome = engine.search(&params, TIMEOUT)?;
+    assert_eq!(outcome.best, Some(parse_san(&start, "e4")?));
+    assert!(outcome.ponder.is_some());
+    assert_eq!(outcome.infos.len(), 3);
+    assert_eq!(
+        outcome.infos[0].string.as_deref(),
+        Some("searching go depth 2")
+    );
+    assert_eq!(outcome.infos[1].pv.len(), 2);
+    assert_eq!(outcome.infos[2].score, Some(Score::Mate(-3)));
+    assert_eq!(outcome.infos[2].bound, ScoreBound::Upper);
+    assert_eq!(outcome.infos[2].multipv, Some(2));
+
+    // the scripted answers are illegal once White has moved
+    let e4 = parse_san(&start, "e4")?;
+    engine.set_position(&start, &[e4])?;
+    assert_eq!(engine.board().get_turn(), piece::Color::Black);
+    engine.go(&params)?;
+    assert!(matches!(
+        engine.next_event(TIMEOUT)?,
+        Some(EngineEvent::Info(_))
+    ));
+    assert!(engine.next_event(TIMEOUT).is_err());
+
+    engine.quit(TIMEOUT)
+}
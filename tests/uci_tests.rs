@@ -0,0 +1,198 @@
+use super::super::*;
+use std::io::{BufReader, Read, Write};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use uci::{parse_position, GoParams};
+use utils::ChessError;
+
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Output {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+
+    fn wait_for(&self, line: &str) -> () {
+        let start = Instant::now();
+        while !self.text().lines().any(|l| l.starts_with(line)) {
+            assert!(
+                start.elapsed() < Duration::from_secs(30),
+                "no {} in output",
+                line
+            );
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+struct Input(Receiver<String>, Vec<u8>);
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.1.is_empty() {
+            match self.0.recv() {
+                Ok(line) => self.1 = line.into_bytes(),
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.1.len());
+        buf[..n].copy_from_slice(&self.1[..n]);
+        self.1.drain(..n);
+        Ok(n)
+    }
+}
+
+#[test]
+fn uci_go_params() -> Result<(), ChessError> {
+    let params = GoParams::parse(&[
+        "wtime",
+        "60000",
+        "btime",
+        "30000",
+        "winc",
+        "1000",
+        "movestogo",
+        "20",
+    ])?;
+    assert_eq!(params.btime, Some(Duration::from_millis(30_000)));
+    let limits = params.limits(piece::Color::White);
+    assert_eq!(limits.movetime, None);
+    assert_eq!(
+        limits.clock,
+        Some(timeman::SearchClock {
+            remaining: Duration::from_secs(60),
+            increment: Duration::from_secs(1),
+            moves_to_go: Some(20),
+        })
+    );
+    assert!(!limits.infinite);
+    assert!(limits.pondering.is_none());
+    assert!(GoParams::parse(&["ponder"])?
+        .limits(piece::Color::White)
+        .pondering
+        .is_some());
+
+    assert!(GoParams::parse(&["depth", "x"]).is_err());
+    assert!(
+        GoParams::parse(&["infinite"])?
+            .limits(piece::Color::Black)
+            .infinite
+    );
+    Ok(())
+}
+
+#[test]
+fn uci_position() -> Result<(), ChessError> {
+    let board = parse_position(&["startpos", "moves", "e2e4", "c7c5", "g1f3"])?;
+    assert_eq!(
+        board.generate_fen()?,
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+
+    let promotion = parse_position(&[
+        "fen",
+        "8/4P3/8/8/8/8/k7/4K3",
+        "w",
+        "-",
+        "-",
+        "0",
+        "1",
+        "moves",
+        "e7e8n",
+    ])?;
+    assert_eq!(promotion.generate_fen()?, "4N3/8/8/8/8/8/k7/4K3 b - - 0 1");
+
+    assert!(parse_position(&["startpos", "moves", "e2e5"]).is_err());
+    assert!(parse_position(&["fen"]).is_err());
+    Ok(())
+}
+
+#[test]
+fn uci_session() -> Result<(), ChessError> {
+    let (send, receive) = channel::<String>();
+    let output = Output::default();
+    let engine_output = output.clone();
+    let engine =
+        thread::spawn(move || uci::run(BufReader::new(Input(receive, Vec::new())), engine_output));
+
+    for line in [
+        "uci",
+        "isready",
+        "position fen 6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1",
+        "go depth 2",
+        "setoption name Foo value 1",
+        "setoption name Hash value 2",
+        "setoption name Hash value 0",
+        "setoption name Threads value 2",
+        "bogus",
+    ] {
+        send.send(format!("{}\n", line)).unwrap();
+    }
+    output.wait_for("bestmove");
+
+    send.send("go infinite\n".into()).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(output.text().matches("bestmove").count(), 1);
+    send.send("stop\n".into()).unwrap();
+
+    // a finished ponder search holds its move back until the hit
+    send.send("go ponder depth 1\n".into()).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(output.text().matches("bestmove").count(), 2);
+    send.send("ponderhit\n".into()).unwrap();
+    let start = Instant::now();
+    while output.text().matches("bestmove").count() < 3 {
+        assert!(
+            start.elapsed() < Duration::from_secs(30),
+            "no bestmove after ponderhit"
+        );
+        thread::sleep(Duration::from_millis(5));
+    }
+    send.send("quit\n".into()).unwrap();
+    engine.join().unwrap()?;
+    assert_eq!(output.text().matches("bestmove").count(), 3);
+
+    let text = output.text();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "id name chess");
+    assert!(lines.contains(&"uciok"));
+    assert!(lines.contains(&"readyok"));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("info depth 1 score mate 1")));
+    assert!(lines.contains(&"bestmove a1a8"));
+    assert!(lines.contains(&"option name Hash type spin default 16 min 1 max 4096"));
+    assert!(lines.contains(&"option name Threads type spin default 1 min 1 max 256"));
+    assert!(lines.contains(&"info string unknown option Foo"));
+    assert!(lines.contains(&"info string error: parse error: invalid Hash value 0"));
+    assert!(lines.contains(&"info string unknown command bogus"));
+    Ok(())
+}
+
+#[test]
+fn uci_search_outlives_input() -> Result<(), ChessError> {
+    let output = Output::default();
+    let input = "position startpos\ngo depth 3\n";
+    uci::run(BufReader::new(input.as_bytes()), output.clone())?;
+    let text = output.text();
+    assert!(text.lines().any(|line| line.starts_with("info depth 3 ")));
+    assert_eq!(text.matches("bestmove").count(), 1);
+
+    let output = Output::default();
+    uci::run(BufReader::new("go infinite\n".as_bytes()), output.clone())?;
+    assert_eq!(output.text().matches("bestmove").count(), 1);
+    Ok(())
+}
@@ -1,13 +1,14 @@
 use super::super::*;
+use geometry::Geometry;
 use utils::ChessError;
 
 #[test]
 fn masking() -> Result<(), ChessError> {
-    assert_eq!(utils::mask(1, 1)?, 1u64 << 9);
-    assert_eq!(utils::mask(6, 1)?, 1u64 << 49);
+    assert_eq!(Geometry::STANDARD.mask(1, 1)?, 1 << 9);
+    assert_eq!(Geometry::STANDARD.mask(6, 1)?, 1 << 49);
 
-    assert_eq!(utils::unmask(1u64 << 9)?, (1, 1));
-    assert_eq!(utils::unmask(1u64 << 49)?, (6, 1));
+    assert_eq!(Geometry::STANDARD.unmask(1 << 9)?, (1, 1));
+    assert_eq!(Geometry::STANDARD.unmask(1 << 49)?, (6, 1));
     Ok(())
 }
 
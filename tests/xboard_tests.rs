@@ -0,0 +1,141 @@
+use super::super::*;
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use utils::ChessError;
+use xboard::Level;
+
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn session(commands: &[&str]) -> Result<Vec<String>, ChessError> {
+    let output = Output::default();
+    let input = Cursor::new(commands.join("\n"));
+    xboard::run(input, output.clone())?;
+
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    Ok(text.lines().map(str::to_string).collect())
+}
+
+#[test]
+fn xboard_level() -> Result<(), ChessError> {
+    let level = Level::parse(&["40", "0:30", "0.5"])?;
+    assert_eq!(level.moves, 40);
+    assert_eq!(level.base, Duration::from_secs(30));
+    assert_eq!(level.inc, Duration::from_millis(500));
+
+    let level = Level::parse(&["0", "5", "12"])?;
+    assert_eq!(level.base, Duration::from_secs(300));
+    assert_eq!(level.inc, Duration::from_secs(12));
+
+    assert!(Level::parse(&["0", "5"]).is_err());
+    assert!(Level::parse(&["0", "five", "0"]).is_err());
+    assert!(Level::parse(&["0", "5", "-1"]).is_err());
+    Ok(())
+}
+
+#[test]
+fn xboard_session() -> Result<(), ChessError> {
+    let lines = session(&[
+        "xboard",
+        "protover 2",
+        "new",
+        "level 0 1 0",
+        "sd 1",
+        "cores 2",
+        "usermove e2e4",
+        "ping 1",
+        "usermove e2e5",
+        "remove",
+        "usermove e2e4",
+        "ping 2",
+        "force",
+        "bogus",
+        "quit",
+    ])?;
+
+    assert!(lines[0].starts_with("feature myname=\"chess\""));
+    assert!(lines[0].contains("smp=1"));
+    assert!(lines[0].contains("variants=\"normal\""));
+    assert_eq!(lines[1], "feature done=1");
+
+    // the engine answers each move before the following ping
+    let pong = |n: &str| {
+        lines
+            .iter()
+            .position(|l| *l == format!("pong {}", n))
+            .unwrap()
+    };
+    let moves: Vec<usize> = (0..lines.len())
+        .filter(|&idx| lines[idx].starts_with("move "))
+        .collect();
+    assert_eq!(moves.len(), 2);
+    assert!(moves[0] < pong("1") && pong("1") < moves[1] && moves[1] < pong("2"));
+
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|l| l.starts_with("Illegal move"))
+            .count(),
+        1
+    );
+    assert!(lines.contains(&"Illegal move: e2e5".to_string()));
+    assert!(lines.contains(&"Error (unknown command): bogus".to_string()));
+    Ok(())
+}
+
+#[test]
+fn xboard_game_end() -> Result<(), ChessError> {
+    let lines = session(&[
+        "new",
+        "force",
+        "setboard 6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1",
+        "usermove a1a8",
+        "undo",
+        "sd 2",
+        "post",
+        "go",
+        "ping 1",
+        "usermove g8h8",
+        "setboard 8/8/8/8",
+        "new",
+        "variant normal",
+        "sd 1",
+        "nopost",
+        "usermove a2a3",
+        "ping 2",
+        "variant crazyhouse",
+        "variant losalamos",
+    ])?;
+
+    assert_eq!(lines[0], "1-0 {White mates}");
+    assert!(lines[1].starts_with("1 100001 "));
+    let played = lines.iter().position(|l| l == "move a1a8").unwrap();
+    assert_eq!(lines[played + 1], "1-0 {White mates}");
+    assert_eq!(lines[played + 2], "pong 1");
+
+    // no moves are accepted once the game is over
+    assert_eq!(lines[played + 3], "Illegal move: g8h8");
+    assert_eq!(lines[played + 4], "tellusererror Illegal position");
+    assert!(lines[played + 5].starts_with("move "));
+    assert_eq!(lines[played + 6], "pong 2");
+    assert_eq!(
+        lines[played + 7],
+        "Error (parse error: unsupported variant crazyhouse): variant crazyhouse"
+    );
+    assert_eq!(
+        lines[played + 8],
+        "Error (parse error: unsupported variant losalamos): variant losalamos"
+    );
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::piece::Move;
+
+/// Time kept back for each move to cover engine and GUI latency.
+pub const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+/// Moves left in the game assumed for sudden death time controls.
+pub const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+/// The hard limit allows this many soft limits, at most.
+const HARD_RATIO: u32 = 4;
+
+/// Clock of the side to move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchClock {
+    pub remaining: Duration,
+    pub increment: Duration,
+    pub moves_to_go: Option<u32>,
+}
+
+impl SearchClock {
+    /// Soft and hard thinking time: the search aims for the soft limit and
+    /// never runs past the hard one.
+    pub fn allocate(&self) -> (Duration, Duration) {
+        let usable = self.remaining.saturating_sub(MOVE_OVERHEAD);
+        let moves = self.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+        let soft = (self.remaining / moves + self.increment / 2).min(usable);
+        let hard = (soft * HARD_RATIO).min(usable / 2).max(soft);
+        (soft, hard)
+    }
+}
+
+/// Decides when a search under a clock stops. Iterations end early when the
+/// best move is settled and run longer when it keeps changing or the score
+/// drops.
+#[derive(Debug)]
+pub struct TimeManager {
+    start: Instant,
+    soft: Duration,
+    hard: Duration,
+    forced: bool,
+    pondering: Option<Arc<AtomicBool>>,
+    best_move: Option<Move>,
+    score: Option<i32>,
+    /// Best move changes, halved every iteration so old ones fade out.
+    instability: f64,
+    score_drop: i32,
+}
+
+impl TimeManager {
+    /// `forced` marks a position with a single legal move. While `pondering`
+    /// is set the clock does not run; it starts once the flag is cleared.
+    pub fn new(clock: SearchClock, forced: bool, pondering: Option<Arc<AtomicBool>>) -> Self {
+        let (soft, hard) = clock.allocate();
+        Self {
+            start: Instant::now(),
+            soft,
+            hard,
+            forced,
+            pondering,
+            best_move: None,
+            score: None,
+            instability: 0.0,
+            score_drop: 0,
+        }
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.pondering
+            .as_ref()
+            .is_some_and(|pondering| pondering.load(Ordering::Relaxed))
+    }
+
+    fn poll_ponder_hit(&mut self) -> () {
+        if self.pondering.is_some() && !self.is_pondering() {
+            self.pondering = None;
+            self.start = Instant::now();
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn hard_limit(&self) -> Duration {
+        self.hard
+    }
+
+    /// The soft limit stretched by how unsettled the search is, never beyond
+    /// the hard limit.
+    pub fn soft_limit(&self) -> Duration {
+        let unstable = 1.0 + self.instability.min(2.0) / 2.0;
+        let dropping = 1.0 + (self.score_drop.clamp(0, 100) as f64) / 100.0;
+        self.soft.mul_f64(unstable * dropping).min(self.hard)
+    }
+
+    /// Whether the hard limit has passed. If so `stop` is raised, so every
+    /// thread watching the flag ends its search too.
+    pub fn out_of_time(&mut self, stop: &AtomicBool) -> bool {
+        self.poll_ponder_hit();
+        if self.is_pondering() || self.elapsed() < self.hard {
+            return false;
+        }
+        stop.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Records the result of a completed iteration.
+    pub fn update(&mut self, best_move: Option<Move>, score: i32) -> () {
+        self.instability /= 2.0;
+        if self.best_move.is_some() && best_move != self.best_move {
+            self.instability += 1.0;
+        }
+        self.score_drop = self.score.map_or(0, |previous| previous - score);
+        self.best_move = best_move;
+        self.score = Some(score);
+    }
+
+    /// Whether to stop instead of starting another iteration.
+    pub fn stop_iterating(&mut self) -> bool {
+        self.poll_ponder_hit();
+        if self.is_pondering() {
+            return false;
+        }
+        self.forced || self.elapsed() >= self.soft_limit()
+    }
+}
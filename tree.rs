@@ -0,0 +1,728 @@
+use std::time::Duration;
+
+use super::board::Board;
+use super::game::{format_clock, Game, GameResult, START_FEN};
+use super::geometry::Geometry;
+use super::notation::{parse_san, pgn_tag, to_san, wrap_movetext};
+use super::piece::{Color, Move, Position};
+use super::utils::ChessError;
+
+pub type NodeId = usize;
+
+pub const ROOT: NodeId = 0;
+
+pub const NAG_GOOD: u8 = 1;
+pub const NAG_MISTAKE: u8 = 2;
+pub const NAG_BRILLIANT: u8 = 3;
+pub const NAG_BLUNDER: u8 = 4;
+pub const NAG_INTERESTING: u8 = 5;
+pub const NAG_DUBIOUS: u8 = 6;
+
+pub fn nag_from_symbol(symbol: &str) -> Option<u8> {
+    match symbol {
+        "!" => Some(NAG_GOOD),
+        "?" => Some(NAG_MISTAKE),
+        "!!" => Some(NAG_BRILLIANT),
+        "??" => Some(NAG_BLUNDER),
+        "!?" => Some(NAG_INTERESTING),
+        "?!" => Some(NAG_DUBIOUS),
+        _ => symbol.strip_prefix('$').and_then(|n| n.parse().ok()),
+    }
+}
+
+pub fn nag_symbol(nag: u8) -> Option<&'static str> {
+    match nag {
+        NAG_GOOD => Some("!"),
+        NAG_MISTAKE => Some("?"),
+        NAG_BRILLIANT => Some("!!"),
+        NAG_BLUNDER => Some("??"),
+        NAG_INTERESTING => Some("!?"),
+        NAG_DUBIOUS => Some("?!"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkColor {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl MarkColor {
+    pub fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'G' => Some(MarkColor::Green),
+            'R' => Some(MarkColor::Red),
+            'Y' => Some(MarkColor::Yellow),
+            'B' => Some(MarkColor::Blue),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(&self) -> char {
+        match self {
+            MarkColor::Green => 'G',
+            MarkColor::Red => 'R',
+            MarkColor::Yellow => 'Y',
+            MarkColor::Blue => 'B',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arrow {
+    pub color: MarkColor,
+    pub from: Position,
+    pub to: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highlight {
+    pub color: MarkColor,
+    pub square: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eval {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+impl Eval {
+    pub fn parse(text: &str) -> Result<Self, ChessError> {
+        let err = || ChessError::ParseError(format!("invalid eval: {}", text));
+        match text.strip_prefix('#') {
+            Some(mate) => mate.parse().map(Eval::Mate).map_err(|_| err()),
+            None => text
+                .parse::<f64>()
+                .map(|pawns| Eval::Centipawns((pawns * 100.0).round() as i32))
+                .map_err(|_| err()),
+        }
+    }
+
+    pub fn as_pgn(&self) -> String {
+        match self {
+            Eval::Centipawns(cp) => format!("{:.2}", *cp as f64 / 100.0),
+            Eval::Mate(n) => format!("#{}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TreeNode {
+    pub mv: Option<Move>,
+    pub san: String,
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
+    pub ply: usize,
+    pub comment: Option<String>,
+    pub nags: Vec<u8>,
+    pub arrows: Vec<Arrow>,
+    pub highlights: Vec<Highlight>,
+    pub clock: Option<Duration>,
+    pub eval: Option<Eval>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameTree {
+    nodes: Vec<Option<TreeNode>>,
+    root_board: Board,
+    board: Board,
+    current: NodeId,
+    headers: Vec<(String, String)>,
+}
+
+impl GameTree {
+    pub fn new(board: Board) -> Self {
+        let mut root_board = board;
+        root_board.set_clock(None);
+
+        Self {
+            nodes: vec![Some(TreeNode::default())],
+            board: root_board.clone(),
+            root_board,
+            current: ROOT,
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn from_game(game: &Game) -> Result<Self, ChessError> {
+        let mut board = Board::new();
+        board.from_fen(game.start_fen().to_string())?;
+
+        let mut tree = Self::new(board);
+        for played in game.moves() {
+            let id = tree.play(played.mv)?;
+            tree.node_mut(id)?.clock = played.clock;
+        }
+        tree.set_header("Result", game.result().as_pgn());
+        Ok(tree)
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    pub fn node(&self, id: NodeId) -> Result<&TreeNode, ChessError> {
+        self.nodes
+            .get(id)
+            .and_then(Option::as_ref)
+            .ok_or_else(|| ChessError::InvalidPosition(format!("no node {} in the tree", id)))
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Result<&mut TreeNode, ChessError> {
+        self.nodes
+            .get_mut(id)
+            .and_then(Option::as_mut)
+            .ok_or_else(|| ChessError::InvalidPosition(format!("no node {} in the tree", id)))
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_header(&mut self, name: impl Into<String>, value: impl Into<String>) -> () {
+        let (name, value) = (name.into(), value.into());
+        match self.headers.iter_mut().find(|(tag, _)| *tag == name) {
+            Some(header) => header.1 = value,
+            None => self.headers.push((name, value)),
+        }
+    }
+
+    pub fn path(&self, id: NodeId) -> Result<Vec<NodeId>, ChessError> {
+        let mut path = vec![id];
+        let mut node = self.node(id)?;
+        while let Some(parent) = node.parent {
+            path.push(parent);
+            node = self.node(parent)?;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    pub fn mainline(&self) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut id = ROOT;
+        while let Some(&next) = self.nodes[id].as_ref().and_then(|n| n.children.first()) {
+            line.push(next);
+            id = next;
+        }
+        line
+    }
+
+    pub fn board_at(&self, id: NodeId) -> Result<Board, ChessError> {
+        let mut board = self.root_board.clone();
+        for step in self.path(id)?.into_iter().skip(1) {
+            let mv = self
+                .node(step)?
+                .mv
+                .ok_or_else(|| ChessError::InternalError(format!("node {} has no move", step)))?;
+            board.make_move(mv.from, mv.to, mv.promotion)?;
+        }
+        Ok(board)
+    }
+
+    pub fn play(&mut self, mv: Move) -> Result<NodeId, ChessError> {
+        let existing = self
+            .node(self.current)?
+            .children
+            .iter()
+            .copied()
+            .find(|&child| self.nodes[child].as_ref().and_then(|n| n.mv) == Some(mv));
+        if let Some(child) = existing {
+            self.board.make_move(mv.from, mv.to, mv.promotion)?;
+            self.current = child;
+            return Ok(child);
+        }
+
+        let san = to_san(&self.board, mv)?;
+        self.board.make_move(mv.from, mv.to, mv.promotion)?;
+
+        let id = self.nodes.len();
+        let ply = self.node(self.current)?.ply + 1;
+        self.nodes.push(Some(TreeNode {
+            mv: Some(mv),
+            san,
+            parent: Some(self.current),
+            ply,
+            ..TreeNode::default()
+        }));
+        let current = self.current;
+        self.node_mut(current)?.children.push(id);
+        self.current = id;
+        Ok(id)
+    }
+
+    pub fn play_san(&mut self, san: &str) -> Result<NodeId, ChessError> {
+        let mv = parse_san(&self.board, san)?;
+        self.play(mv)
+    }
+
+    pub fn goto(&mut self, id: NodeId) -> Result<(), ChessError> {
+        self.board = self.board_at(id)?;
+        self.current = id;
+        Ok(())
+    }
+
+    pub fn back(&mut self) -> Result<bool, ChessError> {
+        match self.node(self.current)?.parent {
+            Some(parent) => self.goto(parent).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    pub fn forward(&mut self) -> Result<bool, ChessError> {
+        match self.node(self.current)?.children.first().copied() {
+            Some(child) => {
+                let mv = self.node(child)?.mv.unwrap();
+                self.board.make_move(mv.from, mv.to, mv.promotion)?;
+                self.current = child;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn promote_variation(&mut self, id: NodeId) -> Result<(), ChessError> {
+        let mut child = id;
+        while let Some(parent) = self.node(child)?.parent {
+            let siblings = &mut self.node_mut(parent)?.children;
+            let idx = siblings.iter().position(|&c| c == child).unwrap();
+            if idx > 0 {
+                siblings.swap(idx, idx - 1);
+                return Ok(());
+            }
+            child = parent;
+        }
+        Ok(())
+    }
+
+    pub fn make_mainline(&mut self, id: NodeId) -> Result<(), ChessError> {
+        let mut child = id;
+        while let Some(parent) = self.node(child)?.parent {
+            let siblings = &mut self.node_mut(parent)?.children;
+            let idx = siblings.iter().position(|&c| c == child).unwrap();
+            let moved = siblings.remove(idx);
+            siblings.insert(0, moved);
+            child = parent;
+        }
+        Ok(())
+    }
+
+    pub fn delete_from(&mut self, id: NodeId) -> Result<(), ChessError> {
+        let parent = self
+            .node(id)?
+            .parent
+            .ok_or_else(|| ChessError::IllegalMove("cannot delete the root node".into()))?;
+        let in_subtree = self.path(self.current)?.contains(&id);
+
+        self.node_mut(parent)?.children.retain(|&child| child != id);
+        let mut stack = vec![id];
+        while let Some(next) = stack.pop() {
+            if let Some(node) = self.nodes[next].take() {
+                stack.extend(node.children);
+            }
+        }
+
+        if in_subtree {
+            self.goto(parent)?;
+        }
+        Ok(())
+    }
+
+    pub fn from_pgn(pgn: &str) -> Result<Self, ChessError> {
+        let mut headers = Vec::new();
+        let mut movetext = String::new();
+        for line in pgn.lines() {
+            let trimmed = line.trim();
+            match trimmed.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                Some(tag) if movetext.trim().is_empty() => headers.push(parse_tag(tag)?),
+                _ => {
+                    movetext.push_str(line);
+                    movetext.push('\n');
+                }
+            }
+        }
+
+        let mut board = Board::new();
+        let fen = headers
+            .iter()
+            .find(|(tag, _)| tag == "FEN")
+            .map_or(START_FEN, |(_, fen)| fen.as_str());
+        board.from_fen(fen.to_string())?;
+
+        let mut tree = Self::new(board);
+        tree.headers = headers;
+        tree.parse_movetext(&movetext)?;
+        tree.goto(ROOT)?;
+        Ok(tree)
+    }
+
+    /// Parses a PGN file holding any number of games, one tree per game.
+    pub fn from_pgn_collection(pgn: &str) -> Result<Vec<Self>, ChessError> {
+        let mut games = Vec::new();
+        let mut chunk = String::new();
+        let mut in_movetext = false;
+        for line in pgn.lines() {
+            let trimmed = line.trim();
+            let is_tag = trimmed.starts_with('[') && !trimmed.starts_with("[%");
+            // a tag after movetext opens the next game
+            if is_tag && in_movetext {
+                games.push(Self::from_pgn(&chunk)?);
+                chunk.clear();
+                in_movetext = false;
+            }
+            in_movetext |= !is_tag && !trimmed.is_empty();
+            chunk.push_str(line);
+            chunk.push('\n');
+        }
+        if !chunk.trim().is_empty() {
+            games.push(Self::from_pgn(&chunk)?);
+        }
+        Ok(games)
+    }
+
+    pub fn to_pgn(&self) -> Result<String, ChessError> {
+        let result = self.header("Result").unwrap_or("*").to_string();
+
+        let mut pgn = String::new();
+        for (tag, value) in self.headers.iter() {
+            pgn.push_str(&pgn_tag(tag, value));
+        }
+        if !self.headers.is_empty() {
+            pgn.push('\n');
+        }
+
+        let mut tokens = Vec::new();
+        if let Some(comment) = self.comment_text(self.node(ROOT)?) {
+            tokens.push(comment);
+        }
+        self.write_line(ROOT, true, &mut tokens)?;
+        tokens.push(result);
+
+        pgn.push_str(&wrap_movetext(tokens));
+        Ok(pgn)
+    }
+
+    fn parse_movetext(&mut self, movetext: &str) -> Result<(), ChessError> {
+        let mut stack: Vec<NodeId> = Vec::new();
+        let mut chars = movetext.char_indices().peekable();
+
+        while let Some((start, ch)) = chars.next() {
+            match ch {
+                '{' => {
+                    let mut text = String::new();
+                    for (_, c) in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                        text.push(c);
+                    }
+                    let geometry = self.board.geometry();
+                    let node = self.node_mut(self.current)?;
+                    apply_comment(node, &text, geometry)?;
+                }
+                ';' => {
+                    for (_, c) in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                '(' => {
+                    stack.push(self.current);
+                    let parent = self.node(self.current)?.parent.ok_or_else(|| {
+                        ChessError::ParseError("variation before the first move".into())
+                    })?;
+                    self.goto(parent)?;
+                }
+                ')' => {
+                    let resume = stack
+                        .pop()
+                        .ok_or_else(|| ChessError::ParseError("unbalanced ')' in PGN".into()))?;
+                    self.goto(resume)?;
+                }
+                c if c.is_whitespace() => {}
+                _ => {
+                    let mut end = start + ch.len_utf8();
+                    while let Some(&(idx, c)) = chars.peek() {
+                        if c.is_whitespace() || "{}();".contains(c) {
+                            break;
+                        }
+                        end = idx + c.len_utf8();
+                        chars.next();
+                    }
+                    self.apply_token(&movetext[start..end])?;
+                }
+            }
+        }
+
+        match stack.is_empty() {
+            true => Ok(()),
+            false => Err(ChessError::ParseError("unclosed variation in PGN".into())),
+        }
+    }
+
+    fn apply_token(&mut self, token: &str) -> Result<(), ChessError> {
+        if matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+            if self.header("Result").is_none() {
+                self.set_header("Result", GameResult::from_pgn(token).as_pgn());
+            }
+            return Ok(());
+        }
+        if let Some(nag) = nag_from_symbol(token) {
+            let current = self.current;
+            self.node_mut(current)?.nags.push(nag);
+            return Ok(());
+        }
+
+        // strip move numbers such as "12." or "12..." glued to the move
+        let san = match token.rfind('.') {
+            Some(dot) => &token[dot + 1..],
+            None => token,
+        };
+        if san.is_empty() {
+            return Ok(());
+        }
+
+        let glyph_start = san.find(['!', '?']).unwrap_or(san.len());
+        let (san, glyph) = san.split_at(glyph_start);
+        let id = self.play_san(san)?;
+        if let Some(nag) = nag_from_symbol(glyph) {
+            self.node_mut(id)?.nags.push(nag);
+        }
+        Ok(())
+    }
+
+    fn write_line(
+        &self,
+        from: NodeId,
+        mut force_number: bool,
+        tokens: &mut Vec<String>,
+    ) -> Result<(), ChessError> {
+        let mut id = from;
+        loop {
+            let children = &self.node(id)?.children;
+            let Some(&main) = children.first() else {
+                return Ok(());
+            };
+
+            force_number = self.write_move(main, force_number, tokens)?;
+            for &variation in children[1..].iter() {
+                tokens.push("(".into());
+                let commented = self.write_move(variation, true, tokens)?;
+                self.write_line(variation, commented, tokens)?;
+                tokens.push(")".into());
+                force_number = true;
+            }
+            id = main;
+        }
+    }
+
+    fn write_move(
+        &self,
+        id: NodeId,
+        force_number: bool,
+        tokens: &mut Vec<String>,
+    ) -> Result<bool, ChessError> {
+        let node = self.node(id)?;
+        let root = &self.root_board;
+        let index = (root.get_fullmove() as usize - 1) * 2
+            + (root.get_turn() == Color::Black) as usize
+            + node.ply
+            - 1;
+        let number = index / 2 + 1;
+
+        match index % 2 {
+            0 => tokens.push(format!("{}.", number)),
+            _ if force_number => tokens.push(format!("{}...", number)),
+            _ => {}
+        }
+
+        let mut san = node.san.clone();
+        for &nag in node.nags.iter() {
+            match nag_symbol(nag) {
+                Some(symbol) => san.push_str(symbol),
+                None => san.push_str(&format!(" ${}", nag)),
+            }
+        }
+        tokens.push(san);
+
+        Ok(match self.comment_text(node) {
+            Some(comment) => {
+                tokens.push(comment);
+                true
+            }
+            None => false,
+        })
+    }
+
+    fn comment_text(&self, node: &TreeNode) -> Option<String> {
+        let geometry = self.root_board.geometry();
+        let mut parts = Vec::new();
+
+        if let Some(clock) = node.clock {
+            parts.push(format!("[%clk {}]", format_clock(clock)));
+        }
+        if let Some(eval) = node.eval {
+            parts.push(format!("[%eval {}]", eval.as_pgn()));
+        }
+        if !node.arrows.is_empty() {
+            let arrows: Vec<String> = node
+                .arrows
+                .iter()
+                .map(|arrow| {
+                    format!(
+                        "{}{}{}",
+                        arrow.color.as_char(),
+                        geometry.square_name(arrow.from),
+                        geometry.square_name(arrow.to)
+                    )
+                })
+                .collect();
+            parts.push(format!("[%cal {}]", arrows.join(",")));
+        }
+        if !node.highlights.is_empty() {
+            let squares: Vec<String> = node
+                .highlights
+                .iter()
+                .map(|mark| {
+                    format!(
+                        "{}{}",
+                        mark.color.as_char(),
+                        geometry.square_name(mark.square)
+                    )
+                })
+                .collect();
+            parts.push(format!("[%csl {}]", squares.join(",")));
+        }
+        if let Some(comment) = node.comment.as_ref() {
+            parts.push(comment.clone());
+        }
+
+        match parts.is_empty() {
+            true => None,
+            false => Some(format!("{{{}}}", parts.join(" "))),
+        }
+    }
+}
+
+fn parse_tag(tag: &str) -> Result<(String, String), ChessError> {
+    let (name, value) = tag
+        .split_once(' ')
+        .ok_or_else(|| ChessError::ParseError(format!("invalid PGN tag: [{}]", tag)))?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(|| ChessError::ParseError(format!("invalid PGN tag: [{}]", tag)))?;
+    Ok((
+        name.to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+fn parse_clock(text: &str) -> Result<Duration, ChessError> {
+    let err = || ChessError::ParseError(format!("invalid clock: {}", text));
+    let mut secs = 0.0;
+    for part in text.split(':') {
+        let part = part.parse::<f64>().map_err(|_| err())?;
+        if !part.is_finite() || part.is_sign_negative() {
+            return Err(err());
+        }
+        secs = secs * 60.0 + part;
+    }
+    Duration::try_from_secs_f64(secs).map_err(|_| err())
+}
+
+fn parse_marks(
+    text: &str,
+    geometry: Geometry,
+) -> Result<Vec<(MarkColor, Position, Option<Position>)>, ChessError> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|mark| !mark.is_empty())
+        .map(|mark| {
+            let err = || ChessError::ParseError(format!("invalid mark: {}", mark));
+            let color = mark
+                .chars()
+                .next()
+                .and_then(MarkColor::from_char)
+                .ok_or_else(err)?;
+            let squares = mark.get(1..).filter(|s| !s.is_empty()).ok_or_else(err)?;
+            // the second square starts at the second letter
+            let second = squares
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| c.is_ascii_lowercase());
+            match second {
+                Some((split, _)) => Ok((
+                    color,
+                    geometry.parse_square(&squares[..split])?,
+                    Some(geometry.parse_square(&squares[split..])?),
+                )),
+                None => Ok((color, geometry.parse_square(squares)?, None)),
+            }
+        })
+        .collect()
+}
+
+fn apply_comment(node: &mut TreeNode, text: &str, geometry: Geometry) -> Result<(), ChessError> {
+    let mut rest = String::new();
+    let mut remaining = text;
+
+    while let Some(open) = remaining.find("[%") {
+        rest.push_str(&remaining[..open]);
+        let close = remaining[open..]
+            .find(']')
+            .map(|close| open + close)
+            .ok_or_else(|| ChessError::ParseError(format!("unclosed command in {}", text)))?;
+        let command = &remaining[open + 2..close];
+        let (name, args) = command.split_once(' ').unwrap_or((command, ""));
+        let args = args.trim();
+
+        match name {
+            "clk" => node.clock = Some(parse_clock(args)?),
+            "eval" => node.eval = Some(Eval::parse(args.split(',').next().unwrap_or(""))?),
+            "cal" => {
+                for (color, from, to) in parse_marks(args, geometry)? {
+                    let to = to.ok_or_else(|| {
+                        ChessError::ParseError(format!("arrow needs two squares: {}", args))
+                    })?;
+                    node.arrows.push(Arrow { color, from, to });
+                }
+            }
+            "csl" => {
+                for (color, square, _) in parse_marks(args, geometry)? {
+                    node.highlights.push(Highlight { color, square });
+                }
+            }
+            // unknown commands are kept verbatim in the comment
+            _ => rest.push_str(&remaining[open..=close]),
+        }
+        remaining = &remaining[close + 1..];
+    }
+    rest.push_str(remaining);
+
+    let rest = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !rest.is_empty() {
+        node.comment = Some(match node.comment.take() {
+            Some(existing) => format!("{} {}", existing, rest),
+            None => rest,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use super::geometry::Geometry;
+use super::piece::{Color, Move, Piece};
+use super::search::{MATE, MATE_BOUND};
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+const SLOT_BYTES: usize = 16;
+const AGE_MASK: u8 = 0x3f;
+
+const MOVE_BITS: u64 = 18;
+const MOVE_MASK: u64 = (1 << MOVE_BITS) - 1;
+const MOVE_VALID: u64 = 1 << 17;
+const SCORE_SHIFT: u64 = MOVE_BITS;
+const DEPTH_SHIFT: u64 = SCORE_SHIFT + 16;
+const BOUND_SHIFT: u64 = DEPTH_SHIFT + 8;
+const AGE_SHIFT: u64 = BOUND_SHIFT + 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtEntry {
+    pub depth: u32,
+    pub bound: Bound,
+    pub score: i32,
+    pub best_move: Option<Move>,
+    pub age: u8,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TtStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+}
+
+impl TtStats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.probes {
+            0 => 0.0,
+            probes => self.hits as f64 / probes as f64,
+        }
+    }
+}
+
+/// Mate scores are stored relative to the node instead of the root, so they
+/// stay valid when the position is reached at a different ply.
+pub fn score_to_tt(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s >= MATE_BOUND => s + ply,
+        s if s <= -MATE_BOUND => s - ply,
+        s => s,
+    }
+}
+
+pub fn score_from_tt(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s >= MATE_BOUND => s - ply,
+        s if s <= -MATE_BOUND => s + ply,
+        s => s,
+    }
+}
+
+fn pack_move(mv: Option<Move>, geometry: Geometry) -> u64 {
+    let Some(mv) = mv else {
+        return 0;
+    };
+    let promotion = match mv.promotion {
+        None => 0,
+        Some(Piece::Knight(_)) => 1,
+        Some(Piece::Bishop(_)) => 2,
+        Some(Piece::Rook(_)) => 3,
+        Some(Piece::Queen(_)) => 4,
+        Some(_) => 0,
+    };
+    MOVE_VALID
+        | geometry.index(mv.from) as u64
+        | (geometry.index(mv.to) as u64) << 7
+        | promotion << 14
+}
+
+fn unpack_move(packed: u64, geometry: Geometry, color: Color) -> Option<Move> {
+    if packed & MOVE_VALID == 0 {
+        return None;
+    }
+    let square = |idx: u64| {
+        let idx = idx as usize;
+        (idx < geometry.squares()).then(|| geometry.position(idx))
+    };
+    let promotion = match (packed >> 14) & 0x7 {
+        1 => Some(Piece::Knight(color)),
+        2 => Some(Piece::Bishop(color)),
+        3 => Some(Piece::Rook(color)),
+        4 => Some(Piece::Queen(color)),
+        _ => None,
+    };
+
+    Some(Move {
+        from: square(packed & 0x7f)?,
+        to: square((packed >> 7) & 0x7f)?,
+        promotion,
+    })
+}
+
+fn pack(entry: &TtEntry, geometry: Geometry) -> u64 {
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+    pack_move(entry.best_move, geometry)
+        | (entry.score as i16 as u16 as u64) << SCORE_SHIFT
+        | (entry.depth.min(u8::MAX as u32) as u64) << DEPTH_SHIFT
+        | bound << BOUND_SHIFT
+        | ((entry.age & AGE_MASK) as u64) << AGE_SHIFT
+}
+
+fn unpack(data: u64, geometry: Geometry, color: Color) -> TtEntry {
+    TtEntry {
+        depth: ((data >> DEPTH_SHIFT) & 0xff) as u32,
+        bound: match (data >> BOUND_SHIFT) & 0x3 {
+            1 => Bound::Lower,
+            2 => Bound::Upper,
+            _ => Bound::Exact,
+        },
+        score: (data >> SCORE_SHIFT) as u16 as i16 as i32,
+        best_move: unpack_move(data & MOVE_MASK, geometry, color),
+        age: ((data >> AGE_SHIFT) as u8) & AGE_MASK,
+    }
+}
+
+/// One slot stores `key ^ data` next to `data`, so a torn write from another
+/// thread simply fails the key check instead of returning a mixed entry.
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Slot {
+    fn load(&self, hash: u64) -> Option<u64> {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key.load(Ordering::Relaxed);
+        (data != 0 && key ^ data == hash).then_some(data)
+    }
+
+    fn save(&self, hash: u64, data: u64) -> () {
+        self.key.store(hash ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+/// Each bucket holds a depth-preferred slot and an always-replace slot.
+#[derive(Default)]
+struct Bucket {
+    deep: Slot,
+    recent: Slot,
+}
+
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: AtomicU8,
+    probes: AtomicU64,
+    hits: AtomicU64,
+    stores: AtomicU64,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl TranspositionTable {
+    pub fn new(mb: usize) -> Self {
+        let buckets = (mb.max(1) * 1024 * 1024 / (2 * SLOT_BYTES)).max(1);
+        Self {
+            buckets: (0..buckets).map(|_| Bucket::default()).collect(),
+            age: AtomicU8::new(0),
+            probes: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+        }
+    }
+
+    pub fn resize(&mut self, mb: usize) -> () {
+        *self = Self::new(mb);
+    }
+
+    pub fn size_mb(&self) -> usize {
+        self.buckets.len() * 2 * SLOT_BYTES / (1024 * 1024)
+    }
+
+    pub fn clear(&self) -> () {
+        for bucket in self.buckets.iter() {
+            for slot in [&bucket.deep, &bucket.recent] {
+                slot.key.store(0, Ordering::Relaxed);
+                slot.data.store(0, Ordering::Relaxed);
+            }
+        }
+        self.age.store(0, Ordering::Relaxed);
+        self.probes.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+        self.stores.store(0, Ordering::Relaxed);
+    }
+
+    /// Marks older entries as stale; call once per new search.
+    pub fn new_search(&self) -> () {
+        let age = (self.age.load(Ordering::Relaxed) + 1) & AGE_MASK;
+        self.age.store(age, Ordering::Relaxed);
+    }
+
+    pub fn age(&self) -> u8 {
+        self.age.load(Ordering::Relaxed)
+    }
+
+    fn bucket(&self, hash: u64) -> &Bucket {
+        &self.buckets[(hash % self.buckets.len() as u64) as usize]
+    }
+
+    pub fn probe(&self, hash: u64, geometry: Geometry, color: Color) -> Option<TtEntry> {
+        self.probes.fetch_add(1, Ordering::Relaxed);
+        let bucket = self.bucket(hash);
+        let data = bucket
+            .deep
+            .load(hash)
+            .or_else(|| bucket.recent.load(hash))?;
+
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(unpack(data, geometry, color))
+    }
+
+    /// Stores `entry`, stamped with the current age. The depth-preferred slot is
+    /// only overwritten by the same position, a deeper search or when stale.
+    pub fn store(&self, hash: u64, entry: TtEntry, geometry: Geometry) -> () {
+        let age = self.age();
+        let bucket = self.bucket(hash);
+        let mut data = pack(&TtEntry { age, ..entry }, geometry);
+
+        // keep the old best move when the new search did not find one
+        if entry.best_move.is_none() {
+            if let Some(old) = bucket.deep.load(hash).or_else(|| bucket.recent.load(hash)) {
+                data |= old & MOVE_MASK;
+            }
+        }
+
+        self.stores.fetch_add(1, Ordering::Relaxed);
+        self.place(bucket, hash, data, entry.depth, age);
+    }
+
+    fn place(&self, bucket: &Bucket, hash: u64, data: u64, depth: u32, age: u8) -> () {
+        let deep = bucket.deep.data.load(Ordering::Relaxed);
+        let deep_depth = ((deep >> DEPTH_SHIFT) & 0xff) as u32;
+        let deep_age = ((deep >> AGE_SHIFT) as u8) & AGE_MASK;
+
+        if deep == 0 || bucket.deep.load(hash).is_some() || depth >= deep_depth || deep_age != age {
+            bucket.deep.save(hash, data);
+        } else {
+            bucket.recent.save(hash, data);
+        }
+    }
+
+    /// Permille of sampled slots used by the current search, as UCI reports it.
+    pub fn hashfull(&self) -> u32 {
+        let age = self.age();
+        let sample = self.buckets.len().min(500);
+        let used: usize = self.buckets[..sample]
+            .iter()
+            .flat_map(|bucket| [&bucket.deep, &bucket.recent])
+            .map(|slot| slot.data.load(Ordering::Relaxed))
+            .filter(|&data| data != 0 && ((data >> AGE_SHIFT) as u8 & AGE_MASK) == age)
+            .count();
+        (used * 1000 / (sample * 2)) as u32
+    }
+
+    pub fn stats(&self) -> TtStats {
+        TtStats {
+            probes: self.probes.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+            stores: self.stores.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Whether a stored score settles the node without searching it again.
+pub fn cuts_off(entry: &TtEntry, alpha: i32, beta: i32) -> bool {
+    match entry.bound {
+        Bound::Exact => true,
+        Bound::Lower => entry.score >= beta,
+        Bound::Upper => entry.score <= alpha,
+    }
+}
+
+// scores are packed into 16 bits
+const _: () = assert!(MATE < i16::MAX as i32);
@@ -0,0 +1,376 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::board::Board;
+use super::eval::default_params;
+use super::game::START_FEN;
+use super::piece::{Color, Move};
+use super::search::{search_with, Score, SearchInfo, SearchLimits, MAX_THREADS};
+use super::timeman::SearchClock;
+use super::tt::{TranspositionTable, DEFAULT_HASH_MB};
+use super::utils::ChessError;
+
+pub static ENGINE_NAME: &str = "chess";
+pub static ENGINE_AUTHOR: &str = "the chess crate authors";
+pub const MAX_HASH_MB: usize = 4096;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GoParams {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+impl GoParams {
+    pub fn parse(args: &[&str]) -> Result<Self, ChessError> {
+        let mut params = GoParams::default();
+        let mut tokens = args.iter();
+
+        while let Some(&token) = tokens.next() {
+            let mut value = || -> Result<u64, ChessError> {
+                tokens
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| ChessError::ParseError(format!("go {} needs a number", token)))
+            };
+            let millis = |ms: u64| Some(Duration::from_millis(ms));
+
+            match token {
+                "depth" => params.depth = Some(value()? as u32),
+                "nodes" => params.nodes = Some(value()?),
+                "movetime" => params.movetime = millis(value()?),
+                "wtime" => params.wtime = millis(value()?),
+                "btime" => params.btime = millis(value()?),
+                "winc" => params.winc = millis(value()?),
+                "binc" => params.binc = millis(value()?),
+                "movestogo" => params.movestogo = Some(value()? as u32),
+                "infinite" => params.infinite = true,
+                "ponder" => params.ponder = true,
+                _ => {}
+            }
+        }
+
+        Ok(params)
+    }
+
+    pub fn to_command(&self) -> String {
+        let mut command = String::from("go");
+        if self.ponder {
+            command.push_str(" ponder");
+        }
+        for (name, value) in [
+            ("wtime", self.wtime),
+            ("btime", self.btime),
+            ("winc", self.winc),
+            ("binc", self.binc),
+            ("movetime", self.movetime),
+        ] {
+            if let Some(value) = value {
+                command.push_str(&format!(" {} {}", name, value.as_millis()));
+            }
+        }
+        if let Some(movestogo) = self.movestogo {
+            command.push_str(&format!(" movestogo {}", movestogo));
+        }
+        if let Some(depth) = self.depth {
+            command.push_str(&format!(" depth {}", depth));
+        }
+        if let Some(nodes) = self.nodes {
+            command.push_str(&format!(" nodes {}", nodes));
+        }
+        if self.infinite {
+            command.push_str(" infinite");
+        }
+        command
+    }
+
+    pub fn limits(&self, color: Color) -> SearchLimits {
+        let (time, inc) = match color {
+            Color::White => (self.wtime, self.winc),
+            Color::Black => (self.btime, self.binc),
+        };
+        let clock = time.map(|remaining| SearchClock {
+            remaining,
+            increment: inc.unwrap_or_default(),
+            moves_to_go: self.movestogo,
+        });
+
+        SearchLimits {
+            depth: self.depth,
+            nodes: self.nodes,
+            movetime: self.movetime,
+            infinite: self.infinite,
+            threads: 1,
+            clock,
+            pondering: self.ponder.then(|| Arc::new(AtomicBool::new(true))),
+        }
+    }
+}
+
+pub fn format_info(info: &SearchInfo, board: &Board) -> String {
+    let millis = info.elapsed.as_millis().max(1);
+    let score = match info.score {
+        Score::Centipawns(cp) => format!("cp {}", cp),
+        Score::Mate(moves) => format!("mate {}", moves),
+    };
+    let pv: Vec<String> = info
+        .pv
+        .iter()
+        .map(|mv| mv.to_uci(board.geometry()))
+        .collect();
+
+    format!(
+        "info depth {} score {} nodes {} nps {} time {} pv {}",
+        info.depth,
+        score,
+        info.nodes,
+        info.nodes as u128 * 1000 / millis,
+        millis,
+        pv.join(" ")
+    )
+}
+
+pub fn parse_position(args: &[&str]) -> Result<Board, ChessError> {
+    let moves_at = args.iter().position(|&arg| arg == "moves");
+    let (setup, moves) = match moves_at {
+        Some(idx) => (&args[..idx], &args[idx + 1..]),
+        None => (args, &[][..]),
+    };
+
+    let fen = match setup.split_first() {
+        Some((&"startpos", _)) => START_FEN.to_string(),
+        Some((&"fen", fen)) if !fen.is_empty() => fen.join(" "),
+        _ => {
+            return Err(ChessError::ParseError(
+                "position needs startpos or fen".into(),
+            ))
+        }
+    };
+
+    let mut board = Board::new();
+    board.from_fen(fen)?;
+    for text in moves {
+        let mv = Move::from_uci(text, board.geometry(), board.get_turn())?;
+        board.make_move(mv.from, mv.to, mv.promotion)?;
+    }
+    Ok(board)
+}
+
+struct Uci<W: Write + Send + 'static> {
+    board: Board,
+    out: Arc<Mutex<W>>,
+    tt: Arc<TranspositionTable>,
+    threads: usize,
+    stop: Arc<AtomicBool>,
+    infinite: bool,
+    pondering: Option<Arc<AtomicBool>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    fn send(&self, line: &str) -> Result<(), ChessError> {
+        send(&self.out, line)
+    }
+
+    fn stop_search(&mut self) -> () {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    /// Lets the running search finish on its own. Infinite and ponder searches
+    /// are stopped, as no `stop` or `ponderhit` can arrive any more.
+    fn finish_search(&mut self) -> () {
+        if self.infinite || self.pondering.is_some() {
+            self.stop_search();
+        } else if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    fn handle(&mut self, line: &str) -> Result<bool, ChessError> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = tokens.split_first() else {
+            return Ok(true);
+        };
+
+        match command {
+            "uci" => {
+                self.send(&format!("id name {}", ENGINE_NAME))?;
+                self.send(&format!("id author {}", ENGINE_AUTHOR))?;
+                self.send(&format!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_HASH_MB, MAX_HASH_MB
+                ))?;
+                self.send("option name Clear Hash type button")?;
+                self.send(&format!(
+                    "option name Threads type spin default 1 min 1 max {}",
+                    MAX_THREADS
+                ))?;
+                self.send("uciok")?;
+            }
+            "isready" => self.send("readyok")?,
+            "ucinewgame" => {
+                self.stop_search();
+                self.board = parse_position(&["startpos"])?;
+                self.tt.clear();
+            }
+            "position" => {
+                self.stop_search();
+                self.board = parse_position(args)?;
+            }
+            "setoption" => {
+                let text = args.join(" ");
+                let (name, value) = match text.strip_prefix("name ") {
+                    Some(rest) => match rest.split_once(" value ") {
+                        Some((name, value)) => (name.trim(), value.trim()),
+                        None => (rest.trim(), ""),
+                    },
+                    None => return Err(ChessError::ParseError("setoption needs a name".into())),
+                };
+                self.set_option(name, value)?;
+            }
+            "go" => {
+                self.stop_search();
+                self.go(GoParams::parse(args)?);
+            }
+            "stop" => self.stop_search(),
+            // the ponder search carries on, now on the clock
+            "ponderhit" => {
+                if let Some(pondering) = self.pondering.take() {
+                    pondering.store(false, Ordering::Relaxed);
+                }
+            }
+            "quit" => {
+                self.stop_search();
+                return Ok(false);
+            }
+            _ => self.send(&format!("info string unknown command {}", command))?,
+        }
+
+        Ok(true)
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), ChessError> {
+        match name.to_ascii_lowercase().as_str() {
+            "hash" => {
+                let mb = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|mb| (1..=MAX_HASH_MB).contains(mb))
+                    .ok_or_else(|| {
+                        ChessError::ParseError(format!("invalid Hash value {}", value))
+                    })?;
+                self.stop_search();
+                self.tt = Arc::new(TranspositionTable::new(mb));
+            }
+            "clear hash" => {
+                self.stop_search();
+                self.tt.clear();
+            }
+            "threads" => {
+                self.threads = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|threads| (1..=MAX_THREADS).contains(threads))
+                    .ok_or_else(|| {
+                        ChessError::ParseError(format!("invalid Threads value {}", value))
+                    })?;
+            }
+            _ => self.send(&format!("info string unknown option {}", name))?,
+        }
+        Ok(())
+    }
+
+    fn go(&mut self, params: GoParams) -> () {
+        let board = self.board.clone();
+        let limits = SearchLimits {
+            threads: self.threads,
+            ..params.limits(board.get_turn())
+        };
+        let tt = self.tt.clone();
+        let out = self.out.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        self.stop = stop.clone();
+        self.infinite = limits.infinite;
+        self.pondering = limits.pondering.clone();
+
+        self.worker = Some(thread::spawn(move || {
+            let result = search_with(&board, &limits, default_params(), &tt, &stop, |info| {
+                let _ = send(&out, &format_info(info, &board));
+            });
+
+            // in infinite mode bestmove must wait for "stop", in ponder mode for "ponderhit"
+            let pondering = || {
+                limits
+                    .pondering
+                    .as_ref()
+                    .is_some_and(|pondering| pondering.load(Ordering::Relaxed))
+            };
+            while (limits.infinite || pondering()) && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            let line = match result {
+                Ok(result) => match result.best_move {
+                    Some(best) => {
+                        let mut line = format!("bestmove {}", best.to_uci(board.geometry()));
+                        if let Some(ponder) = result.pv.get(1) {
+                            line.push_str(&format!(" ponder {}", ponder.to_uci(board.geometry())));
+                        }
+                        line
+                    }
+                    None => "bestmove 0000".to_string(),
+                },
+                Err(err) => format!("info string search failed: {}\nbestmove 0000", err),
+            };
+            let _ = send(&out, &line);
+        }));
+    }
+}
+
+fn send<W: Write>(out: &Mutex<W>, line: &str) -> Result<(), ChessError> {
+    let mut out = out
+        .lock()
+        .map_err(|_| ChessError::InternalError("uci output lock poisoned".into()))?;
+    writeln!(out, "{}", line)
+        .and_then(|_| out.flush())
+        .map_err(|err| ChessError::InternalError(format!("fail writing uci output: {}", err)))
+}
+
+pub fn run<R: BufRead, W: Write + Send + 'static>(input: R, output: W) -> Result<(), ChessError> {
+    let mut uci = Uci {
+        board: parse_position(&["startpos"])?,
+        out: Arc::new(Mutex::new(output)),
+        tt: Arc::new(TranspositionTable::default()),
+        threads: 1,
+        stop: Arc::new(AtomicBool::new(false)),
+        infinite: false,
+        pondering: None,
+        worker: None,
+    };
+
+    for line in input.lines() {
+        let line =
+            line.map_err(|err| ChessError::InternalError(format!("fail reading input: {}", err)))?;
+        match uci.handle(line.trim()) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(err) => uci.send(&format!("info string error: {}", err))?,
+        }
+    }
+
+    uci.finish_search();
+    Ok(())
+}
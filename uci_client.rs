@@ -0,0 +1,506 @@
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::board::Board;
+use super::game::START_FEN;
+use super::piece::Move;
+use super::search::Score;
+use super::uci::GoParams;
+use super::utils::ChessError;
+
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionKind {
+    Check { default: bool },
+    Spin { default: i64, min: i64, max: i64 },
+    Combo { default: String, vars: Vec<String> },
+    Button,
+    String { default: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineOption {
+    pub name: String,
+    pub kind: OptionKind,
+}
+
+impl EngineOption {
+    pub fn parse(line: &str) -> Result<Self, ChessError> {
+        let err = || ChessError::ParseError(format!("invalid option line: {}", line));
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let keywords = ["name", "type", "default", "min", "max", "var"];
+
+        // values may contain spaces, so collect everything up to the next keyword
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        for token in tokens.iter().skip(1) {
+            match keywords.contains(token) {
+                true => fields.push((token, String::new())),
+                false => {
+                    let (_, value) = fields.last_mut().ok_or_else(err)?;
+                    if !value.is_empty() {
+                        value.push(' ');
+                    }
+                    value.push_str(token);
+                }
+            }
+        }
+        let field = |key: &str| {
+            fields
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.clone())
+        };
+        let number = |key: &str| -> Result<i64, ChessError> {
+            field(key).and_then(|v| v.parse().ok()).ok_or_else(err)
+        };
+
+        let name = field("name").ok_or_else(err)?;
+        let kind = match field("type").ok_or_else(err)?.as_str() {
+            "check" => OptionKind::Check {
+                default: field("default").as_deref() == Some("true"),
+            },
+            "spin" => OptionKind::Spin {
+                default: number("default")?,
+                min: number("min")?,
+                max: number("max")?,
+            },
+            "combo" => OptionKind::Combo {
+                default: field("default").unwrap_or_default(),
+                vars: fields
+                    .iter()
+                    .filter(|(k, _)| *k == "var")
+                    .map(|(_, v)| v.clone())
+                    .collect(),
+            },
+            "button" => OptionKind::Button,
+            "string" => OptionKind::String {
+                default: field("default").unwrap_or_default(),
+            },
+            _ => return Err(err()),
+        };
+
+        Ok(Self { name, kind })
+    }
+
+    pub fn validate(&self, value: &str) -> Result<(), ChessError> {
+        let ok = match &self.kind {
+            OptionKind::Check { .. } => value == "true" || value == "false",
+            OptionKind::Spin { min, max, .. } => value
+                .parse::<i64>()
+                .is_ok_and(|v| (*min..=*max).contains(&v)),
+            OptionKind::Combo { vars, .. } => {
+                vars.iter().any(|var| var.eq_ignore_ascii_case(value))
+            }
+            OptionKind::Button => value.is_empty(),
+            OptionKind::String { .. } => true,
+        };
+        match ok {
+            true => Ok(()),
+            false => Err(ChessError::ParseError(format!(
+                "{} is not a valid value for option {}",
+                value, self.name
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreBound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfoLine {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub multipv: Option<u32>,
+    pub score: Option<Score>,
+    pub bound: ScoreBound,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    pub time: Option<Duration>,
+    pub hashfull: Option<u32>,
+    pub currmove: Option<Move>,
+    pub pv: Vec<Move>,
+    pub string: Option<String>,
+}
+
+impl InfoLine {
+    pub fn parse(line: &str, board: &Board) -> Result<Self, ChessError> {
+        let err = |what: &str| ChessError::ParseError(format!("invalid info {}: {}", what, line));
+        let mut info = InfoLine {
+            depth: None,
+            seldepth: None,
+            multipv: None,
+            score: None,
+            bound: ScoreBound::Exact,
+            nodes: None,
+            nps: None,
+            time: None,
+            hashfull: None,
+            currmove: None,
+            pv: Vec::new(),
+            string: None,
+        };
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let mut idx = 1;
+        while idx < tokens.len() {
+            let key = tokens[idx];
+            let value = tokens.get(idx + 1).copied().unwrap_or("");
+            let number = || value.parse::<u64>().map_err(|_| err(key));
+            idx += 2;
+
+            match key {
+                "depth" => info.depth = Some(number()? as u32),
+                "seldepth" => info.seldepth = Some(number()? as u32),
+                "multipv" => info.multipv = Some(number()? as u32),
+                "nodes" => info.nodes = Some(number()?),
+                "nps" => info.nps = Some(number()?),
+                "hashfull" => info.hashfull = Some(number()? as u32),
+                "time" => info.time = Some(Duration::from_millis(number()?)),
+                "currmove" => {
+                    info.currmove = Some(Move::from_uci(value, board.geometry(), board.get_turn())?)
+                }
+                "score" => {
+                    let amount = tokens
+                        .get(idx)
+                        .and_then(|v| v.parse::<i32>().ok())
+                        .ok_or_else(|| err("score"))?;
+                    info.score = Some(match value {
+                        "cp" => Score::Centipawns(amount),
+                        "mate" => Score::Mate(amount),
+                        _ => return Err(err("score")),
+                    });
+                    idx += 1;
+                    match tokens.get(idx) {
+                        Some(&"lowerbound") => info.bound = ScoreBound::Lower,
+                        Some(&"upperbound") => info.bound = ScoreBound::Upper,
+                        _ => continue,
+                    }
+                    idx += 1;
+                }
+                "pv" => {
+                    let mut position = board.clone();
+                    position.set_clock(None);
+                    for text in tokens[idx - 1..].iter() {
+                        let mv = Move::from_uci(text, position.geometry(), position.get_turn())?;
+                        position
+                            .make_move(mv.from, mv.to, mv.promotion)
+                            .map_err(|_| err("pv"))?;
+                        info.pv.push(mv);
+                    }
+                    break;
+                }
+                "string" => {
+                    info.string = Some(tokens[idx - 1..].join(" "));
+                    break;
+                }
+                // unknown keys carry one value
+                _ => {}
+            }
+        }
+
+        Ok(info)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineEvent {
+    Info(InfoLine),
+    BestMove {
+        best: Option<Move>,
+        ponder: Option<Move>,
+    },
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchOutcome {
+    pub best: Option<Move>,
+    pub ponder: Option<Move>,
+    pub infos: Vec<InfoLine>,
+}
+
+pub struct UciEngine {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    name: Option<String>,
+    author: Option<String>,
+    options: Vec<EngineOption>,
+    board: Board,
+}
+
+fn io_error(err: std::io::Error) -> ChessError {
+    ChessError::InternalError(format!("engine i/o failed: {}", err))
+}
+
+impl UciEngine {
+    pub fn spawn<S: AsRef<OsStr>>(program: S, args: &[S]) -> Result<Self, ChessError> {
+        let mut command = Command::new(program);
+        command.args(args);
+        Self::from_command(command)
+    }
+
+    pub fn from_command(mut command: Command) -> Result<Self, ChessError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(io_error)?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (send, lines) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if send.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Self {
+            child,
+            stdin,
+            lines,
+            name: None,
+            author: None,
+            options: Vec::new(),
+            board: Board::new(),
+        };
+        engine.board.from_fen(START_FEN.to_string())?;
+        engine.handshake()?;
+        Ok(engine)
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn options(&self) -> &[EngineOption] {
+        &self.options
+    }
+
+    pub fn option(&self, name: &str) -> Option<&EngineOption> {
+        self.options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn send(&mut self, command: &str) -> Result<(), ChessError> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(io_error)
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), ChessError> {
+        let option = self.option(name).ok_or_else(|| {
+            ChessError::ParseError(format!("engine has no option named {}", name))
+        })?;
+        option.validate(value)?;
+
+        let command = match option.kind {
+            OptionKind::Button => format!("setoption name {}", option.name),
+            _ => format!("setoption name {} value {}", option.name, value),
+        };
+        self.send(&command)
+    }
+
+    pub fn is_ready(&mut self, timeout: Duration) -> Result<(), ChessError> {
+        self.send("isready")?;
+        self.wait_for(timeout, |line| line == "readyok").map(|_| ())
+    }
+
+    pub fn new_game(&mut self) -> Result<(), ChessError> {
+        self.send("ucinewgame")
+    }
+
+    pub fn set_position(&mut self, start: &Board, moves: &[Move]) -> Result<(), ChessError> {
+        let mut board = start.clone();
+        board.set_clock(None);
+        let mut command = format!("position fen {}", start.generate_fen()?);
+        if !moves.is_empty() {
+            command.push_str(" moves");
+        }
+        for mv in moves {
+            board.make_move(mv.from, mv.to, mv.promotion)?;
+            command.push(' ');
+            command.push_str(&mv.to_uci(board.geometry()));
+        }
+
+        self.send(&command)?;
+        self.board = board;
+        Ok(())
+    }
+
+    pub fn go(&mut self, params: &GoParams) -> Result<(), ChessError> {
+        self.send(&params.to_command())
+    }
+
+    pub fn stop(&mut self) -> Result<(), ChessError> {
+        self.send("stop")
+    }
+
+    pub fn ponder_hit(&mut self) -> Result<(), ChessError> {
+        self.send("ponderhit")
+    }
+
+    pub fn poll_event(&mut self) -> Result<Option<EngineEvent>, ChessError> {
+        match self.lines.try_recv() {
+            Ok(line) => self.parse_event(&line).map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                Err(ChessError::InternalError("engine closed its output".into()))
+            }
+        }
+    }
+
+    pub fn next_event(&mut self, timeout: Duration) -> Result<Option<EngineEvent>, ChessError> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => self.parse_event(&line).map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(ChessError::InternalError("engine closed its output".into()))
+            }
+        }
+    }
+
+    pub fn search(
+        &mut self,
+        params: &GoParams,
+        timeout: Duration,
+    ) -> Result<SearchOutcome, ChessError> {
+        self.go(params)?;
+        let deadline = Instant::now() + timeout;
+        let mut infos = Vec::new();
+
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.next_event(left)? {
+                Some(EngineEvent::Info(info)) => infos.push(info),
+                Some(EngineEvent::BestMove { best, ponder }) => {
+                    return Ok(SearchOutcome {
+                        best,
+                        ponder,
+                        infos,
+                    })
+                }
+                Some(EngineEvent::Other(_)) => {}
+                None => {
+                    return Err(ChessError::InternalError(
+                        "engine did not answer with bestmove in time".into(),
+                    ))
+                }
+            }
+        }
+    }
+
+    pub fn quit(mut self, timeout: Duration) -> Result<(), ChessError> {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if self.child.try_wait().map_err(io_error)?.is_some() {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        self.child.kill().map_err(io_error)
+    }
+
+    fn handshake(&mut self) -> Result<(), ChessError> {
+        self.send("uci")?;
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let line = self.lines.recv_timeout(left).map_err(|_| {
+                ChessError::InternalError("engine did not finish the uci handshake".into())
+            })?;
+
+            if let Some(name) = line.strip_prefix("id name ") {
+                self.name = Some(name.trim().to_string());
+            } else if let Some(author) = line.strip_prefix("id author ") {
+                self.author = Some(author.trim().to_string());
+            } else if line.starts_with("option ") {
+                self.options.push(EngineOption::parse(&line)?);
+            } else if line.trim() == "uciok" {
+                return Ok(());
+            }
+        }
+    }
+
+    fn wait_for(
+        &mut self,
+        timeout: Duration,
+        done: impl Fn(&str) -> bool,
+    ) -> Result<String, ChessError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let line = self
+                .lines
+                .recv_timeout(left)
+                .map_err(|_| ChessError::InternalError("engine did not answer in time".into()))?;
+            if done(line.trim()) {
+                return Ok(line);
+            }
+        }
+    }
+
+    fn parse_event(&self, line: &str) -> Result<EngineEvent, ChessError> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first() {
+            Some(&"info") => InfoLine::parse(line, &self.board).map(EngineEvent::Info),
+            Some(&"bestmove") => {
+                let geometry = self.board.geometry();
+                let turn = self.board.get_turn();
+                let best = match tokens.get(1) {
+                    Some(&"0000") | Some(&"(none)") | None => None,
+                    Some(text) => Some(Move::from_uci(text, geometry, turn)?),
+                };
+
+                let mut ponder = None;
+                if let Some(best) = best {
+                    let mut after = self.board.clone();
+                    after.set_clock(None);
+                    after.make_move(best.from, best.to, best.promotion)?;
+
+                    if let (Some(&"ponder"), Some(text)) = (tokens.get(2), tokens.get(3)) {
+                        let reply = Move::from_uci(text, geometry, after.get_turn())?;
+                        after.make_move(reply.from, reply.to, reply.promotion)?;
+                        ponder = Some(reply);
+                    }
+                }
+                Ok(EngineEvent::BestMove { best, ponder })
+            }
+            _ => Ok(EngineEvent::Other(line.to_string())),
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
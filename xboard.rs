@@ -0,0 +1,433 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::board::Board;
+use super::eval::default_params;
+use super::game::{GameResult, START_FEN};
+use super::geometry::Geometry;
+use super::piece::{Color, Move};
+use super::rules::{legal_moves, outcome, Outcome};
+use super::search::{search_with, Score, SearchInfo, SearchLimits, MAX_THREADS};
+use super::tt::TranspositionTable;
+use super::uci::{GoParams, ENGINE_NAME, MAX_HASH_MB};
+use super::utils::ChessError;
+
+/// Variants announced to the GUI, with their board size and starting position.
+/// Only variants whose rules move generation follows belong here.
+pub static VARIANTS: &[(&str, Geometry, &str)] = &[("normal", Geometry::STANDARD, START_FEN)];
+
+/// Used when the GUI never sent a time control.
+pub const DEFAULT_MOVETIME: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    pub moves: u32,
+    pub base: Duration,
+    pub inc: Duration,
+}
+
+impl Level {
+    /// Parses the arguments of `level MPS BASE INC`, where BASE is minutes or
+    /// minutes:seconds and INC is (possibly fractional) seconds.
+    pub fn parse(args: &[&str]) -> Result<Self, ChessError> {
+        let err = || ChessError::ParseError(format!("invalid level: {}", args.join(" ")));
+        let [moves, base, inc] = args else {
+            return Err(err());
+        };
+
+        let (minutes, seconds) = base.split_once(':').unwrap_or((base, "0"));
+        let minutes: u64 = minutes.parse().map_err(|_| err())?;
+        let seconds: u64 = seconds.parse().map_err(|_| err())?;
+        let inc: f64 = inc.parse().map_err(|_| err())?;
+        if !inc.is_finite() || inc < 0.0 {
+            return Err(err());
+        }
+
+        Ok(Self {
+            moves: moves.parse().map_err(|_| err())?,
+            base: Duration::from_secs(minutes * 60 + seconds),
+            inc: Duration::from_secs_f64(inc),
+        })
+    }
+}
+
+pub fn format_thinking(info: &SearchInfo, board: &Board) -> String {
+    // mate scores use the 100000 + N convention
+    let score = match info.score {
+        Score::Centipawns(cp) => cp,
+        Score::Mate(moves) => moves.signum() * (100_000 + moves.abs()),
+    };
+    let pv: Vec<String> = info
+        .pv
+        .iter()
+        .map(|mv| mv.to_uci(board.geometry()))
+        .collect();
+
+    format!(
+        "{} {} {} {} {}",
+        info.depth,
+        score,
+        info.elapsed.as_millis() / 10,
+        info.nodes,
+        pv.join(" ")
+    )
+}
+
+pub fn result_line(board: &Board) -> Result<Option<String>, ChessError> {
+    let mut scratch = board.clone();
+    let Some(outcome) = outcome(&mut scratch)? else {
+        return Ok(None);
+    };
+
+    let result = match outcome.winner() {
+        Some(winner) => GameResult::win_for(winner),
+        None => GameResult::Draw,
+    };
+    let comment = match outcome {
+        Outcome::Checkmate { winner } => format!("{:?} mates", winner),
+        Outcome::Timeout { winner } => format!("{:?} forfeits on time", winner.opposite()),
+        Outcome::Stalemate => "Stalemate".into(),
+        Outcome::TimeoutVsInsufficientMaterial => "Time forfeit vs insufficient material".into(),
+        Outcome::InsufficientMaterial => "Insufficient material".into(),
+        Outcome::FiftyMoves => "Fifty move rule".into(),
+    };
+    Ok(Some(format!("{} {{{}}}", result, comment)))
+}
+
+struct Xboard<W: Write + Send + 'static> {
+    variant: usize,
+    board: Board,
+    undo: Vec<Board>,
+    out: Arc<Mutex<W>>,
+    engine: Option<Color>,
+    game_over: bool,
+    post: bool,
+    level: Option<Level>,
+    movetime: Option<Duration>,
+    depth: Option<u32>,
+    time: Option<Duration>,
+    tt: Arc<TranspositionTable>,
+    cores: usize,
+    stop: Arc<AtomicBool>,
+    discard: Arc<AtomicBool>,
+    worker: Option<JoinHandle<Option<Move>>>,
+}
+
+impl<W: Write + Send + 'static> Xboard<W> {
+    fn send(&self, line: &str) -> Result<(), ChessError> {
+        send(&self.out, line)
+    }
+
+    fn reset(&mut self) -> Result<(), ChessError> {
+        let (name, geometry, fen) = VARIANTS[self.variant];
+        let mut board = Board::with_geometry(geometry);
+        if self.variant > 0 {
+            board.set_variant(name);
+        }
+        board.from_fen(fen.to_string())?;
+
+        self.board = board;
+        self.undo.clear();
+        self.game_over = false;
+        Ok(())
+    }
+
+    /// Waits for a running search and plays the move it printed, if any.
+    fn sync(&mut self) -> Result<(), ChessError> {
+        let Some(worker) = self.worker.take() else {
+            return Ok(());
+        };
+        let mv = worker
+            .join()
+            .map_err(|_| ChessError::InternalError("search thread panicked".into()))?;
+
+        if let Some(mv) = mv {
+            self.undo.push(self.board.clone());
+            self.board.make_move(mv.from, mv.to, mv.promotion)?;
+            self.game_over = result_line(&self.board)?.is_some();
+        }
+        Ok(())
+    }
+
+    /// Stops a running search. Unless `play` is set its move is thrown away.
+    fn halt(&mut self, play: bool) -> Result<(), ChessError> {
+        self.discard.store(!play, Ordering::Relaxed);
+        self.stop.store(true, Ordering::Relaxed);
+        self.sync()
+    }
+
+    fn handle(&mut self, line: &str) -> Result<bool, ChessError> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = tokens.split_first() else {
+            return Ok(true);
+        };
+        let number = || -> Result<u64, ChessError> {
+            args.first()
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| ChessError::ParseError(format!("{} needs a number", command)))
+        };
+
+        match command {
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer"
+            | "name" | "rating" | "ics" | "draw" | "otim" => {}
+            "protover" => {
+                let variants: Vec<&str> = VARIANTS.iter().map(|(name, ..)| *name).collect();
+                self.send(&format!(
+                    "feature myname=\"{}\" ping=1 setboard=1 usermove=1 playother=1 san=0 \
+                     time=1 draw=0 sigint=0 sigterm=0 reuse=1 analyze=0 colors=0 name=0 \
+                     memory=1 smp=1 variants=\"{}\"",
+                    ENGINE_NAME,
+                    variants.join(",")
+                ))?;
+                self.send("feature done=1")?;
+            }
+            "new" => {
+                self.halt(false)?;
+                self.variant = 0;
+                self.reset()?;
+                self.tt.clear();
+                self.engine = Some(Color::Black);
+                self.depth = None;
+            }
+            "variant" => {
+                self.halt(false)?;
+                let name = args.first().copied().unwrap_or_default();
+                self.variant = VARIANTS
+                    .iter()
+                    .position(|(variant, ..)| *variant == name)
+                    .ok_or_else(|| {
+                        ChessError::ParseError(format!("unsupported variant {}", name))
+                    })?;
+                self.reset()?;
+            }
+            "force" => {
+                self.halt(false)?;
+                self.engine = None;
+            }
+            "go" => {
+                self.sync()?;
+                self.engine = Some(self.board.get_turn());
+                self.think()?;
+            }
+            "playother" => {
+                self.sync()?;
+                self.engine = Some(self.board.get_turn().opposite());
+            }
+            "usermove" => {
+                self.halt(false)?;
+                self.user_move(args.first().copied().unwrap_or_default())?;
+            }
+            "?" => self.halt(true)?,
+            "ping" => {
+                self.sync()?;
+                self.send(&format!("pong {}", args.join(" ")))?;
+            }
+            "level" => {
+                self.level = Some(Level::parse(args)?);
+                self.movetime = None;
+            }
+            "st" => self.movetime = Some(Duration::from_secs(number()?)),
+            "sd" => self.depth = Some(number()? as u32),
+            "time" => self.time = Some(Duration::from_millis(number()? * 10)),
+            "memory" => {
+                self.halt(false)?;
+                let mb = (number()? as usize).clamp(1, MAX_HASH_MB);
+                self.tt = Arc::new(TranspositionTable::new(mb));
+            }
+            "cores" => self.cores = (number()? as usize).clamp(1, MAX_THREADS),
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "setboard" => {
+                self.halt(false)?;
+                let (_, geometry, _) = VARIANTS[self.variant];
+                let mut board = Board::with_geometry(geometry);
+                board.set_variant(self.board.get_variant().to_string());
+                match board.from_fen(args.join(" ")) {
+                    Ok(()) => {
+                        self.board = board;
+                        self.undo.clear();
+                        self.game_over = result_line(&self.board)?.is_some();
+                    }
+                    Err(_) => self.send("tellusererror Illegal position")?,
+                }
+            }
+            "undo" | "remove" => {
+                self.halt(false)?;
+                let plies = if command == "undo" { 1 } else { 2 };
+                for _ in 0..plies {
+                    if let Some(board) = self.undo.pop() {
+                        self.board = board;
+                        self.game_over = false;
+                    }
+                }
+            }
+            "result" => {
+                self.halt(false)?;
+                self.game_over = true;
+            }
+            "quit" => {
+                self.halt(false)?;
+                return Ok(false);
+            }
+            _ => match self.is_move(command) {
+                // protocol version 1 GUIs send bare moves
+                true => {
+                    self.halt(false)?;
+                    self.user_move(command)?;
+                }
+                false => self.send(&format!("Error (unknown command): {}", command))?,
+            },
+        }
+
+        Ok(true)
+    }
+
+    fn is_move(&self, text: &str) -> bool {
+        Move::from_uci(text, self.board.geometry(), self.board.get_turn()).is_ok()
+    }
+
+    fn user_move(&mut self, text: &str) -> Result<(), ChessError> {
+        let color = self.board.get_turn();
+        let mut scratch = self.board.clone();
+        let legal = match Move::from_uci(text, self.board.geometry(), color) {
+            Ok(mv) if !self.game_over && legal_moves(&mut scratch, color)?.contains(&mv) => {
+                Some(mv)
+            }
+            _ => None,
+        };
+        let Some(mv) = legal else {
+            return self.send(&format!("Illegal move: {}", text));
+        };
+
+        self.undo.push(self.board.clone());
+        self.board.make_move(mv.from, mv.to, mv.promotion)?;
+        if let Some(result) = result_line(&self.board)? {
+            self.game_over = true;
+            return self.send(&result);
+        }
+        self.think()
+    }
+
+    fn limits(&self, color: Color) -> SearchLimits {
+        if self.movetime.is_some() || self.level.is_none() && self.time.is_none() {
+            return SearchLimits {
+                depth: self.depth,
+                movetime: self.movetime.or(Some(DEFAULT_MOVETIME)),
+                ..SearchLimits::default()
+            };
+        }
+
+        let level = self.level.unwrap_or(Level {
+            moves: 0,
+            base: Duration::ZERO,
+            inc: Duration::ZERO,
+        });
+        let time = self.time.unwrap_or(level.base);
+        let played = (self.undo.len() / 2) as u32;
+        let params = GoParams {
+            depth: self.depth,
+            wtime: Some(time),
+            btime: Some(time),
+            winc: Some(level.inc),
+            binc: Some(level.inc),
+            movestogo: (level.moves > 0).then(|| level.moves - played % level.moves),
+            ..GoParams::default()
+        };
+        params.limits(color)
+    }
+
+    fn think(&mut self) -> Result<(), ChessError> {
+        let color = self.board.get_turn();
+        if self.game_over || self.worker.is_some() || self.engine != Some(color) {
+            return Ok(());
+        }
+
+        let board = self.board.clone();
+        let limits = SearchLimits {
+            threads: self.cores,
+            ..self.limits(color)
+        };
+        let post = self.post;
+        let tt = self.tt.clone();
+        let out = self.out.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let discard = Arc::new(AtomicBool::new(false));
+        self.stop = stop.clone();
+        self.discard = discard.clone();
+
+        self.worker = Some(thread::spawn(move || {
+            let result = search_with(&board, &limits, default_params(), &tt, &stop, |info| {
+                if post {
+                    let _ = send(&out, &format_thinking(info, &board));
+                }
+            });
+            if discard.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            let best = match result {
+                Ok(result) => result.best_move?,
+                Err(err) => {
+                    let _ = send(&out, &format!("Error (search failed): {}", err));
+                    return None;
+                }
+            };
+            let _ = send(&out, &format!("move {}", best.to_uci(board.geometry())));
+
+            let mut after = board.clone();
+            after.make_move(best.from, best.to, best.promotion).ok()?;
+            if let Ok(Some(result)) = result_line(&after) {
+                let _ = send(&out, &result);
+            }
+            Some(best)
+        }));
+        Ok(())
+    }
+}
+
+fn send<W: Write>(out: &Mutex<W>, line: &str) -> Result<(), ChessError> {
+    let mut out = out
+        .lock()
+        .map_err(|_| ChessError::InternalError("xboard output lock poisoned".into()))?;
+    writeln!(out, "{}", line)
+        .and_then(|_| out.flush())
+        .map_err(|err| ChessError::InternalError(format!("fail writing xboard output: {}", err)))
+}
+
+pub fn run<R: BufRead, W: Write + Send + 'static>(input: R, output: W) -> Result<(), ChessError> {
+    let mut xboard = Xboard {
+        variant: 0,
+        board: Board::new(),
+        undo: Vec::new(),
+        out: Arc::new(Mutex::new(output)),
+        engine: Some(Color::Black),
+        game_over: false,
+        post: false,
+        level: None,
+        movetime: None,
+        depth: None,
+        time: None,
+        tt: Arc::new(TranspositionTable::default()),
+        cores: 1,
+        stop: Arc::new(AtomicBool::new(false)),
+        discard: Arc::new(AtomicBool::new(false)),
+        worker: None,
+    };
+    xboard.reset()?;
+
+    for line in input.lines() {
+        let line =
+            line.map_err(|err| ChessError::InternalError(format!("fail reading input: {}", err)))?;
+        match xboard.handle(line.trim()) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(err) => xboard.send(&format!("Error ({}): {}", err, line.trim()))?,
+        }
+    }
+
+    xboard.halt(false)?;
+    Ok(())
+}
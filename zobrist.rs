@@ -0,0 +1,90 @@
+use std::sync::OnceLock;
+
+use super::board::Board;
+use super::encoding::piece_code;
+use super::geometry::{MAX_FILES, MAX_SQUARES};
+use super::piece::{Color, Piece};
+
+const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+struct Keys {
+    pieces: Vec<u64>,
+    black_to_move: u64,
+    castling: [u64; 4],
+    passant_file: [u64; MAX_FILES],
+}
+
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(SEED);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn keys() -> &'static Keys {
+    static KEYS: OnceLock<Keys> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        let mut state = 0;
+        let mut next = || splitmix64(&mut state);
+        Keys {
+            // indexed by piece code (0..16) and square
+            pieces: (0..16 * MAX_SQUARES).map(|_| next()).collect(),
+            black_to_move: next(),
+            castling: std::array::from_fn(|_| next()),
+            passant_file: std::array::from_fn(|_| next()),
+        }
+    })
+}
+
+const PIECES: [fn(Color) -> Piece; 6] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+    Piece::King,
+];
+
+impl Board {
+    /// Zobrist hash of the position: pieces, side to move, castling rights and
+    /// the en passant file. Move counters are left out.
+    pub fn zobrist_hash(&self) -> u64 {
+        let keys = keys();
+        let mut hash = 0;
+
+        for color in [Color::White, Color::Black] {
+            for piece in PIECES.map(|piece| piece(color)) {
+                let base = piece_code(piece) as usize * MAX_SQUARES;
+                let mut bitboard = self.bitboard(piece);
+                while bitboard != 0 {
+                    hash ^= keys.pieces[base + bitboard.trailing_zeros() as usize];
+                    bitboard &= bitboard - 1;
+                }
+            }
+        }
+
+        if self.get_turn() == Color::Black {
+            hash ^= keys.black_to_move;
+        }
+        let castling = self.get_castling();
+        for (idx, right) in [
+            castling.white_king,
+            castling.white_queen,
+            castling.black_king,
+            castling.black_queen,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if right {
+                hash ^= keys.castling[idx];
+            }
+        }
+        if let Some(target) = self.get_passant_target() {
+            hash ^= keys.passant_file[target.file];
+        }
+
+        hash
+    }
+}